@@ -11,11 +11,13 @@
 # the License.
 """Test qoqo mocked backend"""
 
+import numpy as np
+import os
 import pytest
 import sys
 from qoqo import operations as ops
-from qoqo import Circuit
-from qoqo_iqm.devices import DenebDevice
+from qoqo import Circuit, measurements
+from qoqo_iqm.devices import DenebDevice, GarnetDevice
 import qoqo_iqm
 
 
@@ -32,5 +34,375 @@ def test_mocked_backend():
     _backend = qoqo_iqm.Backend(device, "")
 
 
+
+def test_invalid_circuit_raises_value_error():
+    """Test that invalid circuits raise a ValueError"""
+    device = DenebDevice()
+    backend = qoqo_iqm.Backend(device, "DUMMY_ACCESS_TOKEN")
+
+    with pytest.raises(ValueError):
+        backend.run_circuit(Circuit())
+
+    circuit = Circuit()
+    circuit += ops.RotateXY(2, 1.0, 0.0)
+    circuit += ops.DefinitionBit(name="ro", length=2, is_output=True)
+    circuit += ops.PragmaRepeatedMeasurement("ro", 10, None)
+    with pytest.raises(ValueError):
+        backend.run_circuit(circuit)
+
+
+def test_network_error_raises_connection_error():
+    """Test that failing requests raise a ConnectionError"""
+    device = DenebDevice()
+    device.set_endpoint_url("http://localhost:1/deneb/jobs")
+    backend = qoqo_iqm.Backend(device, "DUMMY_ACCESS_TOKEN")
+
+    circuit = Circuit()
+    circuit += ops.RotateXY(0, 1.0, 0.0)
+    circuit += ops.DefinitionBit(name="ro", length=1, is_output=True)
+    circuit += ops.MeasureQubit(0, "ro", 0)
+    with pytest.raises(ConnectionError):
+        backend.run_circuit(circuit)
+    with pytest.raises(ConnectionError):
+        backend.abort_job("DUMMY_ID")
+    with pytest.raises(ConnectionError):
+        backend.ping()
+
+
+
+def test_invalid_measurement_names_failing_circuit():
+    """Test that invalid measurement circuits raise a ValueError naming the circuit"""
+    device = GarnetDevice()
+    device.set_endpoint_url("http://localhost:1/garnet/jobs")
+    backend = qoqo_iqm.Backend(device, "DUMMY_ACCESS_TOKEN")
+
+    constant_circuit = Circuit()
+    constant_circuit += ops.DefinitionBit(name="ro", length=1, is_output=True)
+    valid_circuit = Circuit()
+    valid_circuit += ops.RotateXY(0, 1.0, 0.0)
+    valid_circuit += ops.MeasureQubit(0, "ro", 0)
+    invalid_circuit = Circuit()
+    invalid_circuit += ops.RotateXY(25, 1.0, 0.0)
+    invalid_circuit += ops.MeasureQubit(0, "ro", 0)
+    measurement = measurements.ClassicalRegister(
+        constant_circuit=constant_circuit, circuits=[valid_circuit, invalid_circuit]
+    )
+
+    with pytest.raises(ValueError, match="Circuit 1 of the measurement is invalid"):
+        backend.run_measurement(measurement)
+    with pytest.raises(ValueError, match="Circuit 1 of the measurement is invalid"):
+        backend.submit_measurement(measurement)
+
+
+def test_estimate_cost():
+    """Test the cost estimate of a circuit batch"""
+    device = GarnetDevice()
+    backend = qoqo_iqm.Backend(device, "DUMMY_ACCESS_TOKEN")
+    backend.set_cost_model(0.5, 0.1)
+
+    circuit = Circuit()
+    circuit += ops.DefinitionBit(name="ro", length=20, is_output=True)
+    circuit += ops.RotateXY(0, 1.0, 0.0)
+    circuit += ops.RotateXY(1, 1.0, 0.0)
+    circuit += ops.ControlledPauliZ(0, 1)
+    circuit += ops.PragmaRepeatedMeasurement("ro", 100, None)
+
+    assert backend.estimate_cost([circuit, circuit]) == pytest.approx(160.0)
+
+
+
+def test_set_timeout():
+    """Test setting the timeout for polling the results"""
+    device = GarnetDevice()
+    backend = qoqo_iqm.Backend(device, "DUMMY_ACCESS_TOKEN")
+    backend.set_timeout(3600.0)
+    with pytest.raises(ValueError):
+        backend.set_timeout(-1.0)
+
+
+def test_set_polling_interval():
+    """Test setting the interval between queries of the results"""
+    device = GarnetDevice()
+    backend = qoqo_iqm.Backend(device, "DUMMY_ACCESS_TOKEN")
+    backend.set_polling_interval(0.5)
+    with pytest.raises(ValueError):
+        backend.set_polling_interval(0.0)
+
+
+def test_set_endpoint_paths():
+    """Test setting the templates of the endpoint URLs"""
+    device = GarnetDevice()
+    backend = qoqo_iqm.Backend(device, "DUMMY_ACCESS_TOKEN")
+    backend.set_endpoint_paths(
+        "{base_url}/job-status/{id}", "{jobs_url}/{id}/cancel", "{base_url}/architecture"
+    )
+    backend.set_endpoint_paths(
+        "{base_url}/job-status/{id}",
+        "{jobs_url}/{id}/cancel",
+        "{base_url}/architecture",
+        calibration_metrics="{base_url}/calibration/latest-metrics",
+    )
+
+
+def test_set_heralding_mode():
+    """Test setting the heralding mode of the jobs"""
+    device = GarnetDevice()
+    backend = qoqo_iqm.Backend(device, "DUMMY_ACCESS_TOKEN")
+    backend.set_heralding_mode("zeros")
+    backend.set_heralding_mode("none")
+    with pytest.raises(ValueError):
+        backend.set_heralding_mode("ones")
+
+
+def test_set_qubit_mapping():
+    """Test setting the mapping of the logical qubits onto the physical qubits"""
+    device = GarnetDevice()
+    backend = qoqo_iqm.Backend(device, "DUMMY_ACCESS_TOKEN")
+    backend.set_qubit_mapping({0: "QB3", 1: "QB4"})
+    backend.set_qubit_mapping({})
+
+
+def test_last_warnings():
+    """Test reading the warnings of the last job"""
+    device = GarnetDevice()
+    backend = qoqo_iqm.Backend(device, "DUMMY_ACCESS_TOKEN")
+    backend.set_print_warnings(False)
+    assert backend.get_last_warnings() is None
+    assert backend.get_submission_warnings() is None
+
+
+def test_token_source():
+    """Test reporting the source of the access token"""
+    device = GarnetDevice()
+    backend = qoqo_iqm.Backend(device, "DUMMY_ACCESS_TOKEN")
+    assert backend.token_source() == "argument"
+
+    if "IQM_TOKEN" in os.environ:
+        backend = qoqo_iqm.Backend(device)
+        assert backend.token_source() == "IQM_TOKEN"
+
+
+def test_set_job_priority():
+    """Test setting the priority tier of the jobs"""
+    device = GarnetDevice()
+    backend = qoqo_iqm.Backend(device, "DUMMY_ACCESS_TOKEN")
+    backend.set_job_priority("high")
+    backend.set_job_priority(None)
+
+
+def test_set_refresh_credentials():
+    """Test setting the credentials for refreshing the access token"""
+    device = GarnetDevice()
+    backend = qoqo_iqm.Backend(device, "DUMMY_ACCESS_TOKEN")
+    backend.set_refresh_credentials("DUMMY_REFRESH_TOKEN", "https://auth.example.com")
+
+
+def test_set_empty_result_policy():
+    """Test setting the treatment of results without measurements"""
+    device = GarnetDevice()
+    backend = qoqo_iqm.Backend(device, "DUMMY_ACCESS_TOKEN")
+
+    backend.set_empty_result_policy("error")
+    backend.set_empty_result_policy("retry_then_error")
+    backend.set_empty_result_policy("return_empty")
+    with pytest.raises(ValueError):
+        backend.set_empty_result_policy("ignore")
+
+
+def test_effective_shots():
+    """Test the number of measurements used when submitting a circuit"""
+    device = GarnetDevice()
+    backend = qoqo_iqm.Backend(device, "DUMMY_ACCESS_TOKEN")
+
+    circuit = Circuit()
+    circuit += ops.DefinitionBit(name="ro", length=20, is_output=True)
+    circuit += ops.PragmaRepeatedMeasurement("ro", 100, None)
+
+    assert backend.effective_shots(circuit) == 100
+    backend._overwrite_number_of_measurements(10)
+    assert backend.effective_shots(circuit) == 10
+
+
+def test_repeated_measurement_register_qubits():
+    """Test measuring only the register-length qubits with PragmaRepeatedMeasurement"""
+    device = DenebDevice()
+    backend = qoqo_iqm.Backend(device, "DUMMY_ACCESS_TOKEN")
+
+    circuit = Circuit()
+    circuit += ops.RotateXY(0, 1.0, 0.0)
+    circuit += ops.DefinitionBit(name="ro", length=2, is_output=True)
+    circuit += ops.PragmaRepeatedMeasurement("ro", 10, None)
+    with pytest.raises(ValueError):
+        backend.measured_qubit_counts(circuit)
+
+    backend.set_repeated_measurement_register_qubits(True)
+    assert backend.measured_qubit_counts(circuit) == {"ro": 2}
+
+
+def test_wait_for_status_change_unknown_status():
+    """Test that an unknown job status raises a ValueError"""
+    device = GarnetDevice()
+    backend = qoqo_iqm.Backend(device, "DUMMY_ACCESS_TOKEN")
+
+    with pytest.raises(ValueError):
+        backend.wait_for_status_change("DUMMY_ID", "compiling")
+
+
+def test_set_validation_level():
+    """Test setting the validation level of the backend"""
+    device = GarnetDevice()
+    backend = qoqo_iqm.Backend(device, "DUMMY_ACCESS_TOKEN")
+
+    backend.set_validation_level("off")
+    backend.set_validation_level("connectivity")
+    backend.set_validation_level("full")
+    with pytest.raises(ValueError):
+        backend.set_validation_level("strict")
+
+
+@pytest.mark.skipif("IQM_TOKEN" not in os.environ, reason="IQM_TOKEN is not set")
+def test_run_circuit_return_warnings():
+    """Test returning the warnings of the device from run_circuit"""
+    device = GarnetDevice()
+    backend = qoqo_iqm.Backend(device)
+
+    circuit = Circuit()
+    circuit += ops.DefinitionBit(name="ro", length=1, is_output=True)
+    circuit += ops.RotateXY(0, 1.0, 0.0)
+    circuit += ops.PragmaSetNumberOfMeasurements(10, "ro")
+    circuit += ops.MeasureQubit(0, "ro", 0)
+
+    (bit_registers, _, _), warnings = backend.run_circuit(circuit, return_warnings=True)
+    assert len(bit_registers["ro"]) == 10
+    assert isinstance(warnings, list)
+
+    (bit_registers, _, _) = backend.run_circuit(circuit)
+    assert len(bit_registers["ro"]) == 10
+
+
+
+@pytest.mark.skipif("IQM_TOKEN" not in os.environ, reason="IQM_TOKEN is not set")
+def test_run_circuit_counts():
+    """Test the histogram of the measured bitstrings returned by run_circuit_counts"""
+    device = GarnetDevice()
+    backend = qoqo_iqm.Backend(device)
+
+    circuit = Circuit()
+    circuit += ops.DefinitionBit(name="ro", length=2, is_output=True)
+    circuit += ops.RotateXY(0, 1.0, 0.0)
+    circuit += ops.PragmaSetNumberOfMeasurements(100, "ro")
+    circuit += ops.MeasureQubit(0, "ro", 0)
+    circuit += ops.MeasureQubit(1, "ro", 1)
+
+    counts = backend.run_circuit_counts(circuit, "ro")
+    assert sum(counts.values()) == 100
+    assert all(len(bitstring) == 2 for bitstring in counts)
+
+    # Qubit 1 is never flipped, so it is the last character by default and the first one in the
+    # big-endian ordering
+    assert all(bitstring[1] == "0" for bitstring in counts)
+    counts = backend.run_circuit_counts(circuit, "ro", big_endian=True)
+    assert sum(counts.values()) == 100
+    assert all(bitstring[0] == "0" for bitstring in counts)
+
+
+def test_to_qiskit_counts():
+    """Test the conversion of results into Qiskit-style counts."""
+    registers = (
+        {"ro": [[True, False, False], [True, False, False], [False, False, True]]},
+        {},
+        {},
+    )
+    # Qiskit is little-endian: bit 0 of the register is the rightmost character
+    counts = qoqo_iqm.to_qiskit_counts(registers, "ro")
+    assert counts == {"001": 2, "100": 1}
+
+    with pytest.raises(KeyError):
+        qoqo_iqm.to_qiskit_counts(registers, "missing")
+
+
+def test_results_to_csv():
+    """Test the export of results as CSV."""
+    registers = ({"ro": [[True, False], [False, True]]}, {}, {})
+    csv = qoqo_iqm.results_to_csv(registers, "ro")
+    assert csv.splitlines() == ["shot,bit_0,bit_1", "0,1,0", "1,0,1"]
+
+    with pytest.raises(KeyError):
+        qoqo_iqm.results_to_csv(registers, "missing")
+
+
+def test_qubit_statistics():
+    """Test the per-qubit statistics of a bit register."""
+    registers = ({"ro": [[True, False], [True, True], [True, False], [True, False]]}, {}, {})
+    statistics = qoqo_iqm.qubit_statistics(registers, "ro")
+    assert statistics == [
+        {"qubit": 0, "ones": 4, "zeros": 0, "mean": 1.0},
+        {"qubit": 1, "ones": 1, "zeros": 3, "mean": 0.25},
+    ]
+
+    with pytest.raises(KeyError):
+        qoqo_iqm.qubit_statistics(registers, "missing")
+
+
+def test_add_basis_measurement():
+    """Test the rotations added for measurements in the X and Y bases."""
+    circuit = Circuit()
+    circuit += ops.DefinitionBit(name="ro", length=3, is_output=True)
+
+    x_circuit = qoqo_iqm.add_basis_measurement(circuit, 1, "X", "ro", 2)
+    expected = Circuit()
+    expected += ops.DefinitionBit(name="ro", length=3, is_output=True)
+    expected += ops.RotateXY(1, -np.pi / 2, np.pi / 2)
+    expected += ops.MeasureQubit(1, "ro", 2)
+    assert x_circuit == expected
+
+    y_circuit = qoqo_iqm.add_basis_measurement(circuit, 1, "Y", "ro", 2)
+    expected = Circuit()
+    expected += ops.DefinitionBit(name="ro", length=3, is_output=True)
+    expected += ops.RotateXY(1, np.pi / 2, 0.0)
+    expected += ops.MeasureQubit(1, "ro", 2)
+    assert y_circuit == expected
+
+    z_circuit = qoqo_iqm.add_basis_measurement(circuit, 1, "Z", "ro", 2)
+    expected = Circuit()
+    expected += ops.DefinitionBit(name="ro", length=3, is_output=True)
+    expected += ops.MeasureQubit(1, "ro", 2)
+    assert z_circuit == expected
+
+    with pytest.raises(ValueError):
+        qoqo_iqm.add_basis_measurement(circuit, 1, "W", "ro", 2)
+
+
+def test_add_partial_measurement():
+    """Test the measurement of a subset of qubits."""
+    circuit = Circuit()
+    circuit += ops.RotateXY(0, np.pi, 0.0)
+
+    measured = qoqo_iqm.add_partial_measurement(circuit, [3, 1], "ro")
+    expected = Circuit()
+    expected += ops.RotateXY(0, np.pi, 0.0)
+    expected += ops.DefinitionBit(name="ro", length=2, is_output=True)
+    expected += ops.MeasureQubit(3, "ro", 0)
+    expected += ops.MeasureQubit(1, "ro", 1)
+    assert measured == expected
+
+
+def test_analyze_circuit():
+    """Test the analysis of a circuit with an error and a warning."""
+    circuit = Circuit()
+    circuit += ops.DefinitionBit(name="ro", length=1, is_output=True)
+    circuit += ops.RotateXY(1, np.pi, 0.0)
+    circuit += ops.RotateXY(5, np.pi, 0.0)
+    circuit += ops.ControlledPauliZ(1, 5)
+    circuit += ops.MeasureQubit(1, "ro", 0)
+
+    backend = qoqo_iqm.Backend(GarnetDevice(), "")
+    analysis = backend.analyze_circuit(circuit)
+    assert len(analysis["errors"]) == 1
+    assert analysis["warnings"] == ["Qubit 5 is acted on by gates but never measured."]
+    assert analysis["qubit_count"] == 2
+    assert analysis["gate_count"] == 3
+
+
 if __name__ == "__main__":
     pytest.main(sys.argv)
@@ -0,0 +1,70 @@
+# Copyright © 2020-2023 HQS Quantum Simulations GmbH. All Rights Reserved.
+#
+# Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+# in compliance with the License. You may obtain a copy of the License at
+#
+#     http://www.apache.org/licenses/LICENSE-2.0
+#
+# Unless required by applicable law or agreed to in writing, software distributed under the License
+# is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express
+# or implied. See the License for the specific language governing permissions and limitations under
+# the License.
+"""Test qoqo-iqm devices"""
+import pytest
+import sys
+from qoqo import operations as ops
+from qoqo import Circuit
+import qoqo_iqm
+from qoqo_iqm.devices import DenebDevice, GarnetDevice, ResonatorFreeDevice
+
+
+def test_deneb_validate_circuit():
+    """Test the validation of circuits without a backend"""
+    device = DenebDevice()
+
+    circuit = Circuit()
+    circuit += ops.CZQubitResonator(0, 0)
+    circuit += ops.DefinitionBit(name="ro", length=1, is_output=True)
+    circuit += ops.MeasureQubit(0, "ro", 0)
+    device.validate_circuit(circuit)
+
+    # Qubits are only coupled through the resonator
+    circuit = Circuit()
+    circuit += ops.ControlledPauliZ(0, 1)
+    with pytest.raises(ValueError):
+        device.validate_circuit(circuit)
+
+
+def test_garnet_device():
+    """Test the GarnetDevice and running it with a Backend"""
+    device = GarnetDevice()
+    assert device.number_qubits() == 20
+    assert device.remote_host() == "https://cocos.resonance.meetiqm.com/garnet/jobs"
+    assert (1, 2) in device.two_qubit_edges()
+
+    assert device.single_qubit_gate_time("RotateXY", 0) == 1.0
+    assert device.two_qubit_gate_time("ControlledPauliZ", 1, 2) == 1.0
+    assert device.single_qubit_gate_time("RotateZ", 0) == 0.0
+    with pytest.raises(ValueError):
+        device.single_qubit_gate_time("RotateX", 0)
+    with pytest.raises(ValueError):
+        device.two_qubit_gate_time("ControlledPauliZ", 0, 19)
+
+    device.set_endpoint_url("https://example.com/garnet/jobs")
+    assert device.remote_host() == "https://example.com/garnet/jobs"
+    copied = GarnetDevice.from_bincode(device.to_bincode())
+    assert copied.remote_host() == "https://example.com/garnet/jobs"
+
+    backend = qoqo_iqm.Backend(device, "DUMMY_ACCESS_TOKEN")
+    assert backend.remote_host() == "https://example.com/garnet/jobs"
+
+
+def test_native_two_qubit_gate():
+    """Test the native two-qubit gate of each device"""
+    assert GarnetDevice().native_two_qubit_gate() == "ControlledPauliZ"
+    assert DenebDevice().native_two_qubit_gate() == "CZQubitResonator"
+    assert ResonatorFreeDevice().native_two_qubit_gate() == "ControlledPauliZ"
+
+
+if __name__ == "__main__":
+    pytest.main(sys.argv)
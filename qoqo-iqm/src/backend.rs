@@ -10,25 +10,34 @@
 // express or implied. See the License for the specific language governing permissions and
 // limitations under the License.
 
-use pyo3::exceptions::{PyConnectionError, PyRuntimeError, PyTypeError, PyValueError};
+use pyo3::exceptions::{
+    PyConnectionError, PyKeyError, PyPermissionError, PyRuntimeError, PyTimeoutError, PyTypeError,
+    PyValueError,
+};
 use pyo3::prelude::*;
-use pyo3::types::PyByteArray;
+use pyo3::types::{PyByteArray, PyDict};
 
 use crate::devices::*;
+use crate::measurement_basis::parse_pauli_basis;
+use qoqo::devices::GenericDeviceWrapper;
 use qoqo::{convert_into_circuit, CircuitWrapper};
-use roqoqo::prelude::*;
 use roqoqo::registers::Registers;
-use roqoqo::Circuit;
-use roqoqo_iqm::{results_to_registers, Backend, IqmDevice};
+use roqoqo::{Circuit, RoqoqoBackendError};
+use roqoqo_iqm::{
+    bit_register_to_counts, registers_to_csv, results_to_counts, results_to_registers, Backend,
+    CostModel, EmptyResultPolicy, EndpointPaths, HeraldingMode, IqmBackendError, IqmDevice,
+    RequestFormat, RetryPolicy, Status, TokenSource, ValidationLevel,
+};
 
 use bincode::{deserialize, serialize};
 use std::collections::HashMap;
+use std::time::Duration;
 
 /// IQM backend
 ///
 /// Provides functions to run circuits and measurements on IQM devices.
 #[pyclass(name = "Backend", module = "qoqo_iqm")]
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct BackendWrapper {
     /// Internal storage of [roqoqo_iqm::Backend]
     pub internal: Backend,
@@ -81,34 +90,49 @@ impl BackendWrapper {
 impl BackendWrapper {
     /// Create a new IQM Backend.
     ///
+    /// If the environment variable `IQM_ENDPOINT_URL` is set, it replaces the default endpoint URL
+    /// of the device. An endpoint URL set explicitly with `set_endpoint_url` on the device takes
+    /// precedence over the environment variable.
+    ///
     /// Args:
     ///     device (Device): IQM Device providing information about the endpoint running Circuits.
     ///     access_token (Optional[str]): Optional access token to IQM endpoints.
-    ///                                   When None access token is read from $IQM_TOKEN environmental variable
+    ///                                   When None access token is read from $IQM_TOKEN environmental variable,
+    ///                                   or from the tokens file $IQM_TOKENS_FILE points to
     ///
     /// Raises:
     ///     TypeError: Device Parameter is not IqmDevice
-    ///     RuntimeError: No access token found
+    ///     PermissionError: No access token found, or the tokens file could not be read
     #[pyo3(text_signature = "(device, access_token)")]
     #[new]
     pub fn new(device: &Bound<PyAny>, access_token: Option<String>) -> PyResult<Self> {
-        let iqm_device: IqmDevice;
-        let device_pyany = device.as_gil_ref();
-        if let Ok(dev) = DenebDeviceWrapper::from_pyany(device_pyany.into()) {
-            iqm_device = IqmDevice::from(dev);
-        } else if let Ok(dev) = GarnetDeviceWrapper::from_pyany(device_pyany.into()) {
-            iqm_device = IqmDevice::from(dev);
-        } else if let Ok(dev) = ResonatorFreeDeviceWrapper::from_pyany(device_pyany.into()) {
-            iqm_device = IqmDevice::from(dev);
-        } else {
-            return Err(PyRuntimeError::new_err(
-                "Could not convert input device to one of the available devices.".to_string(),
-            ));
-        }
+        let iqm_device = extract_iqm_device(device)?;
         Ok(Self {
-            internal: Backend::new(iqm_device, access_token).map_err(|err| {
-                PyRuntimeError::new_err(format!("No access token found {:?}", err))
-            })?,
+            internal: Backend::new(iqm_device, access_token).map_err(roqoqo_error_to_pyerr)?,
+        })
+    }
+
+    /// Create a new IQM backend with the access token of a named profile.
+    ///
+    /// The profile is read from the credentials file `~/.config/qoqo_iqm/credentials`, which
+    /// contains a section per profile with a `token` and, optionally, an `endpoint` URL.
+    ///
+    /// Args:
+    ///     device (Device): IQM Device providing information about the endpoint running Circuits.
+    ///     profile_name (str): The name of the profile in the credentials file.
+    ///
+    /// Returns:
+    ///     Backend: The newly created IQM backend.
+    ///
+    /// Raises:
+    ///     RuntimeError: Device Parameter is not IqmDevice
+    ///     PermissionError: No access token found for the profile
+    #[staticmethod]
+    pub fn new_with_profile(device: &Bound<PyAny>, profile_name: &str) -> PyResult<Self> {
+        let iqm_device = extract_iqm_device(device)?;
+        Ok(Self {
+            internal: Backend::new_with_profile(iqm_device, profile_name)
+                .map_err(roqoqo_error_to_pyerr)?,
         })
     }
 
@@ -144,53 +168,850 @@ impl BackendWrapper {
     /// Return the bincode representation of the Backend using the [bincode] crate.
     ///
     /// Returns:
-    ///     ByteArray: The serialized Backend (in [bincode] form).
+    ///     ByteArray: The serialized Backend (in [bincode] form).
+    ///
+    /// Raises:
+    ///     ValueError: Cannot serialize Backend to bytes.
+    pub fn to_bincode(&self) -> PyResult<Py<PyByteArray>> {
+        let serialized = serialize(&self.internal)
+            .map_err(|_| PyValueError::new_err("Cannot serialize Backend to bytes"))?;
+        let b: Py<PyByteArray> = Python::with_gil(|py| -> Py<PyByteArray> {
+            PyByteArray::new_bound(py, &serialized[..]).into()
+        });
+        Ok(b)
+    }
+
+    /// Convert the bincode representation of the Backend to a Backend using the [bincode] crate.
+    ///
+    /// Args:
+    ///     input (ByteArray): The serialized Backend (in [bincode] form).
+    ///
+    /// Returns:
+    ///     Backend: The deserialized Backend.
+    ///
+    /// Raises:
+    ///     TypeError: Input cannot be converted to byte array.
+    ///     ValueError: Input cannot be deserialized to Backend.
+    #[staticmethod]
+    pub fn from_bincode(input: &Bound<PyAny>) -> PyResult<BackendWrapper> {
+        let bytes = input
+            .extract::<Vec<u8>>()
+            .map_err(|_| PyTypeError::new_err("Input cannot be converted to byte array"))?;
+
+        Ok(BackendWrapper {
+            internal: deserialize(&bytes[..])
+                .map_err(|_| PyValueError::new_err("Input cannot be deserialized to Backend"))?,
+        })
+    }
+
+    /// Check that the access token is accepted by the IQM server.
+    ///
+    /// Raises:
+    ///     PermissionError: The access token was rejected.
+    ///     ConnectionError: A connection error when sending the request.
+    pub fn check_authentication(&self) -> PyResult<()> {
+        self.internal
+            .check_authentication()
+            .map_err(roqoqo_error_to_pyerr)
+    }
+
+    /// Check that the IQM server is reachable.
+    ///
+    /// Returns:
+    ///     float: The round-trip time of a lightweight request to the server in seconds.
+    ///
+    /// Raises:
+    ///     ConnectionError: The server is unreachable or answered with a server error.
+    pub fn ping(&self) -> PyResult<f64> {
+        self.internal
+            .ping()
+            .map(|round_trip_time| round_trip_time.as_secs_f64())
+            .map_err(roqoqo_error_to_pyerr)
+    }
+
+    /// Return the endpoint URL the jobs are submitted to.
+    ///
+    /// Returns:
+    ///     str: The endpoint URL.
+    pub fn remote_host(&self) -> String {
+        self.internal.remote_host()
+    }
+
+    /// Return the source the access token of the backend was retrieved from.
+    ///
+    /// Helps to find out which account is used without revealing the token itself.
+    ///
+    /// Returns:
+    ///     str: "argument", "IQM_TOKEN", "IQM_TOKENS_FILE" or "profile".
+    pub fn token_source(&self) -> String {
+        match self.internal.token_source() {
+            TokenSource::Argument => "argument",
+            TokenSource::EnvironmentVariable => "IQM_TOKEN",
+            TokenSource::CredentialsProfile => "profile",
+            TokenSource::TokensFile => "IQM_TOKENS_FILE",
+        }
+        .to_string()
+    }
+
+    /// Get information about the quantum architecture of the given device.
+    ///
+    /// Returns:
+    ///     str: Information about the quantum architecture of the device.
+    ///
+    /// Raises:
+    ///     ConnectionError: A connection error when fetching the information.
+    pub fn get_quantum_architecture(&self) -> PyResult<String> {
+        self.internal
+            .get_quantum_architecture()
+            .map_err(roqoqo_error_to_pyerr)
+    }
+
+    /// Get the quantum architecture of the given device, parsed into a dictionary.
+    ///
+    /// Returns:
+    ///     Dict[str, Any]: The `name` (str), the `qubits` (List[str]), the `qubit_connectivity`
+    ///                     (List[Tuple[str, str]]), the native `operations` (List[str]) and the
+    ///                     `max_circuit_depth` (Optional[int]) of the device.
+    ///
+    /// Raises:
+    ///     ConnectionError: A connection error when fetching the information.
+    ///     RuntimeError: The quantum architecture could not be parsed.
+    pub fn get_quantum_architecture_parsed(&self, py: Python) -> PyResult<Py<PyDict>> {
+        let architecture = self
+            .internal
+            .get_quantum_architecture_parsed()
+            .map_err(roqoqo_error_to_pyerr)?;
+        let dict = PyDict::new_bound(py);
+        dict.set_item("name", architecture.name)?;
+        dict.set_item("qubits", architecture.qubits)?;
+        dict.set_item("qubit_connectivity", architecture.qubit_connectivity)?;
+        dict.set_item("operations", architecture.operations)?;
+        dict.set_item("max_circuit_depth", architecture.max_circuit_depth)?;
+        Ok(dict.unbind())
+    }
+
+    /// Build a device from the quantum architecture of the given device.
+    ///
+    /// Operations between qubits and computational resonators are not included, use a
+    /// DenebDevice for devices with computational resonators.
+    ///
+    /// Returns:
+    ///     GenericDevice: The device with the qubits, couplings and native operations of the
+    ///                    quantum architecture.
+    ///
+    /// Raises:
+    ///     ConnectionError: A connection error when fetching the quantum architecture.
+    ///     RuntimeError: The quantum architecture could not be parsed.
+    pub fn device_from_quantum_architecture(&self) -> PyResult<GenericDeviceWrapper> {
+        self.internal
+            .device_from_quantum_architecture()
+            .map(|internal| GenericDeviceWrapper { internal })
+            .map_err(roqoqo_error_to_pyerr)
+    }
+
+    /// Return the maximum depth of the native circuits accepted by the device.
+    ///
+    /// Returns:
+    ///     Optional[int]: The maximum circuit depth, or None if the device does not limit the depth
+    ///                    or the quantum architecture could not be fetched.
+    pub fn max_circuit_depth(&self) -> Option<usize> {
+        self.internal.max_circuit_depth()
+    }
+
+    /// Get the fidelities of the native gates from the latest calibration of the device.
+    ///
+    /// Returns:
+    ///     Dict[str, float]: The fidelity of each calibrated gate, e.g. `prx.QB1` or `cz.QB1__QB2`.
+    ///
+    /// Raises:
+    ///     ConnectionError: A connection error when fetching the calibration.
+    ///     RuntimeError: The calibration contains an invalid fidelity.
+    pub fn get_gate_fidelities(&self) -> PyResult<HashMap<String, f64>> {
+        self.internal
+            .get_gate_fidelities()
+            .map_err(roqoqo_error_to_pyerr)
+    }
+
+    /// Return the map from measurement keys to register positions built when converting the
+    /// circuit for submission.
+    ///
+    /// Args:
+    ///     circuit (Circuit): The circuit to be converted.
+    ///
+    /// Returns:
+    ///     Dict[str, Tuple[List[int], int]]: For each output register, the register indices the
+    ///                                       measured qubits are written to and the register length.
+    ///
+    /// Raises:
+    ///     TypeError: Circuit argument cannot be converted to qoqo Circuit
+    ///     ValueError: The circuit could not be converted
+    pub fn measurement_key_map(
+        &self,
+        circuit: &Bound<PyAny>,
+    ) -> PyResult<HashMap<String, (Vec<usize>, usize)>> {
+        let circuit = convert_into_circuit(circuit).map_err(|err| {
+            PyTypeError::new_err(format!(
+                "Circuit argument cannot be converted to qoqo Circuit: {:?}",
+                err
+            ))
+        })?;
+        self.internal
+            .measurement_key_map(&circuit)
+            .map_err(iqm_error_to_pyerr)
+    }
+
+    /// Return the number of qubits measured into each output register of a circuit.
+    ///
+    /// The number can be smaller than the length of the register, in which case the bits that are
+    /// not measured are always False in the results.
+    ///
+    /// Args:
+    ///     circuit (Circuit): The circuit to be converted.
+    ///
+    /// Returns:
+    ///     Dict[str, int]: The number of measured qubits of each output register.
+    ///
+    /// Raises:
+    ///     TypeError: Circuit argument cannot be converted to qoqo Circuit
+    ///     ValueError: The circuit could not be converted
+    pub fn measured_qubit_counts(
+        &self,
+        circuit: &Bound<PyAny>,
+    ) -> PyResult<HashMap<String, usize>> {
+        let circuit = convert_into_circuit(circuit).map_err(|err| {
+            PyTypeError::new_err(format!(
+                "Circuit argument cannot be converted to qoqo Circuit: {:?}",
+                err
+            ))
+        })?;
+        self.internal
+            .measured_qubit_counts(&circuit)
+            .map_err(iqm_error_to_pyerr)
+    }
+
+    /// Describe the native instructions a circuit is compiled to, with their timing.
+    ///
+    /// Lists each instruction with its qubits, its gate time on the device and the time at which
+    /// it ends, scheduling instructions on disjoint qubits in parallel.
+    ///
+    /// Args:
+    ///     circuit (Circuit): The circuit to be compiled.
+    ///
+    /// Returns:
+    ///     str: The listing of the native instructions.
+    ///
+    /// Raises:
+    ///     TypeError: Circuit argument cannot be converted to qoqo Circuit
+    ///     ValueError: The circuit could not be converted
+    pub fn explain_compilation(&self, circuit: &Bound<PyAny>) -> PyResult<String> {
+        let circuit = convert_into_circuit(circuit).map_err(|err| {
+            PyTypeError::new_err(format!(
+                "Circuit argument cannot be converted to qoqo Circuit: {:?}",
+                err
+            ))
+        })?;
+        self.internal
+            .explain_compilation(&circuit)
+            .map_err(iqm_error_to_pyerr)
+    }
+
+    /// Analyze a circuit without submitting it.
+    ///
+    /// Collects all the problems of the circuit instead of stopping at the first one, together
+    /// with warnings and statistics of the circuit.
+    ///
+    /// Args:
+    ///     circuit (Circuit): The circuit to be analyzed.
+    ///
+    /// Returns:
+    ///     Dict[str, Any]: The `errors` and `warnings` (List[str]), the `qubit_count` and
+    ///                     `gate_count` (int) and the `estimated_duration` (float) of the circuit.
+    ///
+    /// Raises:
+    ///     TypeError: Circuit argument cannot be converted to qoqo Circuit
+    pub fn analyze_circuit(&self, py: Python, circuit: &Bound<PyAny>) -> PyResult<Py<PyDict>> {
+        let circuit = convert_into_circuit(circuit).map_err(|err| {
+            PyTypeError::new_err(format!(
+                "Circuit argument cannot be converted to qoqo Circuit: {:?}",
+                err
+            ))
+        })?;
+        let analysis = self.internal.analyze_circuit(&circuit);
+        let dict = PyDict::new_bound(py);
+        dict.set_item("errors", analysis.errors)?;
+        dict.set_item("warnings", analysis.warnings)?;
+        dict.set_item("qubit_count", analysis.qubit_count)?;
+        dict.set_item("gate_count", analysis.gate_count)?;
+        dict.set_item("estimated_duration", analysis.estimated_duration)?;
+        Ok(dict.unbind())
+    }
+
+    /// Decompose multi-qubit gates into gates supported by the device before validating and
+    /// submitting circuits.
+    ///
+    /// Currently `ControlledControlledPauliZ` gates are decomposed into `RotateXY` and
+    /// `ControlledPauliZ` gates. Disabled by default.
+    ///
+    /// Args:
+    ///     decompose_multiqubit (bool): Whether multi-qubit gates are decomposed.
+    pub fn set_decompose_multiqubit(&mut self, decompose_multiqubit: bool) {
+        self.internal.set_decompose_multiqubit(decompose_multiqubit)
+    }
+
+    /// Implement ControlledPauliZ gates via the computational resonator on Deneb devices.
+    ///
+    /// Each ControlledPauliZ gate is replaced by a SingleExcitationStore of the target qubit, a
+    /// CZQubitResonator on the control qubit and a SingleExcitationLoad of the target qubit. Has
+    /// no effect on other devices. Disabled by default.
+    ///
+    /// Args:
+    ///     auto_resonator_cz (bool): Whether ControlledPauliZ gates are implemented via the resonator.
+    pub fn set_auto_resonator_cz(&mut self, auto_resonator_cz: bool) {
+        self.internal.set_auto_resonator_cz(auto_resonator_cz)
+    }
+
+    /// Set the timeout for polling the results of jobs.
+    ///
+    /// Args:
+    ///     seconds (float): The timeout in seconds. Defaults to 60 seconds.
+    ///
+    /// Raises:
+    ///     ValueError: The timeout is negative or not finite.
+    pub fn set_timeout(&mut self, seconds: f64) -> PyResult<()> {
+        self.internal
+            .set_timeout(seconds)
+            .map_err(|err| PyValueError::new_err(err.to_string()))
+    }
+
+    /// Set the interval between two queries of the results of a job.
+    ///
+    /// Args:
+    ///     seconds (float): The interval in seconds. Defaults to 4 seconds.
+    ///
+    /// Raises:
+    ///     ValueError: The interval is not strictly positive.
+    pub fn set_polling_interval(&mut self, seconds: f64) -> PyResult<()> {
+        self.internal
+            .set_polling_interval(seconds)
+            .map_err(|err| PyValueError::new_err(err.to_string()))
+    }
+
+    /// Set the templates of the URLs of the job status, abort, quantum architecture and calibration
+    /// metrics endpoints.
+    ///
+    /// The templates can contain the placeholders `{jobs_url}` for the job submission URL of the
+    /// device, `{base_url}` for this URL without the trailing `/jobs` segment and `{id}` for the
+    /// ID of the job.
+    ///
+    /// Args:
+    ///     job_status (str): Template of the URL returning the status and results of a job.
+    ///     abort (str): Template of the URL aborting a job.
+    ///     quantum_architecture (str): Template of the URL returning the quantum architecture.
+    ///     calibration_metrics (Optional[str]): Template of the URL returning the metrics of the latest calibration. Defaults to "{base_url}/calibration/metrics/latest".
+    #[pyo3(signature = (job_status, abort, quantum_architecture, calibration_metrics = None))]
+    pub fn set_endpoint_paths(
+        &mut self,
+        job_status: String,
+        abort: String,
+        quantum_architecture: String,
+        calibration_metrics: Option<String>,
+    ) {
+        let default_paths = EndpointPaths::default();
+        self.internal.set_endpoint_paths(EndpointPaths {
+            job_status,
+            abort,
+            quantum_architecture,
+            calibration_metrics: calibration_metrics.unwrap_or(default_paths.calibration_metrics),
+        })
+    }
+
+    /// Set the policy for retrying job submissions after network errors or server errors.
+    ///
+    /// The delay before each retry is doubled, up to the maximum set with `set_max_backoff`.
+    /// Retries stop once the next one would end after the timeout of the backend. By default
+    /// submissions are not retried.
+    ///
+    /// Args:
+    ///     max_retries (int): Maximum number of retries after the first attempt.
+    ///     initial_backoff (float): Delay before the first retry in seconds.
+    ///
+    /// Raises:
+    ///     ValueError: The backoff is negative or not finite.
+    pub fn set_retry_policy(&mut self, max_retries: usize, initial_backoff: f64) -> PyResult<()> {
+        if !initial_backoff.is_finite() || initial_backoff < 0.0 {
+            return Err(PyValueError::new_err(format!(
+                "Invalid backoff: {}",
+                initial_backoff
+            )));
+        }
+        self.internal.set_retry_policy(RetryPolicy {
+            max_retries,
+            initial_backoff: Duration::from_secs_f64(initial_backoff),
+            ..self.internal.retry_policy()
+        });
+        Ok(())
+    }
+
+    /// Set the maximum delay between two attempts of a job submission.
+    ///
+    /// Args:
+    ///     max_backoff (float): The maximum delay in seconds. Defaults to 30 seconds.
+    ///
+    /// Raises:
+    ///     ValueError: The delay is negative or not finite.
+    pub fn set_max_backoff(&mut self, max_backoff: f64) -> PyResult<()> {
+        if !max_backoff.is_finite() || max_backoff < 0.0 {
+            return Err(PyValueError::new_err(format!(
+                "Invalid backoff: {}",
+                max_backoff
+            )));
+        }
+        self.internal
+            .set_max_backoff(Duration::from_secs_f64(max_backoff));
+        Ok(())
+    }
+
+    /// Set the idempotency key sent with job submissions.
+    ///
+    /// The server discards submissions with an already used key. When no key is set, a new key
+    /// is generated for each submission and shared by all of its retries.
+    ///
+    /// Args:
+    ///     idempotency_key (Optional[str]): The key to send, or None to generate one per submission.
+    pub fn set_idempotency_key(&mut self, idempotency_key: Option<String>) {
+        self.internal.set_idempotency_key(idempotency_key)
+    }
+
+    /// Set a prefix for the names of the circuits submitted to the server.
+    ///
+    /// Circuits are submitted as `<prefix>qc_<index>`. Characters not accepted by the server are
+    /// replaced by `_` and the names are truncated to the maximum length.
+    ///
+    /// Args:
+    ///     circuit_name_prefix (Optional[str]): The prefix, or None to submit the circuits without prefix.
+    pub fn set_circuit_name_prefix(&mut self, circuit_name_prefix: Option<String>) {
+        self.internal.set_circuit_name_prefix(circuit_name_prefix)
+    }
+
+    /// Set the HTTP status codes of the server responses that are treated as success.
+    ///
+    /// Defaults to 200, 201 and 202.
+    ///
+    /// Args:
+    ///     success_status_codes (List[int]): The status codes treated as success.
+    pub fn set_success_status_codes(&mut self, success_status_codes: Vec<u16>) {
+        self.internal.set_success_status_codes(success_status_codes)
+    }
+
+    /// Warn when a job is submitted with fewer shots than the given threshold.
+    ///
+    /// The job is still submitted. The warning is printed as set with `set_print_warnings` and
+    /// can be read with `get_submission_warnings`. Disabled by default.
+    ///
+    /// Args:
+    ///     min_shots (int): The minimum number of shots without warning, 0 disables the warning.
+    pub fn set_min_shots_warning(&mut self, min_shots: usize) {
+        self.internal.set_min_shots_warning(min_shots)
+    }
+
+    /// Measure only as many qubits as the register holds with PragmaRepeatedMeasurement.
+    ///
+    /// By default, a PragmaRepeatedMeasurement without qubit mapping measures all the qubits of
+    /// the device and requires a register at least as long as the number of qubits of the device.
+    ///
+    /// Args:
+    ///     register_qubits (bool): Whether to measure only as many qubits as the register holds.
+    pub fn set_repeated_measurement_register_qubits(&mut self, register_qubits: bool) {
+        self.internal
+            .set_repeated_measurement_register_qubits(register_qubits)
+    }
+
+    /// Set the serialization format of the run requests sent to the server.
+    ///
+    /// Args:
+    ///     request_format (str): "json" (default) or "msgpack".
+    ///
+    /// Raises:
+    ///     ValueError: Unknown request format
+    pub fn set_request_format(&mut self, request_format: &str) -> PyResult<()> {
+        let request_format = match request_format {
+            "json" => RequestFormat::Json,
+            "msgpack" => RequestFormat::MsgPack,
+            _ => {
+                return Err(PyValueError::new_err(format!(
+                    "Unknown request format {}, expected json or msgpack",
+                    request_format
+                )))
+            }
+        };
+        self.internal.set_request_format(request_format);
+        Ok(())
+    }
+
+    /// Set the seed used by the server to sample the measurement results.
+    ///
+    /// Simulators, such as the demo endpoints returning pseudorandom results, produce the same
+    /// results for the same seed. Servers running on hardware ignore the seed.
+    ///
+    /// Args:
+    ///     random_seed (Optional[int]): The seed, or None to let the server choose one.
+    pub fn set_random_seed(&mut self, random_seed: Option<u64>) {
+        self.internal.set_random_seed(random_seed)
+    }
+
+    /// Set the mapping of the logical qubits of the circuits onto the physical qubits of the
+    /// device.
+    ///
+    /// Every qubit acted on by a submitted circuit must be contained in the mapping. An empty
+    /// mapping leaves the placement of the qubits to the server.
+    ///
+    /// Args:
+    ///     mapping (Dict[int, str]): The name of the physical qubit, e.g. "QB3", of each logical
+    ///                               qubit.
+    pub fn set_qubit_mapping(&mut self, mapping: HashMap<usize, String>) {
+        self.internal.set_qubit_mapping(mapping)
+    }
+
+    /// Let the server check the duration of the circuits of submitted jobs.
+    ///
+    /// When enabled, the server rejects circuits whose duration exceeds the coherence-limited
+    /// maximum of the device, which raises a RuntimeError with the message of the server.
+    /// Disabled by default.
+    ///
+    /// Args:
+    ///     circuit_duration_check (bool): Whether the duration of the circuits is checked.
+    pub fn set_circuit_duration_check(&mut self, circuit_duration_check: bool) {
+        self.internal
+            .set_circuit_duration_check(circuit_duration_check)
+    }
+
+    /// Print the warnings of the IQM device to stderr when querying the results of jobs, and the
+    /// warnings of the backend when submitting jobs.
+    ///
+    /// Enabled by default. The warnings can also be read with `get_last_warnings` and
+    /// `get_submission_warnings`.
+    ///
+    /// Args:
+    ///     print_warnings (bool): Whether warnings are printed.
+    pub fn set_print_warnings(&mut self, print_warnings: bool) {
+        self.internal.set_print_warnings(print_warnings)
+    }
+
+    /// Return the warnings of the IQM device for the last job whose results were waited for.
+    ///
+    /// The warnings are updated by all functions running circuits.
+    ///
+    /// Returns:
+    ///     Optional[List[str]]: The warnings, or None if the server did not report warnings.
+    pub fn get_last_warnings(&self) -> Option<Vec<String>> {
+        self.internal.get_last_warnings()
+    }
+
+    /// Return the warnings raised by the backend itself when submitting the last job.
+    ///
+    /// For example a number of shots below the threshold set with `set_min_shots_warning`.
+    ///
+    /// Returns:
+    ///     Optional[List[str]]: The warnings, or None if the last submission raised no warnings.
+    pub fn get_submission_warnings(&self) -> Option<Vec<String>> {
+        self.internal.get_submission_warnings()
+    }
+
+    /// Set the ID of the calibration set the submitted jobs are executed with.
+    ///
+    /// When set, running circuits raises a RuntimeError if a job was executed with a different
+    /// calibration set.
+    ///
+    /// Args:
+    ///     calibration_set_id (Optional[str]): The ID of the calibration set, or None to use the
+    ///                                         latest one.
+    pub fn set_calibration_set_id(&mut self, calibration_set_id: Option<String>) {
+        self.internal.set_calibration_set_id(calibration_set_id)
+    }
+
+    /// Set the credentials used to refresh the access token when it expires.
+    ///
+    /// When the server rejects the access token while submitting jobs or querying their results,
+    /// the refresh token is exchanged for a new access token at the auth server and the request is
+    /// sent once more.
+    ///
+    /// Args:
+    ///     refresh_token (str): The refresh token.
+    ///     auth_server_url (str): The base URL of the auth server.
+    pub fn set_refresh_credentials(&mut self, refresh_token: String, auth_server_url: String) {
+        self.internal
+            .set_refresh_credentials(refresh_token, auth_server_url)
+    }
+
+    /// Set the priority tier requested for the submitted jobs.
+    ///
+    /// Accounts with priority access can request a faster turnaround. If None, the jobs run with
+    /// the default tier of the account.
+    ///
+    /// Args:
+    ///     job_priority (Optional[str]): The name of the priority tier, or None for the default.
+    pub fn set_job_priority(&mut self, job_priority: Option<String>) {
+        self.internal.set_job_priority(job_priority)
+    }
+
+    /// Treat the warnings returned by the IQM device as errors.
+    ///
+    /// When enabled, running circuits raises a RuntimeError for jobs whose results carry warnings,
+    /// e.g. about stale calibration data. Disabled by default.
+    ///
+    /// Args:
+    ///     warnings_as_errors (bool): Whether warnings are treated as errors.
+    pub fn set_warnings_as_errors(&mut self, warnings_as_errors: bool) {
+        self.internal.set_warnings_as_errors(warnings_as_errors)
+    }
+
+    /// Check the depth of the circuits against the maximum circuit depth of the device.
+    ///
+    /// When enabled, the validation rejects circuits whose native depth exceeds the maximum
+    /// circuit depth of the device. Disabled by default.
+    ///
+    /// Args:
+    ///     check_circuit_depth (bool): Whether the depth of the circuits is checked.
+    pub fn set_check_circuit_depth(&mut self, check_circuit_depth: bool) {
+        self.internal.set_check_circuit_depth(check_circuit_depth)
+    }
+
+    /// Set the treatment of jobs reported as ready without measurement results.
+    ///
+    /// Args:
+    ///     empty_result_policy (str): "error" (default) to raise an error when converting the
+    ///                                results, "retry_then_error" to re-fetch the results a few
+    ///                                times before raising an error or "return_empty" to return
+    ///                                empty registers.
+    ///
+    /// Raises:
+    ///     ValueError: Unknown empty result policy
+    pub fn set_empty_result_policy(&mut self, empty_result_policy: &str) -> PyResult<()> {
+        let empty_result_policy = match empty_result_policy {
+            "error" => EmptyResultPolicy::Error,
+            "retry_then_error" => EmptyResultPolicy::RetryThenError,
+            "return_empty" => EmptyResultPolicy::ReturnEmpty,
+            _ => {
+                return Err(PyValueError::new_err(format!(
+                    "Unknown empty result policy {}, expected error, retry_then_error or \
+                     return_empty",
+                    empty_result_policy
+                )))
+            }
+        };
+        self.internal.set_empty_result_policy(empty_result_policy);
+        Ok(())
+    }
+
+    /// Set the strictness of the client-side validation of the circuits.
+    ///
+    /// Args:
+    ///     validation_level (str): "off" to rely on the server rejecting invalid circuits,
+    ///                             "connectivity" to only check the operations against the device
+    ///                             or "full" (default) to check the measurements and registers too.
+    ///
+    /// Raises:
+    ///     ValueError: Unknown validation level
+    pub fn set_validation_level(&mut self, validation_level: &str) -> PyResult<()> {
+        let validation_level = match validation_level {
+            "off" => ValidationLevel::Off,
+            "connectivity" => ValidationLevel::Connectivity,
+            "full" => ValidationLevel::Full,
+            _ => {
+                return Err(PyValueError::new_err(format!(
+                    "Unknown validation level {}, expected off, connectivity or full",
+                    validation_level
+                )))
+            }
+        };
+        self.internal.set_validation_level(validation_level);
+        Ok(())
+    }
+
+    /// Round the number of shots of submitted jobs to the nearest number accepted by the device.
+    ///
+    /// When enabled, a warning is raised if the number of shots changes, printed as set with
+    /// `set_print_warnings` and readable with `get_submission_warnings`. Disabled by default.
+    ///
+    /// Args:
+    ///     round_shots (bool): Whether the number of shots is rounded.
+    pub fn set_round_shots(&mut self, round_shots: bool) {
+        self.internal.set_round_shots(round_shots)
+    }
+
+    /// Set the heralding mode of the submitted jobs.
+    ///
+    /// With "zeros" the server performs a heralding measurement before each shot and only retains
+    /// the shots with an all-zeros result, so that the results contain fewer shots than requested.
+    ///
+    /// Args:
+    ///     heralding_mode (str): "none" (default) or "zeros".
+    ///
+    /// Raises:
+    ///     ValueError: Unknown heralding mode
+    pub fn set_heralding_mode(&mut self, heralding_mode: &str) -> PyResult<()> {
+        let heralding_mode = match heralding_mode {
+            "none" => HeraldingMode::None,
+            "zeros" => HeraldingMode::Zeros,
+            _ => {
+                return Err(PyValueError::new_err(format!(
+                    "Unknown heralding mode {}, expected none or zeros",
+                    heralding_mode
+                )))
+            }
+        };
+        self.internal.set_heralding_mode(heralding_mode);
+        Ok(())
+    }
+
+    /// Set the cost model used by `estimate_cost`.
+    ///
+    /// The cost of a circuit is `shots * (cost_per_shot + cost_per_gate * number_of_gates)`.
+    ///
+    /// Args:
+    ///     cost_per_shot (float): Cost of a single shot of a circuit, independent of the circuit.
+    ///     cost_per_gate (float): Additional cost of a single shot for each gate in the circuit.
+    pub fn set_cost_model(&mut self, cost_per_shot: f64, cost_per_gate: f64) {
+        self.internal.set_cost_model(CostModel {
+            cost_per_shot,
+            cost_per_gate,
+        })
+    }
+
+    /// Estimate the cost in credits of running a batch of circuits.
+    ///
+    /// Args:
+    ///     circuits (List[Circuit]): The circuit batch to be estimated.
+    ///
+    /// Returns:
+    ///     float: The estimated cost of the batch.
+    ///
+    /// Raises:
+    ///     TypeError: `circuits` argument is not a list of qoqo Circuits
+    ///     ValueError: A circuit could not be converted
+    pub fn estimate_cost(&self, circuits: Vec<Bound<PyAny>>) -> PyResult<f64> {
+        let circuit_batch = get_circuit_list(circuits)?;
+        self.internal
+            .estimate_cost(&circuit_batch)
+            .map_err(iqm_error_to_pyerr)
+    }
+
+    /// Return the number of measurements that will be used when submitting a circuit.
+    ///
+    /// A number of measurements set with `_overwrite_number_of_measurements` takes precedence over
+    /// the one defined in the circuit.
+    ///
+    /// Args:
+    ///     circuit (Circuit): The circuit to be submitted.
+    ///
+    /// Returns:
+    ///     int: The number of measurements of the circuit.
+    ///
+    /// Raises:
+    ///     TypeError: Circuit argument cannot be converted to qoqo Circuit
+    pub fn effective_shots(&self, circuit: &Bound<PyAny>) -> PyResult<usize> {
+        let circuit = convert_into_circuit(circuit).map_err(|err| {
+            PyTypeError::new_err(format!(
+                "Circuit argument cannot be converted to qoqo Circuit {:?}",
+                err
+            ))
+        })?;
+        Ok(self.internal.effective_shots(&circuit))
+    }
+
+    /// List the most recent jobs submitted to the endpoint of the device.
+    ///
+    /// Args:
+    ///     limit (int): The maximum number of jobs to list.
+    ///
+    /// Returns:
+    ///     List[Tuple[str, str, Optional[str]]]: The ID, status and submission time of each job.
+    ///
+    /// Raises:
+    ///     ConnectionError: A connection error when fetching the list of jobs.
+    pub fn list_jobs(&self, limit: usize) -> PyResult<Vec<(String, String, Option<String>)>> {
+        let jobs = self
+            .internal
+            .list_jobs(limit)
+            .map_err(roqoqo_error_to_pyerr)?;
+        Ok(jobs
+            .into_iter()
+            .map(|job| (job.id, job.status, job.submitted_at))
+            .collect())
+    }
+
+    /// Poll the status of a job until it differs from the given status.
+    ///
+    /// Args:
+    ///     id (str): The job ID for the query.
+    ///     from_status (str): The status the job is expected to leave, e.g. "pending compilation".
+    ///
+    /// Returns:
+    ///     str: The new status of the job.
+    ///
+    /// Raises:
+    ///     ValueError: Unknown job status
+    ///     ConnectionError: A connection error when sending the request.
+    ///     TimeoutError: The status did not change before the timeout.
+    pub fn wait_for_status_change(&self, id: String, from_status: &str) -> PyResult<String> {
+        let from = match serde_json::from_value(serde_json::Value::String(from_status.to_string()))
+        {
+            Ok(Status::Unknown(_)) | Err(_) => {
+                return Err(PyValueError::new_err(format!(
+                    "Unknown job status: {}",
+                    from_status
+                )))
+            }
+            Ok(status) => status,
+        };
+        let status = self
+            .internal
+            .wait_for_status_change(id, from)
+            .map_err(iqm_error_to_pyerr)?;
+        serde_json::to_value(status)
+            .ok()
+            .and_then(|value| value.as_str().map(str::to_string))
+            .ok_or_else(|| PyRuntimeError::new_err("Could not serialize the job status"))
+    }
+
+    /// Estimate the time until a submitted job has finished from the queue metrics of the server.
+    ///
+    /// Args:
+    ///     id (str): The job ID for the query.
+    ///
+    /// Returns:
+    ///     Optional[float]: The estimated time in seconds, or None if the server does not report
+    ///                      the queue position or the average execution time.
     ///
     /// Raises:
-    ///     ValueError: Cannot serialize Backend to bytes.
-    pub fn to_bincode(&self) -> PyResult<Py<PyByteArray>> {
-        let serialized = serialize(&self.internal)
-            .map_err(|_| PyValueError::new_err("Cannot serialize Backend to bytes"))?;
-        let b: Py<PyByteArray> = Python::with_gil(|py| -> Py<PyByteArray> {
-            PyByteArray::new_bound(py, &serialized[..]).into()
-        });
-        Ok(b)
+    ///     ConnectionError: A connection error when sending the request.
+    pub fn estimate_wait_time(&self, id: String) -> PyResult<Option<f64>> {
+        self.internal
+            .estimate_wait_time(id)
+            .map(|wait_time| wait_time.map(|wait_time| wait_time.as_secs_f64()))
+            .map_err(roqoqo_error_to_pyerr)
     }
 
-    /// Convert the bincode representation of the Backend to a Backend using the [bincode] crate.
+    /// Query the status of a submitted job without waiting for it to finish.
     ///
     /// Args:
-    ///     input (ByteArray): The serialized Backend (in [bincode] form).
+    ///     id (str): The job ID for the query.
     ///
     /// Returns:
-    ///     Backend: The deserialized Backend.
+    ///     str: The status of the job, e.g. "pending compilation" or "ready".
     ///
     /// Raises:
-    ///     TypeError: Input cannot be converted to byte array.
-    ///     ValueError: Input cannot be deserialized to Backend.
-    #[staticmethod]
-    pub fn from_bincode(input: &Bound<PyAny>) -> PyResult<BackendWrapper> {
-        let bytes = input
-            .extract::<Vec<u8>>()
-            .map_err(|_| PyTypeError::new_err("Input cannot be converted to byte array"))?;
-
-        Ok(BackendWrapper {
-            internal: deserialize(&bytes[..])
-                .map_err(|_| PyValueError::new_err("Input cannot be deserialized to Backend"))?,
-        })
+    ///     ConnectionError: A connection error when sending the request.
+    pub fn get_status(&self, id: String) -> PyResult<String> {
+        self.internal.get_status(id).map_err(roqoqo_error_to_pyerr)
     }
 
-    /// Get information about the quantum architecture of the given device.
+    /// Clear the cache of the results of finished jobs.
     ///
-    /// Returns:
-    ///     str: Information about the quantum architecture of the device.
-    ///
-    /// Raises:
-    ///     ConnectionError: A connection error when fetching the information.
-    pub fn get_quantum_architecture(&self) -> PyResult<String> {
-        self.internal
-            .get_quantum_architecture()
-            .map_err(|err| PyConnectionError::new_err(err.to_string()))
+    /// The results of jobs that are ready, failed or aborted are cached, so that repeated queries
+    /// for the same job are answered without a request to the server.
+    pub fn clear_result_cache(&self) {
+        self.internal.clear_result_cache()
     }
 
     /// Abort a submitted job.
@@ -198,12 +1019,35 @@ impl BackendWrapper {
     /// Args:
     ///     id (str): The ID of the job to abort.
     ///
-    /// Returns:
+    /// Raises:
     ///     ConnectionError: A connection error when sending the request.
+    ///     RuntimeError: The job could not be aborted.
     pub fn abort_job(&self, id: String) -> PyResult<()> {
-        self.internal
-            .abort_job(id)
-            .map_err(|err| PyConnectionError::new_err(err.to_string()))
+        self.internal.abort_job(id).map_err(iqm_error_to_pyerr)
+    }
+
+    /// Abort all unfinished jobs whose name starts with the given prefix.
+    ///
+    /// The jobs are searched among the most recent jobs of the device.
+    ///
+    /// Args:
+    ///     prefix (str): The prefix of the names of the jobs to abort.
+    ///
+    /// Returns:
+    ///     List[Tuple[str, Optional[str]]]: The ID of each matching job, with the error message if
+    ///                                      the job could not be aborted.
+    ///
+    /// Raises:
+    ///     ConnectionError: A connection error when fetching the list of jobs.
+    pub fn abort_jobs_by_prefix(&self, prefix: &str) -> PyResult<Vec<(String, Option<String>)>> {
+        let results = self
+            .internal
+            .abort_jobs_by_prefix(prefix)
+            .map_err(iqm_error_to_pyerr)?;
+        Ok(results
+            .into_iter()
+            .map(|(id, result)| (id, result.err().map(|err| err.to_string())))
+            .collect())
     }
 
     /// Run a circuit with the IQM backend and poll results until job is either ready, failed,
@@ -219,14 +1063,105 @@ impl BackendWrapper {
     ///
     /// Args:
     ///     circuit (Circuit): The circuit that is run on the backend.
+    ///     return_warnings (bool): Whether to also return the warnings of the IQM device. Defaults to False.
     ///
     /// Returns:
     ///     Tuple[Dict[str, List[List[bool]]], Dict[str, List[List[float]]]], Dict[str, List[List[complex]]]]: The output registers written by the evaluated circuits.
+    ///     When `return_warnings` is True, a tuple of the output registers and the list of warnings (List[str]) is returned.
     ///
     /// Raises:
     ///     TypeError: Circuit argument cannot be converted to qoqo Circuit
+    ///     ValueError: The circuit is not valid for the device
+    ///     ConnectionError: A connection error when communicating with the server
     ///     RuntimeError: Running Circuit failed
-    pub fn run_circuit(&self, circuit: &Bound<PyAny>) -> PyResult<Registers> {
+    #[pyo3(signature = (circuit, return_warnings = false))]
+    pub fn run_circuit(&self, circuit: &Bound<PyAny>, return_warnings: bool) -> PyResult<PyObject> {
+        let py = circuit.py();
+        let circuit = convert_into_circuit(circuit).map_err(|err| {
+            PyTypeError::new_err(format!(
+                "Circuit argument cannot be converted to qoqo Circuit: {:?}",
+                err
+            ))
+        })?;
+        let (registers, warnings) = self
+            .internal
+            .run_circuit_batch_with_warnings(&[circuit])
+            .map_err(iqm_error_to_pyerr)?;
+        if return_warnings {
+            Ok((registers, warnings).into_py(py))
+        } else {
+            Ok(registers.into_py(py))
+        }
+    }
+
+    /// Measure the expectation values of products of Pauli operators on the state prepared by a
+    /// circuit.
+    ///
+    /// For each term, a copy of `base_circuit` measuring the qubits of the term in the given bases
+    /// is built. All the circuits are submitted as a single batch sharing the same number of shots.
+    ///
+    /// Args:
+    ///     base_circuit (Circuit): The circuit preparing the state, without measurements.
+    ///     pauli_terms (Dict[str, List[Tuple[int, str]]]): The qubits and bases ("X", "Y" or "Z")
+    ///                                                     of each product, keyed by term name.
+    ///
+    /// Returns:
+    ///     Dict[str, float]: The expectation value of each term.
+    ///
+    /// Raises:
+    ///     TypeError: Circuit argument cannot be converted to qoqo Circuit
+    ///     ValueError: Unknown basis, or a circuit is not valid for the device
+    ///     ConnectionError: A connection error when communicating with the server
+    ///     RuntimeError: Running the circuits failed
+    pub fn run_pauli_measurement(
+        &self,
+        base_circuit: &Bound<PyAny>,
+        pauli_terms: HashMap<String, Vec<(usize, String)>>,
+    ) -> PyResult<HashMap<String, f64>> {
+        let base_circuit = convert_into_circuit(base_circuit).map_err(|err| {
+            PyTypeError::new_err(format!(
+                "Circuit argument cannot be converted to qoqo Circuit: {:?}",
+                err
+            ))
+        })?;
+        let pauli_terms = pauli_terms
+            .into_iter()
+            .map(|(name, term)| {
+                let term = term
+                    .into_iter()
+                    .map(|(qubit, basis)| Ok((qubit, parse_pauli_basis(&basis)?)))
+                    .collect::<PyResult<Vec<_>>>()?;
+                Ok((name, term))
+            })
+            .collect::<PyResult<HashMap<_, _>>>()?;
+        self.internal
+            .run_pauli_measurement(&base_circuit, &pauli_terms)
+            .map_err(iqm_error_to_pyerr)
+    }
+
+    /// Run a circuit with symbolic parameters for several sets of parameter values.
+    ///
+    /// The circuit is expanded into one circuit per parameter set, and all circuits are submitted
+    /// as a single batch.
+    ///
+    /// Args:
+    ///     circuit (Circuit): The circuit with symbolic parameters.
+    ///     parameter_sets (List[Dict[str, float]]): The value of each symbolic parameter, for each
+    ///                                              circuit of the sweep.
+    ///
+    /// Returns:
+    ///     List[Registers]: The output registers of each parameter set.
+    ///
+    /// Raises:
+    ///     TypeError: Circuit argument cannot be converted to qoqo Circuit
+    ///     ValueError: A parameter set does not define all parameters
+    ///     ConnectionError: A connection error when communicating with the server
+    ///     RuntimeError: Running the circuits failed
+    pub fn run_parameter_sweep(
+        &self,
+        circuit: &Bound<PyAny>,
+        parameter_sets: Vec<HashMap<String, f64>>,
+    ) -> PyResult<Vec<Registers>> {
         let circuit = convert_into_circuit(circuit).map_err(|err| {
             PyTypeError::new_err(format!(
                 "Circuit argument cannot be converted to qoqo Circuit: {:?}",
@@ -234,8 +1169,91 @@ impl BackendWrapper {
             ))
         })?;
         self.internal
-            .run_circuit(&circuit)
-            .map_err(|err| PyRuntimeError::new_err(format!("Running Circuit failed: {:?}", err)))
+            .run_parameter_sweep(&circuit, &parameter_sets)
+            .map_err(iqm_error_to_pyerr)
+    }
+
+    /// Run a circuit with the IQM backend and measure the time spent in each stage of the run.
+    ///
+    /// Args:
+    ///     circuit (Circuit): The circuit that is run on the backend.
+    ///
+    /// Returns:
+    ///     Tuple[Tuple[Dict[str, List[List[bool]]], Dict[str, List[List[float]]]], Dict[str, List[List[complex]]]], Dict[str, float]]: The output registers and the
+    ///     time in seconds spent submitting the circuit (`submit`), waiting for the results (`wait`) and processing them (`processing`).
+    ///
+    /// Raises:
+    ///     TypeError: Circuit argument cannot be converted to qoqo Circuit
+    ///     ValueError: The circuit is not valid for the device
+    ///     ConnectionError: A connection error when communicating with the server
+    ///     RuntimeError: Running Circuit failed
+    pub fn run_circuit_timed(&self, circuit: &Bound<PyAny>) -> PyResult<PyObject> {
+        let py = circuit.py();
+        let circuit = convert_into_circuit(circuit).map_err(|err| {
+            PyTypeError::new_err(format!(
+                "Circuit argument cannot be converted to qoqo Circuit: {:?}",
+                err
+            ))
+        })?;
+        let (registers, timing) = self
+            .internal
+            .run_circuit_timed(&circuit)
+            .map_err(iqm_error_to_pyerr)?;
+        let timing: HashMap<&str, f64> = [
+            ("submit", timing.submit.as_secs_f64()),
+            ("wait", timing.wait.as_secs_f64()),
+            ("processing", timing.processing.as_secs_f64()),
+        ]
+        .into_iter()
+        .collect();
+        Ok((registers, timing).into_py(py))
+    }
+
+    /// Run a circuit with the IQM backend and return the histogram of the measured bitstrings of
+    /// a register.
+    ///
+    /// Each bitstring contains one character per bit of the register. By default the bit at index 0
+    /// of the register is the first character of the bitstring. With `big_endian` the bit at index 0
+    /// is the last character, i.e. the least significant bit when read as a binary number.
+    ///
+    /// Args:
+    ///     circuit (Circuit): The circuit that is run on the backend.
+    ///     register_name (str): The name of the bit register to aggregate.
+    ///     big_endian (bool): Whether the bit at index 0 is the last character of the bitstrings. Defaults to False.
+    ///
+    /// Returns:
+    ///     Dict[str, int]: The number of shots for each measured bitstring.
+    ///
+    /// Raises:
+    ///     TypeError: Circuit argument cannot be converted to qoqo Circuit
+    ///     KeyError: The circuit does not write to the given register
+    ///     ValueError: The circuit is not valid for the device
+    ///     ConnectionError: A connection error when communicating with the server
+    ///     RuntimeError: Running Circuit failed
+    #[pyo3(signature = (circuit, register_name, big_endian = false))]
+    pub fn run_circuit_counts(
+        &self,
+        circuit: &Bound<PyAny>,
+        register_name: &str,
+        big_endian: bool,
+    ) -> PyResult<HashMap<String, usize>> {
+        let circuit = convert_into_circuit(circuit).map_err(|err| {
+            PyTypeError::new_err(format!(
+                "Circuit argument cannot be converted to qoqo Circuit: {:?}",
+                err
+            ))
+        })?;
+        let (bit_registers, _, _) = self
+            .internal
+            .run_circuit_batch(&[circuit])
+            .map_err(iqm_error_to_pyerr)?;
+        let register = bit_registers.get(register_name).ok_or_else(|| {
+            PyKeyError::new_err(format!(
+                "The circuit does not write to a bit register named {}",
+                register_name
+            ))
+        })?;
+        Ok(bit_register_to_counts(register, big_endian))
     }
 
     /// Runs a measurement with the IQM backend and waits for results.
@@ -248,21 +1266,21 @@ impl BackendWrapper {
     ///
     /// Raises:
     ///     TypeError: Measurement evaluate function could not be used
+    ///     ValueError: A circuit of the measurement is not valid for the device
+    ///     ConnectionError: A connection error when communicating with the server
     ///     RuntimeError: Internal error measurement. Evaluation returned unknown type
     pub fn run_measurement(
         &self,
         measurement: &Bound<PyAny>,
     ) -> PyResult<Option<HashMap<String, f64>>> {
         let circuit_batch = get_circuit_list_from_measurement(measurement)?;
+        self.internal
+            .validate_measurement_circuits(&circuit_batch)
+            .map_err(iqm_error_to_pyerr)?;
         let registers = self
             .internal
             .run_circuit_batch(&circuit_batch)
-            .map_err(|err| {
-                PyRuntimeError::new_err(format!(
-                    "Something went wrong when running the list of circuits: {:?}",
-                    err
-                ))
-            })?;
+            .map_err(iqm_error_to_pyerr)?;
         self.evaluate_measurement(measurement, registers)
     }
 
@@ -314,25 +1332,18 @@ impl BackendWrapper {
     ///     Optional[Dict[str, float]]: The results of the measurement
     ///
     /// Raises:
-    ///     RunTimeError: Something went wrong either while getting the results from the server or
-    ///     during post processing.
+    ///     ConnectionError: A connection error when getting the results from the server
+    ///     RuntimeError: The job failed or something went wrong during post processing
     pub fn get_measurement_results(
         &self,
         id: String,
         measurement: &Bound<PyAny>,
     ) -> PyResult<Option<HashMap<String, f64>>> {
-        let results = self.internal.wait_for_results(id.clone()).map_err(|err| {
-            PyRuntimeError::new_err(format!(
-                "Something went wrong when getting the results from the server: {:?}",
-                err
-            ))
-        })?;
-        let registers = results_to_registers(results, id).map_err(|err| {
-            PyRuntimeError::new_err(format!(
-                "Something went wrong when processing the results into output registers: {:?}",
-                err
-            ))
-        })?;
+        let results = self
+            .internal
+            .wait_for_results(id.clone())
+            .map_err(iqm_error_to_pyerr)?;
+        let registers = results_to_registers(results, id).map_err(iqm_error_to_pyerr)?;
         self.evaluate_measurement(measurement, registers)
     }
 
@@ -345,26 +1356,69 @@ impl BackendWrapper {
     ///     str: Job ID to retrieve the results.
     ///
     /// Raises:
-    ///     RuntimeError: Something went wrong when submitting the job to the backend.
+    ///     TypeError: `circuits` argument is not a list of qoqo Circuits
+    ///     ValueError: A circuit is not valid for the device
+    ///     ConnectionError: A connection error when submitting the job to the backend
     pub fn submit_circuit_batch(&self, circuits: Vec<Bound<PyAny>>) -> PyResult<String> {
-        let mut circuit_batch: Vec<Circuit> = Vec::new();
-        for circuit in circuits.into_iter() {
-            let tmp_circuit = CircuitWrapper::from_pyany(&circuit).map_err(|err| {
-                PyTypeError::new_err(format!(
-                    "`circuits` argument is not a list of qoqo Circuits: {}",
-                    err
-                ))
-            })?;
-            circuit_batch.push(tmp_circuit)
-        }
+        let circuit_batch = get_circuit_list(circuits)?;
         self.internal
             .submit_circuit_batch(&circuit_batch)
-            .map_err(|err| {
-                PyRuntimeError::new_err(format!(
-                    "Something went wrong when submitting the job to the backend: {:?}",
-                    err
-                ))
-            })
+            .map_err(iqm_error_to_pyerr)
+    }
+
+    /// Get the compilation warnings of the individual circuits of a submitted job.
+    ///
+    /// Args:
+    ///     id (str): The ID of the job.
+    ///
+    /// Returns:
+    ///     Dict[str, List[str]]: The warnings of each circuit with warnings, keyed by circuit name.
+    ///
+    /// Raises:
+    ///     ConnectionError: A connection error when fetching the results.
+    pub fn get_circuit_warnings(&self, id: String) -> PyResult<HashMap<String, Vec<String>>> {
+        self.internal
+            .get_circuit_warnings(id)
+            .map_err(roqoqo_error_to_pyerr)
+    }
+
+    /// Resubmit the circuits of a batch job that failed on the server.
+    ///
+    /// Only the failed circuits are resubmitted, with the same settings as the original job.
+    /// Requires a server that reports the status of the individual circuits of a batch.
+    ///
+    /// Args:
+    ///     id (str): The ID of the job with failed circuits.
+    ///
+    /// Returns:
+    ///     str: The ID of the job running the failed circuits.
+    ///
+    /// Raises:
+    ///     ConnectionError: A connection error when sending the requests.
+    ///     RuntimeError: The job has no failed circuits.
+    pub fn rerun_failed_circuits(&self, id: String) -> PyResult<String> {
+        self.internal
+            .rerun_failed_circuits(id)
+            .map_err(iqm_error_to_pyerr)
+    }
+
+    /// Compile a batch of circuits on the server without executing it.
+    ///
+    /// Args:
+    ///     circuits (List[Circuit]): The circuit batch that is compiled.
+    ///
+    /// Returns:
+    ///     str: The compiled representation returned by the server, as JSON.
+    ///
+    /// Raises:
+    ///     TypeError: `circuits` argument is not a list of qoqo Circuits
+    ///     ConnectionError: A connection error when sending the circuits to the server
+    ///     RuntimeError: A circuit is not valid for the device
+    pub fn compile_on_server(&self, circuits: Vec<Bound<PyAny>>) -> PyResult<String> {
+        let circuit_batch = get_circuit_list(circuits)?;
+        self.internal
+            .compile_on_server(&circuit_batch)
+            .map_err(roqoqo_error_to_pyerr)
     }
 
     /// Fetch the results of a previously submitted batch run from the server.
@@ -377,21 +1431,58 @@ impl BackendWrapper {
     ///
     /// Raises:
     ///     ConnectionError: Something went wrong when getting the results
-    ///     PyRuntimeError: Something went wrong when converting the results into the qoqo registers
-    ///     format
+    ///     RuntimeError: The job failed or the results could not be converted into the qoqo
+    ///     registers format
     pub fn get_batch_raw_results(&self, id: String) -> PyResult<Registers> {
-        let results = self.internal.wait_for_results(id.clone()).map_err(|err| {
-            PyConnectionError::new_err(format!(
-                "Something went wrong when retrieving the results of a batch run: {}",
-                err
-            ))
-        })?;
-        results_to_registers(results, id).map_err(|err| {
-            PyRuntimeError::new_err(format!(
-                "Something went wrong when post processing the results of a batch run: {}",
-                err
-            ))
-        })
+        let results = self
+            .internal
+            .wait_for_results(id.clone())
+            .map_err(iqm_error_to_pyerr)?;
+        results_to_registers(results, id).map_err(iqm_error_to_pyerr)
+    }
+
+    /// Fetch the results of a previously submitted batch run in the native layout of the server.
+    ///
+    /// Args:
+    ///     id (str): The ID of the job
+    ///
+    /// Returns:
+    ///     List[Dict[str, List[List[int]]]]: For each circuit, the per-shot outcomes of the
+    ///                                       measured qubits by measurement key
+    ///
+    /// Raises:
+    ///     ConnectionError: Something went wrong when getting the results
+    ///     RuntimeError: The job failed or returned empty results
+    pub fn get_raw_batch_result(&self, id: String) -> PyResult<Vec<HashMap<String, Vec<Vec<u8>>>>> {
+        self.internal
+            .get_raw_batch_result(id)
+            .map_err(iqm_error_to_pyerr)
+    }
+
+    /// Fetch the results of a previously submitted batch run as counts of the measured bitstrings.
+    ///
+    /// The bit at index 0 of a register is the first character of the bitstrings, as in the
+    /// registers returned by `get_batch_raw_results`.
+    ///
+    /// Args:
+    ///     id (str): The ID of the job
+    ///
+    /// Returns:
+    ///     Dict[str, Dict[str, int]]: For each register, the number of shots for each measured
+    ///                                bitstring
+    ///
+    /// Raises:
+    ///     ConnectionError: Something went wrong when getting the results
+    ///     RuntimeError: The job failed or the results could not be converted into counts
+    pub fn get_batch_counts(
+        &self,
+        id: String,
+    ) -> PyResult<HashMap<String, HashMap<String, usize>>> {
+        let results = self
+            .internal
+            .wait_for_results(id)
+            .map_err(iqm_error_to_pyerr)?;
+        results_to_counts(&results).map_err(iqm_error_to_pyerr)
     }
 
     /// Submit a measurement to the backend for asynchronous execution.
@@ -403,7 +1494,9 @@ impl BackendWrapper {
     ///     str: Job ID to retrieve the results.
     ///
     /// Raises:
-    ///     RuntimeError: Something went wrong when submitting the job to the backend.
+    ///     RuntimeError: The circuits could not be extracted from the measurement
+    ///     ValueError: A circuit of the measurement is not valid for the device
+    ///     ConnectionError: A connection error when submitting the job to the backend
     pub fn submit_measurement(&self, measurement: &Bound<PyAny>) -> PyResult<String> {
         let circuit_batch = get_circuit_list_from_measurement(measurement).map_err(|err| {
             PyRuntimeError::new_err(format!(
@@ -411,15 +1504,190 @@ impl BackendWrapper {
                 err
             ))
         })?;
+        self.internal
+            .validate_measurement_circuits(&circuit_batch)
+            .map_err(iqm_error_to_pyerr)?;
         self.internal
             .submit_circuit_batch(&circuit_batch)
-            .map_err(|err| {
-                PyRuntimeError::new_err(format!(
-                    "Something went wrong when submitting the job to the backend: {:?}",
-                    err
-                ))
-            })
+            .map_err(iqm_error_to_pyerr)
+    }
+}
+
+/// Convert the results of a run into a dictionary compatible with Qiskit's `Counts`.
+///
+/// Follows Qiskit's little-endian convention: the bit at index 0 of the register is the last
+/// (rightmost) character of each bitstring. The returned dictionary can be passed to
+/// `qiskit.result.Counts` directly.
+///
+/// Args:
+///     registers (Tuple[Dict[str, List[List[bool]]], Dict[str, List[List[float]]], Dict[str, List[List[complex]]]]): The registers returned by `run_circuit`.
+///     register_name (str): The name of the bit register to convert.
+///
+/// Returns:
+///     Dict[str, int]: The number of shots for each measured bitstring.
+///
+/// Raises:
+///     TypeError: `registers` is not a tuple of registers.
+///     KeyError: `registers` does not contain a bit register with the given name.
+#[pyfunction]
+pub fn to_qiskit_counts(
+    registers: &Bound<PyAny>,
+    register_name: &str,
+) -> PyResult<HashMap<String, usize>> {
+    let bit_registers = extract_bit_registers(registers)?;
+    let register = bit_registers
+        .get(register_name)
+        .ok_or_else(|| PyKeyError::new_err(format!("No bit register named {}", register_name)))?;
+    Ok(bit_register_to_counts(register, true))
+}
+
+/// Export the shots of a bit register of the results of a run as CSV.
+///
+/// The CSV has a header row `shot,bit_0,bit_1,...` followed by one row per shot, with the index
+/// of the shot and the value (`0` or `1`) of each bit of the register.
+///
+/// Args:
+///     registers (Tuple[Dict[str, List[List[bool]]], Dict[str, List[List[float]]], Dict[str, List[List[complex]]]]): The registers returned by `run_circuit`.
+///     register_name (str): The name of the bit register to export.
+///
+/// Returns:
+///     str: The CSV.
+///
+/// Raises:
+///     TypeError: `registers` is not a tuple of registers.
+///     KeyError: `registers` does not contain a bit register with the given name.
+#[pyfunction]
+pub fn results_to_csv(registers: &Bound<PyAny>, register_name: &str) -> PyResult<String> {
+    let bit_registers = extract_bit_registers(registers)?;
+    if !bit_registers.contains_key(register_name) {
+        return Err(PyKeyError::new_err(format!(
+            "No bit register named {}",
+            register_name
+        )));
+    }
+    Ok(registers_to_csv(
+        &(bit_registers, HashMap::new(), HashMap::new()),
+        register_name,
+    ))
+}
+
+/// Compute the number of ones and zeros measured for each bit of a bit register.
+///
+/// Useful as a quick diagnostic of the readout, e.g. to spot qubits that are stuck in one state.
+///
+/// Args:
+///     registers (Tuple[Dict[str, List[List[bool]]], Dict[str, List[List[float]]], Dict[str, List[List[complex]]]]): The registers returned by `run_circuit`.
+///     register_name (str): The name of the bit register to analyse.
+///
+/// Returns:
+///     List[Dict[str, Union[int, float]]]: For each bit of the register, a dictionary with the
+///                                         index `qubit` of the bit, the number of `ones` and
+///                                         `zeros` and the fraction `mean` of ones.
+///
+/// Raises:
+///     TypeError: `registers` is not a tuple of registers.
+///     KeyError: `registers` does not contain a bit register with the given name.
+#[pyfunction]
+pub fn qubit_statistics(
+    py: Python,
+    registers: &Bound<PyAny>,
+    register_name: &str,
+) -> PyResult<Vec<Py<PyDict>>> {
+    let bit_registers = extract_bit_registers(registers)?;
+    if !bit_registers.contains_key(register_name) {
+        return Err(PyKeyError::new_err(format!(
+            "No bit register named {}",
+            register_name
+        )));
+    }
+    roqoqo_iqm::qubit_statistics(
+        &(bit_registers, HashMap::new(), HashMap::new()),
+        register_name,
+    )
+    .into_iter()
+    .map(|stat| {
+        let dict = PyDict::new_bound(py);
+        dict.set_item("qubit", stat.qubit)?;
+        dict.set_item("ones", stat.ones)?;
+        dict.set_item("zeros", stat.zeros)?;
+        dict.set_item("mean", stat.mean)?;
+        Ok(dict.unbind())
+    })
+    .collect()
+}
+
+/// Convert a Python device into one of the IQM devices.
+fn extract_iqm_device(device: &Bound<PyAny>) -> PyResult<IqmDevice> {
+    let device_pyany = device.as_gil_ref();
+    if let Ok(dev) = DenebDeviceWrapper::from_pyany(device_pyany.into()) {
+        Ok(IqmDevice::from(dev))
+    } else if let Ok(dev) = GarnetDeviceWrapper::from_pyany(device_pyany.into()) {
+        Ok(IqmDevice::from(dev))
+    } else if let Ok(dev) = ResonatorFreeDeviceWrapper::from_pyany(device_pyany.into()) {
+        Ok(IqmDevice::from(dev))
+    } else {
+        Err(PyRuntimeError::new_err(
+            "Could not convert input device to one of the available devices.".to_string(),
+        ))
+    }
+}
+
+/// Convert an [IqmBackendError] into the Python exception that best matches the error.
+///
+/// Errors in the submitted circuits are raised as `ValueError`, errors reported by the IQM server
+/// (failed or aborted jobs, invalid results) as `RuntimeError`. Errors propagated from roqoqo are
+/// converted with [roqoqo_error_to_pyerr].
+///
+/// The conversion cannot be a `From` implementation, as neither type is defined in this crate.
+fn iqm_error_to_pyerr(err: IqmBackendError) -> PyErr {
+    match err {
+        IqmBackendError::EmptyCircuit
+        | IqmBackendError::RegisterTooSmall { .. }
+        | IqmBackendError::InvalidCircuit { .. } => PyValueError::new_err(err.to_string()),
+        IqmBackendError::RoqoqoBackendError(err) => roqoqo_error_to_pyerr(err),
+        _ => PyRuntimeError::new_err(err.to_string()),
+    }
+}
+
+/// Convert a [RoqoqoBackendError] into the Python exception that best matches the error.
+fn roqoqo_error_to_pyerr(err: RoqoqoBackendError) -> PyErr {
+    match err {
+        RoqoqoBackendError::NetworkError { .. } => PyConnectionError::new_err(err.to_string()),
+        RoqoqoBackendError::MissingAuthentication { .. } => {
+            PyPermissionError::new_err(err.to_string())
+        }
+        RoqoqoBackendError::Timeout { .. } => PyTimeoutError::new_err(err.to_string()),
+        RoqoqoBackendError::OperationNotInBackend { .. } => PyValueError::new_err(err.to_string()),
+        _ => PyRuntimeError::new_err(err.to_string()),
+    }
+}
+
+/// Helper function to convert a list of Python circuits into a list of roqoqo circuits.
+fn get_circuit_list(circuits: Vec<Bound<PyAny>>) -> PyResult<Vec<Circuit>> {
+    let mut circuit_batch: Vec<Circuit> = Vec::new();
+    for circuit in circuits.into_iter() {
+        let tmp_circuit = CircuitWrapper::from_pyany(&circuit).map_err(|err| {
+            PyTypeError::new_err(format!(
+                "`circuits` argument is not a list of qoqo Circuits: {}",
+                err
+            ))
+        })?;
+        circuit_batch.push(tmp_circuit)
     }
+    Ok(circuit_batch)
+}
+
+/// Helper function to extract the bit registers from the registers returned by running a circuit.
+fn extract_bit_registers(registers: &Bound<PyAny>) -> PyResult<HashMap<String, Vec<Vec<bool>>>> {
+    let (bit_registers, _, _) = registers
+        .extract::<(HashMap<String, Vec<Vec<bool>>>, Bound<PyAny>, Bound<PyAny>)>()
+        .map_err(|err| {
+            PyTypeError::new_err(format!(
+                "`registers` argument is not a tuple of registers: {}",
+                err
+            ))
+        })?;
+    Ok(bit_registers)
 }
 
 /// Helper function to construct the list of circuits from a measurement by appending each circuit
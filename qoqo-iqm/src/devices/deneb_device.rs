@@ -15,8 +15,9 @@ use pyo3::prelude::*;
 use pyo3::types::PyByteArray;
 
 use bincode::{deserialize, serialize};
+use qoqo::convert_into_circuit;
 use roqoqo::devices::Device;
-use roqoqo_iqm::devices::DenebDevice;
+use roqoqo_iqm::devices::{DenebDevice, IqmDevice};
 
 /// IQM Deneb device
 ///
@@ -79,6 +80,63 @@ impl DenebDeviceWrapper {
         self.internal.set_endpoint_url(new_url)
     }
 
+    /// Return the qubits coupled to each computational resonator of the device.
+    ///
+    /// Returns:
+    ///     list[list[int]]: For each resonator, the qubits coupled to it.
+    pub fn resonator_couplings(&self) -> Vec<Vec<usize>> {
+        self.internal.resonator_couplings().to_vec()
+    }
+
+    /// Change the computational resonators of the device and the qubits coupled to them.
+    ///
+    /// Args:
+    ///     resonator_couplings (list[list[int]]): For each resonator, the qubits coupled to it.
+    pub fn set_resonator_couplings(&mut self, resonator_couplings: Vec<Vec<usize>>) {
+        self.internal.set_resonator_couplings(resonator_couplings)
+    }
+
+    /// Return the maximum number of moves between qubits and resonators allowed in a circuit.
+    ///
+    /// Returns:
+    ///     Optional[int]: The maximum number of moves, or None if there is no limit.
+    pub fn max_resonator_moves(&self) -> Option<usize> {
+        self.internal.max_resonator_moves()
+    }
+
+    /// Limit the number of moves between qubits and resonators (`SingleExcitationLoad` and
+    /// `SingleExcitationStore` operations) allowed in a circuit.
+    ///
+    /// Args:
+    ///     max_resonator_moves (Optional[int]): The maximum number of moves, or None for no limit.
+    pub fn set_max_resonator_moves(&mut self, max_resonator_moves: Option<usize>) {
+        self.internal.set_max_resonator_moves(max_resonator_moves)
+    }
+
+    /// Validate a circuit against the device without submitting it.
+    ///
+    /// Checks the connectivity of the gates, the loads and stores of the resonator and the
+    /// maximum number of resonator moves. No access token or backend is needed, so circuits can be
+    /// validated offline, e.g. in CI pipelines.
+    ///
+    /// Args:
+    ///     circuit (Circuit): The circuit to validate.
+    ///
+    /// Raises:
+    ///     TypeError: Circuit argument cannot be converted to qoqo Circuit
+    ///     ValueError: The circuit is not valid for the device
+    pub fn validate_circuit(&self, circuit: &Bound<PyAny>) -> PyResult<()> {
+        let circuit = convert_into_circuit(circuit).map_err(|err| {
+            PyTypeError::new_err(format!(
+                "Circuit argument cannot be converted to qoqo Circuit: {:?}",
+                err
+            ))
+        })?;
+        self.internal
+            .validate_circuit(&circuit)
+            .map_err(|err| PyValueError::new_err(err.to_string()))
+    }
+
     /// Return a copy of the DenebDevice (copy here produces a deepcopy).
     ///
     /// Returns:
@@ -143,6 +201,14 @@ impl DenebDeviceWrapper {
         self.internal.number_qubits()
     }
 
+    /// Return the hqslang name of the native two-qubit gate of the device.
+    ///
+    /// Returns:
+    ///     str: The name of the native two-qubit gate.
+    pub fn native_two_qubit_gate(&self) -> &'static str {
+        IqmDevice::from(&self.internal).native_two_qubit_gate()
+    }
+
     /// Return the URL of the API endpoint for the device.
     ///
     /// Returns:
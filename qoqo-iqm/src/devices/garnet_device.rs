@@ -16,7 +16,7 @@ use pyo3::types::PyByteArray;
 
 use bincode::{deserialize, serialize};
 use roqoqo::devices::Device;
-use roqoqo_iqm::devices::GarnetDevice;
+use roqoqo_iqm::devices::{GarnetDevice, IqmDevice};
 
 /// IQM Garnet device
 ///
@@ -143,6 +143,14 @@ impl GarnetDeviceWrapper {
         self.internal.number_qubits()
     }
 
+    /// Return the hqslang name of the native two-qubit gate of the device.
+    ///
+    /// Returns:
+    ///     str: The name of the native two-qubit gate.
+    pub fn native_two_qubit_gate(&self) -> &'static str {
+        IqmDevice::from(&self.internal).native_two_qubit_gate()
+    }
+
     /// Return the URL of the API endpoint for the device.
     ///
     /// Returns:
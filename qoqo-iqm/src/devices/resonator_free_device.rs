@@ -16,7 +16,7 @@ use pyo3::types::PyByteArray;
 
 use bincode::{deserialize, serialize};
 use roqoqo::devices::Device;
-use roqoqo_iqm::devices::ResonatorFreeDevice;
+use roqoqo_iqm::devices::{IqmDevice, ResonatorFreeDevice};
 
 /// Six-qubit device similar to the Deneb device, but without the central resonator and with CZ
 /// gates available between each pair of qubits. Used to transpile algorithms for use on the Deneb
@@ -136,6 +136,14 @@ impl ResonatorFreeDeviceWrapper {
         self.internal.number_qubits()
     }
 
+    /// Return the hqslang name of the native two-qubit gate of the device.
+    ///
+    /// Returns:
+    ///     str: The name of the native two-qubit gate.
+    pub fn native_two_qubit_gate(&self) -> &'static str {
+        IqmDevice::from(&self.internal).native_two_qubit_gate()
+    }
+
     /// Return the list of pairs of qubits linked by a native two-qubit-gate in the device.
     ///
     /// A pair of qubits is considered linked by a native two-qubit-gate if the device
@@ -21,14 +21,17 @@
 
 use pyo3::prelude::*;
 use pyo3::types::PyDict;
-use pyo3::wrap_pymodule;
+use pyo3::{wrap_pyfunction, wrap_pymodule};
 
 /// Qoqo-IQM devices
 pub mod devices;
 pub use devices::*;
 
 mod backend;
-pub use backend::BackendWrapper;
+pub use backend::{qubit_statistics, results_to_csv, to_qiskit_counts, BackendWrapper};
+
+mod measurement_basis;
+pub use measurement_basis::{add_basis_measurement, add_partial_measurement};
 
 /// IQM python interface
 ///
@@ -38,6 +41,11 @@ fn qoqo_iqm(_py: Python, module: &Bound<PyModule>) -> PyResult<()> {
     module.add_class::<BackendWrapper>()?;
     module.add_class::<DenebDeviceWrapper>()?;
     module.add_class::<GarnetDeviceWrapper>()?;
+    module.add_function(wrap_pyfunction!(to_qiskit_counts, module)?)?;
+    module.add_function(wrap_pyfunction!(results_to_csv, module)?)?;
+    module.add_function(wrap_pyfunction!(qubit_statistics, module)?)?;
+    module.add_function(wrap_pyfunction!(add_basis_measurement, module)?)?;
+    module.add_function(wrap_pyfunction!(add_partial_measurement, module)?)?;
 
     let wrapper = wrap_pymodule!(devices::iqm_devices);
     module.add_wrapped(wrapper)?;
@@ -0,0 +1,98 @@
+// Copyright © 2020-2023 HQS Quantum Simulations GmbH. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the
+// License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either
+// express or implied. See the License for the specific language governing permissions and
+// limitations under the License.
+
+use pyo3::exceptions::{PyTypeError, PyValueError};
+use pyo3::prelude::*;
+
+use qoqo::{convert_into_circuit, CircuitWrapper};
+use roqoqo_iqm::PauliBasis;
+
+/// Add the measurement of a qubit in a Pauli basis to a circuit.
+///
+/// IQM devices only measure in the Z basis. For the X and Y bases, a `RotateXY` gate mapping the
+/// eigenstate with eigenvalue +1 onto `|0>` is added before the `MeasureQubit`.
+///
+/// Args:
+///     circuit (Circuit): The circuit the measurement is added to.
+///     qubit (int): The qubit to measure.
+///     basis (str): The basis of the measurement, one of "X", "Y" or "Z".
+///     register (str): The name of the bit register the result is written to.
+///     index (int): The index in the register the result is written to.
+///
+/// Returns:
+///     Circuit: A copy of the circuit with the added measurement.
+///
+/// Raises:
+///     TypeError: Circuit argument cannot be converted to qoqo Circuit
+///     ValueError: Unknown basis
+#[pyfunction]
+pub fn add_basis_measurement(
+    circuit: &Bound<PyAny>,
+    qubit: usize,
+    basis: &str,
+    register: &str,
+    index: usize,
+) -> PyResult<CircuitWrapper> {
+    let mut circuit = convert_into_circuit(circuit).map_err(|err| {
+        PyTypeError::new_err(format!(
+            "Circuit argument cannot be converted to qoqo Circuit: {:?}",
+            err
+        ))
+    })?;
+    let basis = parse_pauli_basis(basis)?;
+    roqoqo_iqm::add_basis_measurement(&mut circuit, qubit, basis, register, index);
+    Ok(CircuitWrapper { internal: circuit })
+}
+
+/// Add the measurement of a subset of qubits to a circuit.
+///
+/// Adds an output `DefinitionBit` with one entry per measured qubit and a `MeasureQubit` for each
+/// of the given qubits. The result of `qubits[i]` is written to index `i` of the register.
+///
+/// Args:
+///     circuit (Circuit): The circuit the measurements are added to.
+///     qubits (List[int]): The qubits to measure.
+///     register (str): The name of the bit register the results are written to.
+///
+/// Returns:
+///     Circuit: A copy of the circuit with the added measurements.
+///
+/// Raises:
+///     TypeError: Circuit argument cannot be converted to qoqo Circuit
+#[pyfunction]
+pub fn add_partial_measurement(
+    circuit: &Bound<PyAny>,
+    qubits: Vec<usize>,
+    register: &str,
+) -> PyResult<CircuitWrapper> {
+    let mut circuit = convert_into_circuit(circuit).map_err(|err| {
+        PyTypeError::new_err(format!(
+            "Circuit argument cannot be converted to qoqo Circuit: {:?}",
+            err
+        ))
+    })?;
+    roqoqo_iqm::add_partial_measurement(&mut circuit, &qubits, register);
+    Ok(CircuitWrapper { internal: circuit })
+}
+
+/// Convert the name of a Pauli basis ("X", "Y" or "Z") into a [PauliBasis].
+pub(crate) fn parse_pauli_basis(basis: &str) -> PyResult<PauliBasis> {
+    match basis {
+        "X" => Ok(PauliBasis::X),
+        "Y" => Ok(PauliBasis::Y),
+        "Z" => Ok(PauliBasis::Z),
+        _ => Err(PyValueError::new_err(format!(
+            "Unknown basis {}, expected one of X, Y or Z",
+            basis
+        ))),
+    }
+}
@@ -10,10 +10,15 @@
 // express or implied. See the License for the specific language governing permissions and
 // limitations under the License.
 
+use pyo3::exceptions::{PyConnectionError, PyKeyError, PyTypeError, PyValueError};
 use pyo3::prelude::*;
 use pyo3::Python;
+use qoqo::CircuitWrapper;
 use qoqo_iqm::devices;
-use qoqo_iqm::BackendWrapper;
+use qoqo_iqm::{results_to_csv, to_qiskit_counts, BackendWrapper};
+use roqoqo::operations::*;
+use roqoqo::Circuit;
+use std::collections::HashMap;
 use std::env;
 
 #[test]
@@ -177,3 +182,87 @@ fn test_creating_backend_garnet_device() {
         })
     }
 }
+
+#[test]
+fn test_error_types() {
+    pyo3::prepare_freethreaded_python();
+
+    Python::with_gil(|py| {
+        let mut device = devices::DenebDeviceWrapper::new();
+        device.set_endpoint_url("http://localhost:1/deneb/jobs".to_string());
+        let backend_type = py.get_type_bound::<BackendWrapper>();
+        let backend = backend_type
+            .call1((Py::new(py, device).unwrap(), "DUMMY_ACCESS_TOKEN"))
+            .unwrap();
+
+        // Invalid circuits raise a ValueError
+        let empty_circuit = CircuitWrapper {
+            internal: Circuit::new(),
+        };
+        let err = backend
+            .call_method1("run_circuit", (empty_circuit,))
+            .unwrap_err();
+        assert!(err.is_instance_of::<PyValueError>(py));
+
+        // Failing requests raise a ConnectionError
+        let mut circuit = Circuit::new();
+        circuit += RotateXY::new(0, 1.0.into(), 0.0.into());
+        circuit += DefinitionBit::new("ro".to_string(), 1, true);
+        circuit += MeasureQubit::new(0, "ro".to_string(), 0);
+        let err = backend
+            .call_method1("run_circuit", (CircuitWrapper { internal: circuit },))
+            .unwrap_err();
+        assert!(err.is_instance_of::<PyConnectionError>(py));
+    })
+}
+
+#[test]
+fn test_to_qiskit_counts() {
+    pyo3::prepare_freethreaded_python();
+
+    Python::with_gil(|py| {
+        let mut bit_registers: HashMap<String, Vec<Vec<bool>>> = HashMap::new();
+        bit_registers.insert(
+            "ro".to_string(),
+            vec![
+                vec![true, false, false],
+                vec![true, false, false],
+                vec![false, false, true],
+            ],
+        );
+        let float_registers: HashMap<String, Vec<Vec<f64>>> = HashMap::new();
+        let complex_registers: HashMap<String, Vec<Vec<f64>>> = HashMap::new();
+        let registers: PyObject = (bit_registers, float_registers, complex_registers).into_py(py);
+
+        // Qiskit convention: bit 0 of the register is the rightmost character
+        let counts = to_qiskit_counts(registers.bind(py), "ro").unwrap();
+        assert_eq!(
+            counts,
+            HashMap::from([("001".to_string(), 2), ("100".to_string(), 1)])
+        );
+
+        let err = to_qiskit_counts(registers.bind(py), "missing").unwrap_err();
+        assert!(err.is_instance_of::<PyKeyError>(py));
+        let err = to_qiskit_counts(&1_i32.into_py(py).into_bound(py), "ro").unwrap_err();
+        assert!(err.is_instance_of::<PyTypeError>(py));
+    })
+}
+
+#[test]
+fn test_results_to_csv() {
+    pyo3::prepare_freethreaded_python();
+
+    Python::with_gil(|py| {
+        let mut bit_registers: HashMap<String, Vec<Vec<bool>>> = HashMap::new();
+        bit_registers.insert("ro".to_string(), vec![vec![true, false], vec![false, true]]);
+        let float_registers: HashMap<String, Vec<Vec<f64>>> = HashMap::new();
+        let complex_registers: HashMap<String, Vec<Vec<f64>>> = HashMap::new();
+        let registers: PyObject = (bit_registers, float_registers, complex_registers).into_py(py);
+
+        let csv = results_to_csv(registers.bind(py), "ro").unwrap();
+        assert_eq!(csv, "shot,bit_0,bit_1\n0,1,0\n1,0,1\n");
+
+        let err = results_to_csv(registers.bind(py), "missing").unwrap_err();
+        assert!(err.is_instance_of::<PyKeyError>(py));
+    })
+}
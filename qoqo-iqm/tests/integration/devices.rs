@@ -9,3 +9,36 @@
 // License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either
 // express or implied. See the License for the specific language governing permissions and
 // limitations under the License.
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use qoqo::CircuitWrapper;
+use qoqo_iqm::devices::DenebDeviceWrapper;
+use roqoqo::operations::*;
+use roqoqo::Circuit;
+
+#[test]
+fn test_deneb_validate_circuit() {
+    pyo3::prepare_freethreaded_python();
+
+    Python::with_gil(|py| {
+        let device = Py::new(py, DenebDeviceWrapper::new()).unwrap();
+        let device = device.bind(py);
+
+        let mut circuit = Circuit::new();
+        circuit += CZQubitResonator::new(0, 0);
+        circuit += DefinitionBit::new("ro".to_string(), 1, true);
+        circuit += MeasureQubit::new(0, "ro".to_string(), 0);
+        device
+            .call_method1("validate_circuit", (CircuitWrapper { internal: circuit },))
+            .unwrap();
+
+        // Qubits are only coupled through the resonator
+        let mut circuit = Circuit::new();
+        circuit += ControlledPauliZ::new(0, 1);
+        let err = device
+            .call_method1("validate_circuit", (CircuitWrapper { internal: circuit },))
+            .unwrap_err();
+        assert!(err.is_instance_of::<PyValueError>(py));
+    })
+}
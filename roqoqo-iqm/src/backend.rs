@@ -10,30 +10,57 @@
 // express or implied. See the License for the specific language governing permissions and
 // limitations under the License.
 
+use crate::decomposition::{decompose_multi_qubit_gates, implement_cz_with_resonator};
 use crate::devices::IqmDevice;
-use crate::interface::{call_circuit, IqmCircuit, MeasuredQubitsMap};
-use crate::IqmBackendError;
+use crate::interface::{
+    _convert_qubit_name_iqm_to_qoqo, _convert_qubit_name_qoqo_to_iqm,
+    _convert_resonator_name_iqm_to_qoqo, call_circuit, sanitize_circuit_name, IqmCircuit,
+    IqmInstruction, MeasuredQubitsMap,
+};
+use crate::quantum_architecture::QuantumArchitecture;
+use crate::{add_basis_measurement, IqmBackendError, IqmErrorDetail, PauliBasis, SubmitError};
 
-use reqwest::blocking::Response;
+use qoqo_calculator::{Calculator, CalculatorFloat};
+use reqwest::blocking::{Client, ClientBuilder, RequestBuilder, Response};
 use roqoqo::backends::{EvaluatingBackend, RegisterResult};
-use roqoqo::devices::Device;
+use roqoqo::devices::{Device, GenericDevice};
+use roqoqo::measurements::Measure;
 use roqoqo::operations::*;
 use roqoqo::registers::{BitOutputRegister, ComplexOutputRegister, FloatOutputRegister, Registers};
 use roqoqo::{Circuit, RoqoqoBackendError};
 
-use std::collections::{HashMap, HashSet};
+use std::borrow::Cow;
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::env::var;
 use std::error::Error;
-use std::time::{Duration, Instant};
+use std::fs;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use std::{fmt, thread};
 
-use reqwest::header::{HeaderMap, HeaderValue};
+use reqwest::header::{HeaderMap, HeaderValue, CONTENT_TYPE};
 use serde::{Deserialize, Serialize};
 
-// Timeout for querying the REST API for results
+#[cfg(feature = "async")]
+mod async_backend;
+#[cfg(feature = "async")]
+pub use async_backend::AsyncBackend;
+
+// Default timeout for querying the REST API for results
 const TIMEOUT_SECS: f64 = 60.0;
-// Time interval between REST API queries
+// Default time interval between REST API queries
 const SECONDS_BETWEEN_CALLS: f64 = 4.0;
+// Path of the credentials file of the named profiles, relative to the home directory
+const CREDENTIALS_FILE_PATH: &str = ".config/qoqo_iqm/credentials";
+// Number of recent jobs searched when aborting jobs by name
+const ABORT_SEARCH_LIMIT: usize = 100;
+// Number of times the results of a job reported ready without measurements are re-fetched
+const EMPTY_RESULT_RETRIES: usize = 3;
+// Realm and client ID of the IQM auth server used to refresh access tokens
+const AUTH_REALM: &str = "cortex";
+const AUTH_CLIENT_ID: &str = "iqm_client";
 
 #[derive(PartialEq, Debug, Clone, Serialize, Deserialize)]
 struct SingleQubitMapping {
@@ -55,6 +82,8 @@ struct IqmRunRequest {
     #[serde(default)]
     circuit_duration_check: bool,
     heralding_mode: HeraldingMode,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    priority: Option<String>,
 }
 
 #[derive(PartialEq, Debug, Clone, Serialize, Deserialize)]
@@ -62,31 +91,69 @@ struct ResponseBody {
     id: String,
 }
 
+/// Response body of the calibration metrics endpoint.
+#[derive(PartialEq, Debug, Clone, Serialize, Deserialize)]
+struct CalibrationMetrics {
+    #[serde(default)]
+    calibration_set_id: Option<String>,
+    metrics: HashMap<String, serde_json::Value>,
+}
+
+/// Summary of a job submitted to the IQM server, as returned when listing the recent jobs.
+#[derive(PartialEq, Eq, Debug, Clone, Serialize, Deserialize)]
+pub struct JobSummary {
+    /// Job ID
+    pub id: String,
+    /// Status of the job (e.g. `pending compilation`, `ready`, `failed`)
+    pub status: String,
+    /// Submission time of the job, if provided by the server
+    #[serde(default)]
+    pub submitted_at: Option<String>,
+    /// Name of the job, if provided by the server
+    #[serde(default)]
+    pub name: Option<String>,
+}
+
+/// Response body of requests rejected by the server, with the reason in the detail field.
 #[derive(PartialEq, Debug, Clone, Serialize, Deserialize)]
-struct AbortResponse {
+struct DetailResponse {
     detail: String,
 }
 
+/// Status of a job submitted to the IQM server.
+///
+/// Statuses introduced by newer server versions, e.g. `pending deletion`, are deserialized into
+/// [Status::Unknown] and treated as non-terminal.
 #[derive(Eq, PartialEq, Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
-enum Status {
+pub enum Status {
+    /// The job is waiting to be compiled
     #[serde(rename = "pending compilation")]
     PendingCompilation,
+    /// The job is compiled and waiting to be executed
     #[serde(rename = "pending execution")]
     PendingExecution,
+    /// The job has finished and its results are available
     #[serde(rename = "ready")]
     Ready,
+    /// The job has failed
     #[serde(rename = "failed")]
     Failed,
+    /// The job has been aborted
     #[serde(rename = "aborted")]
     Aborted,
+    /// A status unknown to this client version
+    #[serde(untagged)]
+    Unknown(String),
 }
 
-#[derive(Eq, PartialEq, Debug, Clone, Serialize, Deserialize)]
+/// Heralding mode of the jobs, see [Backend::set_heralding_mode].
+#[derive(Eq, PartialEq, Debug, Clone, Copy, Default, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
-enum HeraldingMode {
+pub enum HeraldingMode {
     #[serde(rename = "none")]
     /// Do not do any heralding.
+    #[default]
     None,
     #[serde(rename = "zeros")]
     /// Perform a heralding measurement, only retain shots with an all-zeros result.
@@ -96,19 +163,46 @@ enum HeraldingMode {
     Zeros,
 }
 
+/// Source of the access token of a [Backend], see [Backend::token_source].
+#[derive(Eq, PartialEq, Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub enum TokenSource {
+    /// The token was passed as an argument to [Backend::new].
+    #[default]
+    Argument,
+    /// The token was read from the `IQM_TOKEN` environment variable.
+    EnvironmentVariable,
+    /// The token was read from a profile of a credentials file, see [Backend::new_with_profile].
+    CredentialsProfile,
+    /// The token was read from the tokens file the `IQM_TOKENS_FILE` environment variable points to.
+    TokensFile,
+}
+
+/// Status of a single circuit of a batch job, for servers reporting failures per circuit.
+#[derive(Eq, PartialEq, Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum CircuitStatus {
+    Ready,
+    Failed,
+}
+
 /// Measurement results from a single circuit. For each measurement operation in the circuit, maps
 /// the measurement key to the corresponding results. The measurement key is specified in the
 /// `measure` IqmInstruction, and it is currently set equal to the name of the output register. The
 /// outer Vec elements correspond to shots, and the inner Vec elements to the qubits measured in the
 /// measurement operation and the respective outcomes.
-type CircuitResult = HashMap<String, Vec<Vec<u8>>>;
-type BatchResult = Vec<CircuitResult>;
+pub type CircuitResult = HashMap<String, Vec<Vec<u8>>>;
+/// Measurement results of a batch job, with the results of each circuit in submission order.
+pub type BatchResult = Vec<CircuitResult>;
+
+/// ID of each job aborted by [Backend::abort_jobs_by_prefix] with the result of its abortion.
+pub type AbortResults = Vec<(String, Result<(), IqmBackendError>)>;
 
 /// Metadata describing a circuit execution job.
 #[derive(PartialEq, Debug, Clone, Serialize, Deserialize)]
 struct Metadata {
-    // #[serde(default)]
-    // calibration_set_id: Option<String>,
+    /// ID of the calibration set used to execute the job
+    #[serde(default)]
+    calibration_set_id: Option<String>,
     /// Copy of the original IqmRunRequest sent to the server
     request: IqmRunRequest,
     // #[serde(default)]
@@ -133,8 +227,63 @@ pub struct IqmRunResult {
     /// Warnings from the IQM device
     #[serde(default)]
     warnings: Option<Vec<String>>,
+    /// Status of each circuit of the batch, if reported by the server
+    #[serde(default)]
+    circuit_statuses: Option<Vec<CircuitStatus>>,
+    /// Compilation warnings of each circuit of the batch, if reported by the server
+    #[serde(default)]
+    circuit_warnings: Option<Vec<Vec<String>>>,
+    /// Number of jobs queued before the job, if reported by the server
+    #[serde(default)]
+    queue_position: Option<usize>,
+    /// Average execution time of a job on the device in seconds, if reported by the server
+    #[serde(default)]
+    average_execution_time: Option<f64>,
+}
+
+impl IqmRunResult {
+    /// Returns the warnings returned by the IQM device for the job.
+    pub fn warnings(&self) -> Vec<String> {
+        self.warnings.clone().unwrap_or_default()
+    }
+
+    /// Returns the compilation warnings of the circuits of the batch, keyed by circuit name.
+    ///
+    /// Only circuits with warnings are contained. Circuits missing from the copy of the request
+    /// returned with the results are keyed by their index in the batch. Empty if the server does not
+    /// report warnings for the individual circuits.
+    pub fn circuit_warnings(&self) -> HashMap<String, Vec<String>> {
+        let circuits = &self.metadata.request.circuits;
+        self.circuit_warnings
+            .iter()
+            .flatten()
+            .enumerate()
+            .filter(|(_, warnings)| !warnings.is_empty())
+            .map(|(index, warnings)| {
+                let name = circuits
+                    .get(index)
+                    .map(|circuit| circuit.name.clone())
+                    .unwrap_or_else(|| index.to_string());
+                (name, warnings.clone())
+            })
+            .collect()
+    }
+
+    /// Returns the indices of the circuits of the batch that failed.
+    ///
+    /// Empty if the server does not report the status of the individual circuits.
+    pub fn failed_circuits(&self) -> Vec<usize> {
+        self.circuit_statuses
+            .iter()
+            .flatten()
+            .enumerate()
+            .filter(|(_, status)| **status == CircuitStatus::Failed)
+            .map(|(index, _)| index)
+            .collect()
+    }
 }
 
+#[allow(dead_code)]
 #[derive(Eq, PartialEq, Debug, Clone, Serialize, Deserialize)]
 struct IqmRunStatus {
     status: Status,
@@ -142,6 +291,7 @@ struct IqmRunStatus {
     warnings: Option<Vec<String>>,
 }
 
+#[allow(dead_code)]
 #[derive(Eq, PartialEq, Debug, Clone, Serialize, Deserialize)]
 struct Token {
     pid: u64,
@@ -152,6 +302,23 @@ struct Token {
     auth_server_url: String,
 }
 
+/// Credentials used to refresh an expired access token, see [Backend::set_refresh_credentials].
+#[derive(Eq, PartialEq, Debug, Clone, Serialize, Deserialize)]
+struct RefreshCredentials {
+    refresh_token: String,
+    auth_server_url: String,
+}
+
+/// Tokens returned by the auth server when refreshing the access token.
+#[derive(Eq, PartialEq, Debug, Clone, Serialize, Deserialize)]
+struct RefreshedToken {
+    access_token: String,
+    refresh_token: String,
+}
+
+/// Form parameters of a request refreshing the access token.
+type RefreshForm = [(&'static str, String); 3];
+
 #[derive(Debug, Clone)]
 struct TokenError {
     msg: String,
@@ -164,16 +331,446 @@ impl fmt::Display for TokenError {
     }
 }
 
+/// Settings of the HTTP client used by the [Backend].
+#[derive(Eq, PartialEq, Debug, Clone)]
+struct ClientConfig {
+    https_only: bool,
+    pool_max_idle_per_host: Option<usize>,
+    tcp_keepalive: Option<Duration>,
+}
+
+impl ClientConfig {
+    /// Apply the settings to a client builder.
+    fn apply(&self, builder: ClientBuilder) -> ClientBuilder {
+        let mut builder = builder.https_only(self.https_only);
+        if let Some(max_idle) = self.pool_max_idle_per_host {
+            builder = builder.pool_max_idle_per_host(max_idle);
+        }
+        if let Some(interval) = self.tcp_keepalive {
+            builder = builder.tcp_keepalive(interval);
+        }
+        builder
+    }
+}
+
+/// Cost model used to estimate the cost of a job in credits.
+///
+/// The cost of a circuit is `shots * (cost_per_shot + cost_per_gate * number_of_gates)`, and the
+/// cost of a job is the sum of the costs of its circuits.
+#[derive(PartialEq, Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct CostModel {
+    /// Cost of a single shot of a circuit, independent of the circuit
+    pub cost_per_shot: f64,
+    /// Additional cost of a single shot for each gate in the circuit
+    pub cost_per_gate: f64,
+}
+
+impl Default for CostModel {
+    fn default() -> Self {
+        Self {
+            cost_per_shot: 1.0,
+            cost_per_gate: 0.0,
+        }
+    }
+}
+
+/// Policy for retrying the submission of a job after a network error or a server error.
+///
+/// The delay before each retry is doubled, starting from `initial_backoff`, up to `max_backoff`.
+/// Retries stop once the next one would end after the timeout of the backend, regardless of
+/// `max_retries`. All attempts of a submission are sent with the same idempotency key, so that the
+/// server can discard duplicates.
+#[derive(Eq, PartialEq, Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetryPolicy {
+    /// Maximum number of retries after the first attempt
+    pub max_retries: usize,
+    /// Delay before the first retry
+    pub initial_backoff: Duration,
+    /// Maximum delay between two attempts
+    pub max_backoff: Duration,
+}
+
+impl RetryPolicy {
+    /// Returns the delay before the retry with the given index, starting from 0.
+    fn backoff(&self, retry: usize) -> Duration {
+        let factor = u32::try_from(retry)
+            .ok()
+            .and_then(|retry| 2_u32.checked_pow(retry))
+            .unwrap_or(u32::MAX);
+        self.initial_backoff
+            .checked_mul(factor)
+            .unwrap_or(self.max_backoff)
+            .min(self.max_backoff)
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 0,
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(30),
+        }
+    }
+}
+
+/// Templates of the URLs of the endpoints used besides the job submission endpoint.
+///
+/// The templates can contain the placeholders `{jobs_url}` for the job submission URL of the
+/// device, `{base_url}` for the job submission URL without a trailing `/jobs` segment and `{id}`
+/// for the ID of the job. The defaults match the URL layout of the IQM servers.
+#[derive(Eq, PartialEq, Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EndpointPaths {
+    /// Template of the URL returning the status and results of a job
+    pub job_status: String,
+    /// Template of the URL aborting a job
+    pub abort: String,
+    /// Template of the URL returning the quantum architecture of the device
+    pub quantum_architecture: String,
+    /// Template of the URL returning the metrics of the latest calibration of the device
+    pub calibration_metrics: String,
+}
+
+impl EndpointPaths {
+    /// Fill in the placeholders of a template.
+    fn format(template: &str, jobs_url: &str, id: &str) -> String {
+        let jobs_url = jobs_url.trim_end_matches('/');
+        let base_url = jobs_url.strip_suffix("/jobs").unwrap_or(jobs_url);
+        template
+            .replace("{jobs_url}", jobs_url)
+            .replace("{base_url}", base_url)
+            .replace("{id}", id)
+    }
+}
+
+impl Default for EndpointPaths {
+    fn default() -> Self {
+        Self {
+            job_status: "{jobs_url}/{id}".to_string(),
+            abort: "{jobs_url}/jobs/{id}/abort".to_string(),
+            quantum_architecture: "{base_url}/quantum-architecture".to_string(),
+            calibration_metrics: "{base_url}/calibration/metrics/latest".to_string(),
+        }
+    }
+}
+
+/// Serialization format of the run requests sent to the server.
+#[derive(Eq, PartialEq, Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub enum RequestFormat {
+    /// JSON, accepted by all IQM servers
+    #[default]
+    Json,
+    /// MessagePack, more compact than JSON for large batches
+    MsgPack,
+}
+
+impl RequestFormat {
+    /// Returns the value of the `Content-Type` header of requests in this format.
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            RequestFormat::Json => "application/json",
+            RequestFormat::MsgPack => "application/msgpack",
+        }
+    }
+
+    /// Serialize a run request in this format.
+    fn encode<T: Serialize>(&self, request: &T) -> Result<Vec<u8>, RoqoqoBackendError> {
+        match self {
+            RequestFormat::Json => serde_json::to_vec(request).map_err(|err| err.to_string()),
+            RequestFormat::MsgPack => {
+                // Human-readable mode keeps the JSON representation of the arguments, e.g. plain
+                // numbers for CalculatorFloat instead of tagged enums
+                let mut buffer = vec![];
+                let mut serializer = rmp_serde::Serializer::new(&mut buffer)
+                    .with_struct_map()
+                    .with_human_readable();
+                request
+                    .serialize(&mut serializer)
+                    .map(|_| buffer)
+                    .map_err(|err| err.to_string())
+            }
+        }
+        .map_err(|msg| RoqoqoBackendError::GenericError {
+            msg: format!("Could not serialize the run request: {}", msg),
+        })
+    }
+}
+
+/// Strictness of the client-side validation of the circuits, see [Backend::set_validation_level].
+#[derive(Eq, PartialEq, Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub enum ValidationLevel {
+    /// No client-side validation, invalid circuits are rejected by the server
+    Off,
+    /// Only check that the operations of the circuits are supported by the device connectivity
+    Connectivity,
+    /// Check the operations, the measurements and the registers of the circuits
+    #[default]
+    Full,
+}
+
+/// Treatment of jobs reported as ready without measurement results, see
+/// [Backend::set_empty_result_policy].
+#[derive(Eq, PartialEq, Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub enum EmptyResultPolicy {
+    /// Return the results, converting them into registers fails with
+    /// [IqmBackendError::EmptyResult]
+    #[default]
+    Error,
+    /// Re-fetch the results a few times before returning an [IqmBackendError::EmptyResult]
+    RetryThenError,
+    /// Return results without measurements, which are converted into empty registers
+    ReturnEmpty,
+}
+
+/// Report on a circuit produced by [Backend::analyze_circuit].
+#[derive(PartialEq, Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CircuitAnalysis {
+    /// Problems that prevent the circuit from running on the device
+    pub errors: Vec<String>,
+    /// Suspicious constructs that do not prevent the circuit from running
+    pub warnings: Vec<String>,
+    /// Number of qubits acted on or measured by the circuit
+    pub qubit_count: usize,
+    /// Number of gates of the circuit, after the decompositions enabled on the backend
+    pub gate_count: usize,
+    /// Duration of the circuit in units of the gate times of the device, assuming that gates
+    /// acting on disjoint qubits and resonators run in parallel
+    pub estimated_duration: f64,
+}
+
+/// Time spent in the stages of a run, as returned by [Backend::run_circuit_timed].
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct TimingBreakdown {
+    /// Time spent validating, converting and submitting the circuit
+    pub submit: Duration,
+    /// Time spent polling the server until the results were ready
+    pub wait: Duration,
+    /// Time spent converting the results into registers
+    pub processing: Duration,
+}
+
+/// In-memory cache of the results of jobs in a terminal state, keyed by job ID.
+///
+/// The cache is not part of the configuration of the backend: it is neither serialized nor
+/// compared, and clones of a backend share it.
+#[derive(Clone, Default)]
+struct ResultCache(Arc<Mutex<HashMap<String, IqmRunResult>>>);
+
+impl ResultCache {
+    fn get(&self, id: &str) -> Option<IqmRunResult> {
+        self.0.lock().ok()?.get(id).cloned()
+    }
+
+    fn insert(&self, id: String, result: IqmRunResult) {
+        if let Ok(mut cache) = self.0.lock() {
+            cache.insert(id, result);
+        }
+    }
+
+    fn clear(&self) {
+        if let Ok(mut cache) = self.0.lock() {
+            cache.clear();
+        }
+    }
+}
+
+impl PartialEq for ResultCache {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+impl fmt::Debug for ResultCache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let number_results = self.0.lock().map(|cache| cache.len()).unwrap_or_default();
+        write!(f, "ResultCache({} results)", number_results)
+    }
+}
+
+/// Warnings of the results of the last job waited for, see [Backend::get_last_warnings], or of the
+/// last submission, see [Backend::get_submission_warnings].
+///
+/// Like the [ResultCache], the warnings are neither serialized nor compared, and clones of a
+/// backend share them.
+#[derive(Clone, Default)]
+struct LastWarnings(Arc<Mutex<Option<Vec<String>>>>);
+
+impl LastWarnings {
+    fn get(&self) -> Option<Vec<String>> {
+        self.0.lock().ok()?.clone()
+    }
+
+    fn set(&self, warnings: Option<Vec<String>>) {
+        if let Ok(mut last_warnings) = self.0.lock() {
+            *last_warnings = warnings;
+        }
+    }
+
+    fn push(&self, warning: String) {
+        if let Ok(mut last_warnings) = self.0.lock() {
+            last_warnings.get_or_insert_with(Vec::new).push(warning);
+        }
+    }
+}
+
+impl PartialEq for LastWarnings {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+impl fmt::Debug for LastWarnings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "LastWarnings({:?})", self.get())
+    }
+}
+
+/// Tokens obtained by refreshing the access token, see [Backend::set_refresh_credentials].
+///
+/// Like the [ResultCache], the tokens are neither serialized nor compared, and clones of a
+/// backend share them.
+#[derive(Clone, Default)]
+struct RefreshedTokens(Arc<Mutex<Option<RefreshedToken>>>);
+
+impl RefreshedTokens {
+    fn get(&self) -> Option<RefreshedToken> {
+        self.0.lock().ok()?.clone()
+    }
+
+    fn set(&self, token: RefreshedToken) {
+        if let Ok(mut refreshed_token) = self.0.lock() {
+            *refreshed_token = Some(token);
+        }
+    }
+}
+
+impl PartialEq for RefreshedTokens {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+impl fmt::Debug for RefreshedTokens {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let refreshed = self.get().is_some();
+        write!(f, "RefreshedTokens(refreshed: {})", refreshed)
+    }
+}
+
 /// IQM backend
 /// Provides functions to run circuits and measurements on IQM devices.
-#[derive(Eq, PartialEq, Debug, Clone, Serialize, Deserialize)]
+#[derive(PartialEq, Debug, Clone, Serialize, Deserialize)]
 pub struct Backend {
     /// IQM device used by the backend
     pub device: IqmDevice,
     /// OAuth access token for authentication
     access_token: String,
+    /// Source the access token was retrieved from
+    #[serde(default)]
+    token_source: TokenSource,
     /// Number of measurements
     pub number_measurements_internal: Option<usize>,
+    /// Allow plain HTTP connections to the endpoints of the device
+    #[serde(default)]
+    allow_http: bool,
+    /// Maximum number of idle connections per host kept in the connection pool
+    #[serde(default)]
+    pool_max_idle_per_host: Option<usize>,
+    /// Interval of the TCP keep-alive probes
+    #[serde(default)]
+    tcp_keepalive: Option<Duration>,
+    /// Cost model used to estimate the cost of jobs
+    #[serde(default)]
+    cost_model: CostModel,
+    /// Decompose multi-qubit gates into gates supported by the device
+    #[serde(default)]
+    decompose_multiqubit: bool,
+    /// Implement ControlledPauliZ gates via the resonator on Deneb devices
+    #[serde(default)]
+    auto_resonator_cz: bool,
+    /// Retry policy for job submissions
+    #[serde(default)]
+    retry_policy: RetryPolicy,
+    /// Idempotency key sent with job submissions, generated for each submission if None
+    #[serde(default)]
+    idempotency_key: Option<String>,
+    /// Prefix prepended to the names of submitted circuits
+    #[serde(default)]
+    circuit_name_prefix: Option<String>,
+    /// HTTP status codes of the server responses that are treated as success
+    #[serde(default = "default_success_status_codes")]
+    success_status_codes: Vec<u16>,
+    /// Submissions with fewer shots print a warning, 0 disables the warning
+    #[serde(default)]
+    min_shots_warning: usize,
+    /// Serialization format of the run requests
+    #[serde(default)]
+    request_format: RequestFormat,
+    /// Strictness of the client-side validation of the circuits
+    #[serde(default)]
+    validation_level: ValidationLevel,
+    /// Measure only as many qubits as the register holds with PragmaRepeatedMeasurement
+    #[serde(default)]
+    repeated_measurement_register_qubits: bool,
+    /// Seed sent to the server for the sampling of the measurement results
+    #[serde(default)]
+    random_seed: Option<u64>,
+    /// Return an error instead of the results of jobs with warnings
+    #[serde(default)]
+    warnings_as_errors: bool,
+    /// Reject circuits exceeding the maximum circuit depth of the device in the validation
+    #[serde(default)]
+    check_circuit_depth: bool,
+    /// Timeout in seconds for polling the results of jobs
+    #[serde(default = "default_timeout_secs")]
+    timeout_secs: f64,
+    /// Interval in seconds between two queries of the results of jobs
+    #[serde(default = "default_polling_interval_secs")]
+    polling_interval_secs: f64,
+    /// Templates of the URLs of the job status, abort and quantum architecture endpoints
+    #[serde(default)]
+    endpoint_paths: EndpointPaths,
+    /// Heralding mode sent with the run requests
+    #[serde(default)]
+    heralding_mode: HeraldingMode,
+    /// ID of the calibration set the jobs are executed with, the latest one if None
+    #[serde(default)]
+    calibration_set_id: Option<String>,
+    /// Round the number of shots to the granularity accepted by the device before submission
+    #[serde(default)]
+    round_shots: bool,
+    /// Mapping of the logical qubits onto the physical qubits sent with the run requests
+    #[serde(default)]
+    qubit_mapping: Option<Vec<SingleQubitMapping>>,
+    /// Let the server reject circuits exceeding the maximum circuit duration
+    #[serde(default)]
+    circuit_duration_check: bool,
+    /// Print the warnings of the IQM device to stderr when querying the results of jobs
+    #[serde(default = "default_print_warnings")]
+    print_warnings: bool,
+    /// Priority tier requested for the submitted jobs, the default tier of the account if None
+    #[serde(default)]
+    job_priority: Option<String>,
+    /// Credentials used to refresh the access token when it is rejected by the server
+    #[serde(default)]
+    refresh_credentials: Option<RefreshCredentials>,
+    /// Treatment of jobs reported as ready without measurement results
+    #[serde(default)]
+    empty_result_policy: EmptyResultPolicy,
+    /// Results of the jobs in a terminal state that have already been fetched
+    #[serde(skip)]
+    result_cache: ResultCache,
+    /// Warnings of the results of the last job waited for
+    #[serde(skip)]
+    last_warnings: LastWarnings,
+    /// Warnings raised by the backend when submitting the last job
+    #[serde(skip)]
+    submission_warnings: LastWarnings,
+    /// Tokens obtained by refreshing the access token
+    #[serde(skip)]
+    refreshed_tokens: RefreshedTokens,
 }
 
 impl Backend {
@@ -183,371 +780,2220 @@ impl Backend {
     ///
     /// * `device` - The IQM device the Backend uses to execute operations and circuits.
     /// * `access_token` - An access_token is required to access IQM hardware and simulators. The
-    ///                    access_token can either be passed as an argument, or if the argument is set to None will be
-    ///                    read from the environmental variable `IQM_TOKEN`.
+    ///   access_token can either be passed as an argument, or if the argument is set to None will be
+    ///   read from the environmental variable `IQM_TOKEN`. If `IQM_TOKEN` is not set either, the
+    ///   access token is read from the JSON tokens file the environment variable `IQM_TOKENS_FILE`
+    ///   points to. In that case the refresh token of the file is used to refresh the access
+    ///   token when it expires, see [Backend::set_refresh_credentials].
+    ///
+    /// If the environment variable `IQM_ENDPOINT_URL` is set, it replaces the default endpoint URL
+    /// of the device. An endpoint URL set explicitly with `set_endpoint_url` on the device takes
+    /// precedence over the environment variable.
     ///
     /// # Returns
     ///
     /// * `Ok(Backend)` - The newly created IQM backend
-    /// * `Err(RoqoqoBackendError)` - If the access token cannot be retrieved from the `IQM_TOKEN` environment variable.
+    /// * `Err(RoqoqoBackendError)` - If the access token cannot be retrieved from the `IQM_TOKEN` environment variable
+    ///   or the `IQM_TOKENS_FILE`.
     pub fn new(
         device: IqmDevice,
         access_token: Option<String>,
     ) -> Result<Self, RoqoqoBackendError> {
-        let access_token_internal: String = match access_token {
-            Some(s) => s,
-            None => _get_token_from_env_var().map_err(|_| {
-                RoqoqoBackendError::MissingAuthentication {
-                    msg: "IQM access token has not been passed as an argument and could \
-                         not be retrieved from the IQM_TOKEN environment variable."
-                        .to_string(),
+        let mut refresh_credentials = None;
+        let (access_token_internal, token_source) = match access_token {
+            Some(s) => (s, TokenSource::Argument),
+            None => match _get_token_from_env_var() {
+                Ok(token) => (token, TokenSource::EnvironmentVariable),
+                Err(_) => {
+                    let token = _get_token_from_file().map_err(|err| {
+                        RoqoqoBackendError::MissingAuthentication {
+                            msg: format!(
+                                "IQM access token has not been passed as an argument and could \
+                                 not be retrieved from the IQM_TOKEN environment variable or the \
+                                 IQM_TOKENS_FILE: {}",
+                                err
+                            ),
+                        }
+                    })?;
+                    refresh_credentials = Some(RefreshCredentials {
+                        refresh_token: token.refresh_token,
+                        auth_server_url: token.auth_server_url,
+                    });
+                    (token.access_token, TokenSource::TokensFile)
                 }
-            })?,
+            },
         };
 
+        let mut device = device;
+        if let Ok(endpoint_url) = var("IQM_ENDPOINT_URL") {
+            device.override_default_endpoint_url(endpoint_url)
+        }
+
         Ok(Self {
             device,
             access_token: access_token_internal,
+            token_source,
             number_measurements_internal: None,
+            allow_http: false,
+            pool_max_idle_per_host: None,
+            tcp_keepalive: None,
+            cost_model: CostModel::default(),
+            decompose_multiqubit: false,
+            auto_resonator_cz: false,
+            retry_policy: RetryPolicy::default(),
+            idempotency_key: None,
+            circuit_name_prefix: None,
+            success_status_codes: default_success_status_codes(),
+            min_shots_warning: 0,
+            request_format: RequestFormat::Json,
+            validation_level: ValidationLevel::Full,
+            repeated_measurement_register_qubits: false,
+            random_seed: None,
+            warnings_as_errors: false,
+            check_circuit_depth: false,
+            timeout_secs: TIMEOUT_SECS,
+            polling_interval_secs: SECONDS_BETWEEN_CALLS,
+            endpoint_paths: EndpointPaths::default(),
+            heralding_mode: HeraldingMode::None,
+            calibration_set_id: None,
+            round_shots: false,
+            qubit_mapping: None,
+            circuit_duration_check: false,
+            print_warnings: true,
+            job_priority: None,
+            refresh_credentials,
+            empty_result_policy: EmptyResultPolicy::Error,
+            result_cache: ResultCache::default(),
+            last_warnings: LastWarnings::default(),
+            submission_warnings: LastWarnings::default(),
+            refreshed_tokens: RefreshedTokens::default(),
         })
     }
 
-    /// Overwrite the number of measurements that will be executed on the [roqoqo::Circuit] or the
-    /// [roqoqo::QuantumProgram]. The default number of measurements is the one defined in the submitted
-    /// circuits.
+    /// Creates a new IQM backend with the access token of a named profile.
     ///
-    /// WARNING: this function will overwrite the number of measurements set in a Circuit or
-    /// QuantumProgram. Changing the number of measurments WILL change the accuracy of the result.
-    pub fn _overwrite_number_of_measurements(&mut self, number_measurements: usize) {
-        self.number_measurements_internal = Some(number_measurements)
+    /// The profile is read from the credentials file `~/.config/qoqo_iqm/credentials`, see
+    /// [Backend::new_with_profile_file] for its format. Useful to switch between several IQM
+    /// accounts or environments without changing environment variables.
+    ///
+    /// # Arguments
+    ///
+    /// * `device` - The IQM device the Backend uses to execute operations and circuits.
+    /// * `profile_name` - The name of the profile in the credentials file.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Backend)` - The newly created IQM backend
+    /// * `Err(RoqoqoBackendError)` - If the credentials file cannot be read or does not contain a
+    ///   token for the profile.
+    pub fn new_with_profile(
+        device: IqmDevice,
+        profile_name: &str,
+    ) -> Result<Self, RoqoqoBackendError> {
+        let home = var("HOME").map_err(|_| RoqoqoBackendError::MissingAuthentication {
+            msg: "Could not locate the credentials file: the HOME environment variable is not set."
+                .to_string(),
+        })?;
+        Self::new_with_profile_file(
+            device,
+            profile_name,
+            Path::new(&home).join(CREDENTIALS_FILE_PATH),
+        )
     }
 
-    /// Check that the device's connectivity is respected.
+    /// Creates a new IQM backend with the access token of a named profile of a credentials file.
+    ///
+    /// The credentials file contains a section per profile with the access token and, optionally,
+    /// the endpoint URL of the profile:
+    ///
+    /// ```text
+    /// [default]
+    /// token = "my_token"
+    ///
+    /// [staging]
+    /// token = "my_staging_token"
+    /// endpoint = "https://staging.example.com/garnet/jobs"
+    /// ```
+    ///
+    /// The endpoint of the profile replaces the default endpoint URL of the device and takes
+    /// precedence over the `IQM_ENDPOINT_URL` environment variable, but not over an endpoint URL
+    /// set explicitly with `set_endpoint_url` on the device.
     ///
     /// # Arguments
     ///
-    /// * `circuit` - The [roqoqo::Circuit] to be checked
+    /// * `device` - The IQM device the Backend uses to execute operations and circuits.
+    /// * `profile_name` - The name of the profile in the credentials file.
+    /// * `path` - The path of the credentials file.
     ///
     /// # Returns
     ///
-    /// *`Err(RoqoqoBackendError)` - The circuit is invalid.
-    pub fn validate_circuit_connectivity(
-        &self,
-        circuit: &Circuit,
-    ) -> Result<(), RoqoqoBackendError> {
-        let allowed = [
-            "PragmaSetNumberOfMeasurements",
-            "PragmaRepeatedMeasurement",
-            "MeasureQubit",
-            "DefinitionBit",
-            "InputBit",
-            "PragmaGlobalPhase",
-        ];
-
-        for op in circuit.iter() {
-            if let Ok(inner_op) = SingleQubitOperation::try_from(op) {
-                if !allowed.contains(&inner_op.hqslang())
-                    && self
-                        .device
-                        .single_qubit_gate_time(inner_op.hqslang(), inner_op.qubit())
-                        .is_none()
-                {
-                    return Err(RoqoqoBackendError::OperationNotInBackend {
-                        backend: "IQM",
-                        hqslang: inner_op.hqslang(),
-                    });
+    /// * `Ok(Backend)` - The newly created IQM backend
+    /// * `Err(RoqoqoBackendError)` - If the credentials file cannot be read or does not contain a
+    ///   token for the profile.
+    pub fn new_with_profile_file<P: AsRef<Path>>(
+        device: IqmDevice,
+        profile_name: &str,
+        path: P,
+    ) -> Result<Self, RoqoqoBackendError> {
+        let path = path.as_ref();
+        let content =
+            fs::read_to_string(path).map_err(|err| RoqoqoBackendError::MissingAuthentication {
+                msg: format!(
+                    "Could not read the credentials file {}: {}",
+                    path.display(),
+                    err
+                ),
+            })?;
+        let (token, endpoint) =
+            _parse_credentials_profile(&content, profile_name).ok_or_else(|| {
+                RoqoqoBackendError::MissingAuthentication {
+                    msg: format!(
+                        "No access token found for profile {} in the credentials file {}.",
+                        profile_name,
+                        path.display()
+                    ),
                 }
-            } else if let Ok(inner_op) = TwoQubitOperation::try_from(op) {
-                if self
-                    .device
-                    .two_qubit_gate_time(inner_op.hqslang(), inner_op.control(), inner_op.target())
-                    .is_none()
-                {
-                    return Err(RoqoqoBackendError::OperationNotInBackend {
-                        backend: "IQM",
-                        hqslang: inner_op.hqslang(),
-                    });
-                }
-            } else if let Ok(inner_op) = MultiQubitOperation::try_from(op) {
-                if self
-                    .device
-                    .multi_qubit_gate_time(inner_op.hqslang(), inner_op.qubits())
-                    .is_none()
-                {
-                    return Err(RoqoqoBackendError::OperationNotInBackend {
-                        backend: "IQM",
-                        hqslang: inner_op.hqslang(),
-                    });
-                }
-            } else if !allowed.contains(&op.hqslang()) {
-                return Err(RoqoqoBackendError::OperationNotInBackend {
-                    backend: "IQM",
-                    hqslang: op.hqslang(),
-                });
-            }
+            })?;
+
+        let mut device = device;
+        if let Some(endpoint_url) = endpoint {
+            device.override_default_endpoint_url(endpoint_url)
         }
-        Ok(())
+        let mut backend = Self::new(device, Some(token))?;
+        backend.token_source = TokenSource::CredentialsProfile;
+        Ok(backend)
     }
 
-    /// Check if the circuit is well-defined according to the device specifications.
+    /// Returns the source the access token of the backend was retrieved from.
     ///
-    /// # Arguments
-    ///
-    /// * `circuit` - The [roqoqo::Circuit] to be checked
-    pub fn validate_circuit(&self, circuit: &Circuit) -> Result<(), IqmBackendError> {
-        // Check that the circuit doesn't contain more qubits than the device supports
-        let mut measured_qubits: Vec<usize> = vec![];
-        let number_qubits = _get_number_qubits(circuit).ok_or(IqmBackendError::EmptyCircuit)?;
-
-        // NOTE checking also the name is a workaround for a pyo3 deserialization bug that causes
-        // the if let to match even when the device is not Deneb. This issue should have been fixed
-        // by removing the bincode deserialization attempt in the device pyo3 files, but I leave the
-        // name check here for extra insurance.
-        if self.device.name() == "Deneb" {
-            if let IqmDevice::DenebDevice(device) = &self.device {
-                device.validate_circuit(circuit)?
-            }
-        } else {
-            self.validate_circuit_connectivity(circuit).map_err(|err| {
-                IqmBackendError::InvalidCircuit {
-                    msg: err.to_string(),
-                }
-            })?
-        }
-
-        // Check that
-        // 1) Every qubit is measured exactly once
-        // 2) Output registers are large enough
-        let mut measured = false;
-        for op in circuit.iter() {
-            match op {
-                Operation::MeasureQubit(o) => {
-                    measured = true;
-                    let qubit = *o.qubit();
-                    if measured_qubits.contains(&qubit) {
-                        return Err(IqmBackendError::InvalidCircuit {
-                            msg: format!("Qubit {} is being measured multiple times.", qubit),
-                        });
-                    } else {
-                        measured_qubits.push(qubit)
-                    }
-                }
-                Operation::PragmaRepeatedMeasurement(o) => {
-                    measured = true;
-                    if !measured_qubits.is_empty() {
-                        return Err(IqmBackendError::InvalidCircuit {
-                            msg: "Qubits are being measured more than once. When using \
-                                PragmaRepeatedMeasurement, there should not be individual qubit \
-                                measurements, and the PragmaRepeatedMeasurement operation can \
-                                appear only once in the circuit."
-                                .to_string(),
-                        });
-                    } else {
-                        measured_qubits.extend(0..self.device.number_qubits())
-                    }
-
-                    let mut readout_length: usize = 0;
-                    for def in circuit.definitions() {
-                        if let Operation::DefinitionBit(reg) = def {
-                            readout_length = *reg.length()
-                        }
-                    }
-
-                    if number_qubits > readout_length {
-                        return Err(IqmBackendError::RegisterTooSmall {
-                            name: o.readout().to_string(),
-                        });
-                    }
-                }
-                _ => (),
-            }
-        }
-        if !measured {
-            Err(IqmBackendError::InvalidCircuit {
-                msg: "All circuits submitted need to have at least one measurement instruction."
-                    .to_string(),
-            })
-        } else {
-            Ok(())
-        }
+    /// Helps to find out which account is used when the token can come from several places,
+    /// without revealing the token itself.
+    pub fn token_source(&self) -> TokenSource {
+        self.token_source
     }
 
-    /// Query results of a submitted job.
+    /// Set the credentials used to refresh the access token when it expires.
+    ///
+    /// When the server rejects the access token with HTTP status 401 while submitting jobs or
+    /// querying their results, the refresh token is exchanged for a new access token at the
+    /// OpenID Connect token endpoint of the auth server and the request is sent once more. This
+    /// keeps long-running batch jobs alive past the lifetime of the access token. Backends created
+    /// from an `IQM_TOKENS_FILE` use the refresh token of the file by default.
     ///
     /// # Arguments
     ///
-    /// * `id` - The job ID for the query.
+    /// * `refresh_token` - The refresh token.
+    /// * `auth_server_url` - The base URL of the auth server, e.g. `https://auth.example.com`.
+    pub fn set_refresh_credentials(&mut self, refresh_token: String, auth_server_url: String) {
+        self.refresh_credentials = Some(RefreshCredentials {
+            refresh_token,
+            auth_server_url,
+        })
+    }
+
+    /// Returns the access token sent to the server, the refreshed one if it has been refreshed.
+    fn current_access_token(&self) -> String {
+        self.refreshed_tokens
+            .get()
+            .map(|token| token.access_token)
+            .unwrap_or_else(|| self.access_token.clone())
+    }
+
+    /// Exchange the refresh token for a new access token at the auth server.
     ///
     /// # Returns
     ///
-    /// * `Ok(IqmRunResult)` - Result of the job (status can be pending).
-    /// * `Err(RoqoqoBackendError)` - If something goes wrong with the HTTP request, or response is not formatted correctly.
-    pub fn get_results(&self, id: String) -> Result<IqmRunResult, RoqoqoBackendError> {
-        let client = reqwest::blocking::Client::builder()
-            .https_only(true)
-            .build()
-            .map_err(|x| RoqoqoBackendError::NetworkError {
-                msg: format!("Could not create https client {:?}", x),
+    /// * `Ok(())` - The access token has been refreshed.
+    /// * `Err(RoqoqoBackendError::MissingAuthentication)` - The access token could not be refreshed.
+    fn refresh_access_token(&self) -> Result<(), RoqoqoBackendError> {
+        let (token_url, form) = self.refresh_request()?;
+        let client = self.build_client()?;
+        let response = client.post(token_url).form(&form).send().map_err(|err| {
+            RoqoqoBackendError::MissingAuthentication {
+                msg: format!("Could not refresh the IQM access token: {:?}", err),
+            }
+        })?;
+        let status = response.status();
+        let text = response
+            .text()
+            .map_err(|err| RoqoqoBackendError::MissingAuthentication {
+                msg: format!("Could not refresh the IQM access token: {:?}", err),
             })?;
+        self.store_refreshed_token(status, &text)
+    }
 
-        let job_url = self.device.remote_host() + "/" + &id;
-
-        let result = client
-            .get(job_url.clone())
-            .headers(_construct_headers(&self.access_token))
-            .send()
-            .map_err(|e| RoqoqoBackendError::NetworkError {
-                msg: format!("Error during GET request: {:?}", e),
-            })?;
+    /// Returns the URL of the token endpoint of the auth server and the form of the request
+    /// exchanging the refresh token for a new access token.
+    fn refresh_request(&self) -> Result<(String, RefreshForm), RoqoqoBackendError> {
+        let credentials = self.refresh_credentials.as_ref().ok_or_else(|| {
+            RoqoqoBackendError::MissingAuthentication {
+                msg: "IQM access token was rejected and no refresh credentials are set."
+                    .to_string(),
+            }
+        })?;
+        let refresh_token = self
+            .refreshed_tokens
+            .get()
+            .map(|token| token.refresh_token)
+            .unwrap_or_else(|| credentials.refresh_token.clone());
+        let token_url = format!(
+            "{}/realms/{}/protocol/openid-connect/token",
+            credentials.auth_server_url.trim_end_matches('/'),
+            AUTH_REALM
+        );
+        let form = [
+            ("grant_type", "refresh_token".to_string()),
+            ("client_id", AUTH_CLIENT_ID.to_string()),
+            ("refresh_token", refresh_token),
+        ];
+        Ok((token_url, form))
+    }
 
-        let iqm_result =
-            result
-                .json::<IqmRunResult>()
-                .map_err(|err| RoqoqoBackendError::NetworkError {
-                    msg: format!("Error during deserialisation of GET response: {:?}", err),
-                })?;
+    /// Store the tokens of the response of the auth server to a refresh request.
+    fn store_refreshed_token(
+        &self,
+        status: reqwest::StatusCode,
+        text: &str,
+    ) -> Result<(), RoqoqoBackendError> {
+        if !status.is_success() {
+            return Err(RoqoqoBackendError::MissingAuthentication {
+                msg: format!(
+                    "Could not refresh the IQM access token, the auth server responded with \
+                     status code: {}",
+                    status
+                ),
+            });
+        }
+        let token = serde_json::from_str::<RefreshedToken>(text).map_err(|err| {
+            RoqoqoBackendError::MissingAuthentication {
+                msg: format!(
+                    "Could not refresh the IQM access token, invalid response of the auth \
+                     server: {:?}",
+                    err
+                ),
+            }
+        })?;
+        self.refreshed_tokens.set(token);
+        Ok(())
+    }
 
-        if iqm_result.warnings.is_some() {
-            eprintln!("Warnings: {:?}", iqm_result.clone().warnings.unwrap());
+    /// Send a request with the current access token, refreshing the token and sending the request
+    /// once more if the server rejects it with HTTP status 401 and refresh credentials are set.
+    fn send_authenticated(
+        &self,
+        send: impl Fn(&str) -> Result<Response, RoqoqoBackendError>,
+    ) -> Result<Response, RoqoqoBackendError> {
+        let response = send(&self.current_access_token())?;
+        if response.status() == reqwest::StatusCode::UNAUTHORIZED
+            && self.refresh_credentials.is_some()
+        {
+            self.refresh_access_token()?;
+            return send(&self.current_access_token());
         }
-        Ok(iqm_result)
+        Ok(response)
     }
 
-    /// Poll results until job is either ready, failed, aborted or timed out.
+    /// Returns the endpoint URL the jobs are submitted to.
+    pub fn remote_host(&self) -> String {
+        self.device.remote_host()
+    }
+
+    /// Overwrite the number of measurements that will be executed on the [roqoqo::Circuit] or the
+    /// [roqoqo::QuantumProgram]. The default number of measurements is the one defined in the submitted
+    /// circuits.
+    ///
+    /// WARNING: this function will overwrite the number of measurements set in a Circuit or
+    /// QuantumProgram. Changing the number of measurments WILL change the accuracy of the result.
+    pub fn _overwrite_number_of_measurements(&mut self, number_measurements: usize) {
+        self.number_measurements_internal = Some(number_measurements)
+    }
+
+    /// Returns the number of measurements that will be used when submitting a circuit.
+    ///
+    /// A number of measurements set with [Backend::_overwrite_number_of_measurements] takes
+    /// precedence over the one defined by a [roqoqo::operations::PragmaSetNumberOfMeasurements] or
+    /// [roqoqo::operations::PragmaRepeatedMeasurement] in the circuit. Without either, the
+    /// [IqmDevice::default_shots] of the device are used.
     ///
     /// # Arguments
     ///
-    /// * `id` - The job ID for the query"Job failed with job ID: {}"
+    /// * `circuit` - The circuit to be submitted.
     ///
     /// # Returns
     ///
-    /// * `Ok(BatchResult)` - Result of the job if ready.
-    /// * `Err(IqmBackendError)` - If job failed, timed out or aborted, or IQM returned empty results.
-    pub fn wait_for_results(&self, id: String) -> Result<IqmRunResult, IqmBackendError> {
-        let start_time = Instant::now();
-
-        while start_time.elapsed().as_secs_f64() < TIMEOUT_SECS {
-            let iqm_result = self.get_results(id.clone())?;
+    /// * `usize` - The number of measurements of the circuit.
+    pub fn effective_shots(&self, circuit: &Circuit) -> usize {
+        self.number_measurements_internal
+            .or_else(|| _circuit_number_measurements(circuit))
+            .unwrap_or_else(|| self.device.default_shots())
+    }
 
-            match iqm_result.status {
-                Status::Ready => return Ok(iqm_result),
-                Status::Failed => {
-                    let msg = iqm_result.message.expect(
-                        "Job has failed but response message is
-                         empty. Something went wrong on the server side.",
-                    );
-                    return Err(IqmBackendError::JobFailed { id, msg });
-                }
-                Status::Aborted => return Err(IqmBackendError::JobAborted { id }),
-                _ => {
-                    let duration = Duration::from_secs_f64(SECONDS_BETWEEN_CALLS);
-                    thread::sleep(duration);
-                }
-            }
-        }
-        Err(IqmBackendError::RoqoqoBackendError(
-            RoqoqoBackendError::Timeout {
-                msg: format!("Job did not finish in {} seconds", TIMEOUT_SECS),
-            },
-        ))
+    /// Returns the number of measurements to overwrite the ones of a circuit with: the given
+    /// `number_measurements_internal` if set, or the default shots of the device if the circuit
+    /// does not define a number of measurements.
+    fn overwritten_number_measurements(
+        &self,
+        circuit: &Circuit,
+        number_measurements_internal: Option<usize>,
+    ) -> Option<usize> {
+        number_measurements_internal.or_else(|| match _circuit_number_measurements(circuit) {
+            Some(_) => None,
+            None => Some(self.device.default_shots()),
+        })
     }
 
-    /// Abort a submitted job.
+    /// Allow connections to the endpoints of the device over plain HTTP.
+    ///
+    /// By default only HTTPS connections are allowed. Plain HTTP should only be enabled for local
+    /// test servers.
     ///
     /// # Arguments
     ///
-    /// * `id` - The ID of the job to abort.
+    /// * `allow_http` - Whether plain HTTP connections are allowed.
+    pub fn set_allow_http(&mut self, allow_http: bool) {
+        self.allow_http = allow_http
+    }
+
+    /// Set the maximum number of idle connections per host kept in the connection pool of the
+    /// HTTP client.
     ///
-    /// # Returns
+    /// # Arguments
     ///
-    /// * `Err(RoqoqoBackendError)` - If the job abortion failed.
-    pub fn abort_job(&self, id: String) -> Result<(), IqmBackendError> {
-        let client = reqwest::blocking::Client::builder()
-            .https_only(true)
-            .build()
-            .map_err(|err| {
-                IqmBackendError::RoqoqoBackendError(RoqoqoBackendError::NetworkError {
-                    msg: format!("Could not create HTTPS client {:?}", err),
-                })
-            })?;
+    /// * `max_idle` - The maximum number of idle connections per host.
+    pub fn set_pool_max_idle_per_host(&mut self, max_idle: usize) {
+        self.pool_max_idle_per_host = Some(max_idle)
+    }
 
-        let abort_url = [&self.device.remote_host(), "jobs", &id, "abort"].join("/");
+    /// Enable TCP keep-alive probes with the given interval on the connections to the IQM server.
+    ///
+    /// # Arguments
+    ///
+    /// * `interval` - The interval between keep-alive probes.
+    pub fn set_tcp_keepalive(&mut self, interval: Duration) {
+        self.tcp_keepalive = Some(interval)
+    }
 
-        let resp = client
-            .post(abort_url)
-            .headers(_construct_headers(&self.access_token))
-            .send()
-            .map_err(|err| {
-                IqmBackendError::RoqoqoBackendError(RoqoqoBackendError::NetworkError {
-                    msg: format!("Error during POST request of abort_job: {:?}", err),
-                })
-            })?;
+    /// Set the templates of the URLs of the job status, abort, quantum architecture and
+    /// calibration metrics endpoints.
+    ///
+    /// Needed for deployments whose URL layout differs from the one of the IQM servers, see
+    /// [EndpointPaths].
+    ///
+    /// # Arguments
+    ///
+    /// * `endpoint_paths` - The URL templates.
+    pub fn set_endpoint_paths(&mut self, endpoint_paths: EndpointPaths) {
+        self.endpoint_paths = endpoint_paths
+    }
 
-        match resp.status() {
-            reqwest::StatusCode::OK => Ok(()),
-            _ => {
-                let msg = serde_json::from_str::<AbortResponse>(&resp.text().unwrap())
-                    .unwrap()
-                    .detail
-                    .to_string();
-                Err(IqmBackendError::JobAbortionFailed { id, msg })
-            }
-        }
+    /// Returns the templates of the URLs of the job status, abort, quantum architecture and
+    /// calibration metrics endpoints.
+    pub fn endpoint_paths(&self) -> EndpointPaths {
+        self.endpoint_paths.clone()
     }
 
-    /// Get information about the quantum architecture of the given device.
+    /// Set the cost model used by [Backend::estimate_cost].
     ///
-    /// # Returns
+    /// # Arguments
     ///
-    /// * `Ok(String)` - Information about the quantum architecture of the device.
-    /// * `Err(RoqoqoBackendError)` - Error response from IQM server.
-    pub fn get_quantum_architecture(&self) -> Result<String, RoqoqoBackendError> {
-        let endpoint_url = self
-            .device
-            .remote_host()
-            .replace("jobs", "quantum-architecture");
-
-        let client = reqwest::blocking::Client::builder()
-            .https_only(true)
-            .build()
-            .map_err(|x| RoqoqoBackendError::NetworkError {
-                msg: format!("Could not create https client {:?}", x),
-            })?;
+    /// * `cost_model` - The cost model of the IQM account.
+    pub fn set_cost_model(&mut self, cost_model: CostModel) {
+        self.cost_model = cost_model
+    }
 
-        let response = client
-            .get(endpoint_url)
-            .headers(_construct_headers(&self.access_token))
-            .send()
-            .map_err(|e| RoqoqoBackendError::NetworkError {
-                msg: format!("Error during GET request: {:?}", e),
-            })?;
-        if response.status().is_success() {
-            response
-                .text()
-                .map_err(|e| RoqoqoBackendError::NetworkError {
-                    msg: format!("Error during GET request: {:?}", e),
-                })
-        } else {
-            Err(RoqoqoBackendError::NetworkError {
-                msg: format!(
-                    "GET request failed with status code: {:?}",
-                    response.status()
-                ),
-            })
-        }
+    /// Returns the cost model used by [Backend::estimate_cost].
+    pub fn cost_model(&self) -> CostModel {
+        self.cost_model
     }
 
-    /// Validate the batch of circuits to submit by checking that they all write to different output registers.
+    /// Decompose multi-qubit gates into gates supported by the device before validating and
+    /// submitting circuits.
+    ///
+    /// Currently `ControlledControlledPauliZ` gates are decomposed into `RotateXY` and
+    /// `ControlledPauliZ` gates. The decomposition requires one of the three qubits of the gate to
+    /// be coupled to the two others, otherwise the circuit is rejected. Disabled by default.
     ///
     /// # Arguments
     ///
-    /// * `circuit_batch` - The batch of circuits to validate.
+    /// * `decompose_multiqubit` - Whether multi-qubit gates are decomposed.
+    pub fn set_decompose_multiqubit(&mut self, decompose_multiqubit: bool) {
+        self.decompose_multiqubit = decompose_multiqubit
+    }
+
+    /// Implement `ControlledPauliZ` gates via the computational resonator on Deneb devices.
     ///
-    /// # Returns
+    /// Deneb has no native `ControlledPauliZ` gate. When enabled, each `ControlledPauliZ` gate is
+    /// replaced by a `SingleExcitationStore` of the target qubit into a resonator coupled to both
+    /// qubits, a `CZQubitResonator` on the control qubit and a `SingleExcitationLoad` of the
+    /// target qubit, so that device-agnostic circuits can run on Deneb. Has no effect on other
+    /// devices. Disabled by default.
     ///
-    /// * `Err(IqmBackendError)` - The batch is invalid.
-    pub fn validate_circuit_batch(&self, circuit_batch: &[Circuit]) -> Result<(), IqmBackendError> {
-        let mut output_registers = HashSet::new();
+    /// # Arguments
+    ///
+    /// * `auto_resonator_cz` - Whether `ControlledPauliZ` gates are implemented via the resonator.
+    pub fn set_auto_resonator_cz(&mut self, auto_resonator_cz: bool) {
+        self.auto_resonator_cz = auto_resonator_cz
+    }
+
+    /// Set the timeout for polling the results of jobs.
+    ///
+    /// Jobs waiting in a long queue can take much longer than the default of 60 seconds. The
+    /// timeout also bounds the retries of job submissions.
+    ///
+    /// # Arguments
+    ///
+    /// * `seconds` - The timeout in seconds. Defaults to 60 seconds.
+    ///
+    /// # Returns
+    ///
+    /// * `Err(RoqoqoBackendError::GenericError)` - The timeout is negative or not finite.
+    pub fn set_timeout(&mut self, seconds: f64) -> Result<(), RoqoqoBackendError> {
+        if !seconds.is_finite() || seconds < 0.0 {
+            return Err(RoqoqoBackendError::GenericError {
+                msg: format!(
+                    "The timeout must be finite and non-negative, got {} seconds.",
+                    seconds
+                ),
+            });
+        }
+        self.timeout_secs = seconds;
+        Ok(())
+    }
+
+    /// Set the interval between two queries of the results of a job.
+    ///
+    /// Short intervals suit quick simulator jobs, long intervals reduce the load on the server.
+    ///
+    /// # Arguments
+    ///
+    /// * `seconds` - The interval in seconds. Defaults to 4 seconds.
+    ///
+    /// # Returns
+    ///
+    /// * `Err(RoqoqoBackendError::GenericError)` - The interval is not strictly positive and finite.
+    pub fn set_polling_interval(&mut self, seconds: f64) -> Result<(), RoqoqoBackendError> {
+        if !seconds.is_finite() || seconds <= 0.0 {
+            return Err(RoqoqoBackendError::GenericError {
+                msg: format!(
+                    "The polling interval must be strictly positive, got {} seconds.",
+                    seconds
+                ),
+            });
+        }
+        self.polling_interval_secs = seconds;
+        Ok(())
+    }
+
+    /// Set the policy for retrying job submissions after network errors or server errors.
+    ///
+    /// By default submissions are not retried.
+    ///
+    /// # Arguments
+    ///
+    /// * `retry_policy` - The retry policy.
+    pub fn set_retry_policy(&mut self, retry_policy: RetryPolicy) {
+        self.retry_policy = retry_policy
+    }
+
+    /// Returns the policy for retrying job submissions.
+    pub fn retry_policy(&self) -> RetryPolicy {
+        self.retry_policy
+    }
+
+    /// Set the maximum delay between two attempts of a job submission.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_backoff` - The maximum delay.
+    pub fn set_max_backoff(&mut self, max_backoff: Duration) {
+        self.retry_policy.max_backoff = max_backoff
+    }
+
+    /// Set the idempotency key sent in the `Idempotency-Key` header of job submissions.
+    ///
+    /// The server discards submissions with an already used key, which prevents submitting the same
+    /// batch twice when a submission is retried after its response was lost. When no key is set, a
+    /// new key is generated for each submission and shared by all of its retries.
+    ///
+    /// # Arguments
+    ///
+    /// * `idempotency_key` - The key to send, or None to generate one for each submission.
+    pub fn set_idempotency_key(&mut self, idempotency_key: Option<String>) {
+        self.idempotency_key = idempotency_key
+    }
+
+    /// Set the heralding mode of the submitted jobs.
+    ///
+    /// With [HeraldingMode::Zeros] the server performs a heralding measurement before each shot
+    /// and only retains the shots with an all-zeros result. The results then contain fewer shots
+    /// than requested, and the output registers contain one entry per returned shot. Defaults to
+    /// [HeraldingMode::None].
+    ///
+    /// # Arguments
+    ///
+    /// * `heralding_mode` - The heralding mode.
+    pub fn set_heralding_mode(&mut self, heralding_mode: HeraldingMode) {
+        self.heralding_mode = heralding_mode
+    }
+
+    /// Set the mapping of the logical qubits of the circuits onto the physical qubits of the
+    /// device.
+    ///
+    /// Without a mapping the placement of the qubits is left to the server. With a mapping, every
+    /// qubit acted on by a submitted circuit must be contained in the mapping, otherwise the
+    /// submission returns an [IqmBackendError::InvalidCircuit] error. Use
+    /// [results_to_registers_logical] to key the results by the logical qubits. An empty mapping
+    /// removes the mapping.
+    ///
+    /// # Arguments
+    ///
+    /// * `mapping` - The name of the physical qubit, e.g. `QB3`, of each logical qubit.
+    pub fn set_qubit_mapping(&mut self, mapping: HashMap<usize, String>) {
+        if mapping.is_empty() {
+            self.qubit_mapping = None;
+            return;
+        }
+        let mut mapping: Vec<(usize, String)> = mapping.into_iter().collect();
+        mapping.sort();
+        self.qubit_mapping = Some(
+            mapping
+                .into_iter()
+                .map(|(logical_qubit, physical_name)| SingleQubitMapping {
+                    logical_name: _convert_qubit_name_qoqo_to_iqm(logical_qubit),
+                    physical_name,
+                })
+                .collect(),
+        )
+    }
+
+    /// Check that the qubits of the circuits are contained in the qubit mapping, if any.
+    fn check_qubit_mapping(&self, circuits: &[IqmCircuit]) -> Result<(), IqmBackendError> {
+        let mapping = match &self.qubit_mapping {
+            Some(mapping) => mapping,
+            None => return Ok(()),
+        };
+        let logical_names: HashSet<&str> = mapping
+            .iter()
+            .map(|single_mapping| single_mapping.logical_name.as_str())
+            .collect();
+        for circuit in circuits.iter() {
+            // Resonators are not part of the qubit mapping
+            if let Some(qubit) = circuit
+                .instructions
+                .iter()
+                .flat_map(|instruction| instruction.qubits.iter())
+                .find(|qubit| {
+                    _convert_qubit_name_iqm_to_qoqo(qubit).is_some()
+                        && !logical_names.contains(qubit.as_str())
+                })
+            {
+                return Err(IqmBackendError::InvalidCircuit {
+                    msg: format!(
+                        "Qubit {} of circuit {} is missing from the qubit mapping.",
+                        qubit, circuit.name
+                    ),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Let the server check the duration of the circuits of submitted jobs.
+    ///
+    /// When enabled, the server rejects circuits whose duration exceeds the coherence-limited
+    /// maximum of the device. Rejections of a submission because of the circuit duration are
+    /// returned as [IqmBackendError::JobFailed] with the message of the server and an empty job ID,
+    /// since no job is created. Disabled by default.
+    ///
+    /// # Arguments
+    ///
+    /// * `circuit_duration_check` - Whether the duration of the circuits is checked.
+    pub fn set_circuit_duration_check(&mut self, circuit_duration_check: bool) {
+        self.circuit_duration_check = circuit_duration_check
+    }
+
+    /// Print the warnings of the IQM device to stderr when querying the results of jobs, and the
+    /// warnings of the backend when submitting jobs.
+    ///
+    /// Enabled by default. The warnings can also be read with [Backend::get_last_warnings],
+    /// [Backend::run_circuit_batch_with_warnings] and [Backend::get_submission_warnings], which is
+    /// preferable when stderr is not visible, e.g. in notebooks.
+    ///
+    /// # Arguments
+    ///
+    /// * `print_warnings` - Whether warnings are printed.
+    pub fn set_print_warnings(&mut self, print_warnings: bool) {
+        self.print_warnings = print_warnings
+    }
+
+    /// Returns the warnings of the IQM device for the last job whose results were waited for.
+    ///
+    /// The warnings are updated by [Backend::wait_for_results] and therefore by all functions
+    /// running circuits. None if no results have been waited for or the server did not report
+    /// warnings for the last job.
+    pub fn get_last_warnings(&self) -> Option<Vec<String>> {
+        self.last_warnings.get()
+    }
+
+    /// Returns the warnings raised by the backend itself when submitting the last job.
+    ///
+    /// For example a number of shots below the threshold set with
+    /// [Backend::set_min_shots_warning], or rounded as enabled with [Backend::set_round_shots].
+    /// None if the last submission raised no warnings.
+    pub fn get_submission_warnings(&self) -> Option<Vec<String>> {
+        self.submission_warnings.get()
+    }
+
+    /// Record a warning of the current submission, printing it to stderr if enabled with
+    /// [Backend::set_print_warnings].
+    fn warn_submission(&self, warning: String) {
+        if self.print_warnings {
+            eprintln!("Warning: {}", warning);
+        }
+        self.submission_warnings.push(warning);
+    }
+
+    /// Set the ID of the calibration set the submitted jobs are executed with.
+    ///
+    /// Pinning the calibration set makes runs reproducible across recalibrations of the device.
+    /// When set, [Backend::wait_for_results] and the functions running circuits return an
+    /// [IqmBackendError::MetadataError] if the calibration set reported with the results of a job
+    /// differs. Defaults to None, which uses the latest calibration set.
+    ///
+    /// # Arguments
+    ///
+    /// * `calibration_set_id` - The ID of the calibration set, or None for the latest one.
+    pub fn set_calibration_set_id(&mut self, calibration_set_id: Option<String>) {
+        self.calibration_set_id = calibration_set_id
+    }
+
+    /// Set the priority tier requested for the submitted jobs.
+    ///
+    /// The priority is sent in the `priority` field of the run requests. Accounts with priority
+    /// access can request a faster turnaround, the server decides which tiers are accepted. If
+    /// None, the field is omitted and the jobs run with the default tier of the account.
+    ///
+    /// # Arguments
+    ///
+    /// * `job_priority` - The name of the priority tier, or None for the default tier.
+    pub fn set_job_priority(&mut self, job_priority: Option<String>) {
+        self.job_priority = job_priority
+    }
+
+    /// Set the seed used by the server to sample the measurement results.
+    ///
+    /// The seed is sent in the custom settings of the run requests. Simulators, such as the demo
+    /// endpoints returning pseudorandom results, then produce the same results for the same
+    /// seed, which makes runs reproducible. Servers running on hardware ignore the seed.
+    ///
+    /// # Arguments
+    ///
+    /// * `random_seed` - The seed, or None to let the server choose one for each submission.
+    pub fn set_random_seed(&mut self, random_seed: Option<u64>) {
+        self.random_seed = random_seed
+    }
+
+    /// Treat the warnings returned by the IQM device as errors.
+    ///
+    /// When enabled, [Backend::wait_for_results] and the functions running circuits return an
+    /// [IqmBackendError::WarningsPresent] error for jobs whose results carry warnings, e.g. about
+    /// stale calibration data, instead of the results. Disabled by default.
+    ///
+    /// # Arguments
+    ///
+    /// * `warnings_as_errors` - Whether warnings are treated as errors.
+    pub fn set_warnings_as_errors(&mut self, warnings_as_errors: bool) {
+        self.warnings_as_errors = warnings_as_errors
+    }
+
+    /// Check the depth of the circuits against the maximum circuit depth of the device.
+    ///
+    /// When enabled, [Backend::validate_circuit] rejects circuits whose native depth exceeds
+    /// [Backend::max_circuit_depth]. Since the limit is queried from the server for each validated
+    /// circuit, the check is disabled by default.
+    ///
+    /// # Arguments
+    ///
+    /// * `check_circuit_depth` - Whether the depth of the circuits is checked.
+    pub fn set_check_circuit_depth(&mut self, check_circuit_depth: bool) {
+        self.check_circuit_depth = check_circuit_depth
+    }
+
+    /// Returns the custom settings sent with the run requests.
+    fn custom_settings(&self) -> Option<HashMap<String, String>> {
+        self.random_seed
+            .map(|seed| HashMap::from([("seed".to_string(), seed.to_string())]))
+    }
+
+    /// Set a prefix for the names of the circuits submitted to the server.
+    ///
+    /// Circuits are submitted as `<prefix>qc_<index>`, which makes it easier to identify them in
+    /// the job history. The names are sanitized with [crate::sanitize_circuit_name] before
+    /// submission.
+    ///
+    /// # Arguments
+    ///
+    /// * `circuit_name_prefix` - The prefix, or None to submit the circuits without prefix.
+    pub fn set_circuit_name_prefix(&mut self, circuit_name_prefix: Option<String>) {
+        self.circuit_name_prefix = circuit_name_prefix
+    }
+
+    /// Set the HTTP status codes of the server responses that are treated as success.
+    ///
+    /// Defaults to 200, 201 and 202. Only needed for deployments behind gateways returning other
+    /// status codes, e.g. 203, for successful requests.
+    ///
+    /// # Arguments
+    ///
+    /// * `success_status_codes` - The status codes treated as success.
+    pub fn set_success_status_codes(&mut self, success_status_codes: Vec<u16>) {
+        self.success_status_codes = success_status_codes
+    }
+
+    /// Round the number of shots of submitted jobs to the nearest number accepted by the device.
+    ///
+    /// Some devices only accept multiples of a given number of shots, see
+    /// [IqmDevice::round_shots]. When enabled, the number of shots is rounded before submission.
+    /// If it changes, a warning is printed as set with [Backend::set_print_warnings] and can be
+    /// read with [Backend::get_submission_warnings]. Disabled by default.
+    ///
+    /// # Arguments
+    ///
+    /// * `round_shots` - Whether the number of shots is rounded.
+    pub fn set_round_shots(&mut self, round_shots: bool) {
+        self.round_shots = round_shots
+    }
+
+    /// Warn when a job is submitted with fewer shots than the given threshold.
+    ///
+    /// The job is still submitted, so that runs with few shots remain possible for debugging. The
+    /// warning is printed as set with [Backend::set_print_warnings] and can be read with
+    /// [Backend::get_submission_warnings]. Disabled by default.
+    ///
+    /// # Arguments
+    ///
+    /// * `min_shots` - The minimum number of shots without warning, 0 disables the warning.
+    pub fn set_min_shots_warning(&mut self, min_shots: usize) {
+        self.min_shots_warning = min_shots
+    }
+
+    /// Set the serialization format of the run requests sent to the server.
+    ///
+    /// Defaults to JSON. MessagePack reduces the size of large submissions, but requires a
+    /// server accepting `application/msgpack` requests.
+    ///
+    /// # Arguments
+    ///
+    /// * `request_format` - The serialization format.
+    pub fn set_request_format(&mut self, request_format: RequestFormat) {
+        self.request_format = request_format
+    }
+
+    /// Set the treatment of jobs reported as ready without measurement results.
+    ///
+    /// The server can report a job as ready before its measurement results are available. With
+    /// [EmptyResultPolicy::Error] (default) the results are returned as they are and converting
+    /// them into registers fails. [EmptyResultPolicy::RetryThenError] re-fetches the results a few
+    /// times at the polling interval and fails with [IqmBackendError::EmptyResult] if they are
+    /// still missing. [EmptyResultPolicy::ReturnEmpty] returns empty registers, e.g. for jobs whose
+    /// shots were all discarded by heralding.
+    ///
+    /// # Arguments
+    ///
+    /// * `empty_result_policy` - The policy for results without measurements.
+    pub fn set_empty_result_policy(&mut self, empty_result_policy: EmptyResultPolicy) {
+        self.empty_result_policy = empty_result_policy
+    }
+
+    /// Set the strictness of the client-side validation of the circuits.
+    ///
+    /// Defaults to [ValidationLevel::Full]. [ValidationLevel::Off] skips the validation and relies
+    /// on the server rejecting invalid circuits, which saves time for circuits known to be valid,
+    /// e.g. coming from a trusted transpiler. Circuits that cannot be converted into IQM circuits
+    /// are rejected regardless of the level.
+    ///
+    /// # Arguments
+    ///
+    /// * `validation_level` - The validation level.
+    pub fn set_validation_level(&mut self, validation_level: ValidationLevel) {
+        self.validation_level = validation_level
+    }
+
+    /// Measure only as many qubits as the register holds with `PragmaRepeatedMeasurement`.
+    ///
+    /// A `PragmaRepeatedMeasurement` without qubit mapping measures all the qubits of the device,
+    /// which requires a register at least as long as the number of qubits of the device. When
+    /// enabled, only the first qubits of the device, as many as the register holds, are measured
+    /// instead, so that smaller registers can be used for circuits acting on these qubits.
+    /// Disabled by default.
+    ///
+    /// # Arguments
+    ///
+    /// * `register_qubits` - Whether to measure only as many qubits as the register holds.
+    pub fn set_repeated_measurement_register_qubits(&mut self, register_qubits: bool) {
+        self.repeated_measurement_register_qubits = register_qubits
+    }
+
+    /// Returns the number of qubits measured by a `PragmaRepeatedMeasurement` without qubit mapping
+    /// in the circuit, see [Backend::set_repeated_measurement_register_qubits].
+    fn repeated_measurement_qubits(&self, circuit: &Circuit) -> usize {
+        let number_qubits = self.device.number_qubits();
+        if !self.repeated_measurement_register_qubits {
+            return number_qubits;
+        }
+        circuit
+            .iter()
+            .find_map(|op| match op {
+                Operation::PragmaRepeatedMeasurement(o) if o.qubit_mapping().is_none() => {
+                    circuit.definitions().iter().find_map(|def| match def {
+                        Operation::DefinitionBit(reg) if reg.name() == o.readout() => {
+                            Some(*reg.length())
+                        }
+                        _ => None,
+                    })
+                }
+                _ => None,
+            })
+            .map_or(number_qubits, |length| length.min(number_qubits))
+    }
+
+    /// Returns the number of shots submitted for the given requested number of shots, rounded to
+    /// the granularity of the device if enabled with [Backend::set_round_shots].
+    fn submitted_shots(&self, shots: usize) -> usize {
+        if !self.round_shots {
+            return shots;
+        }
+        let rounded = self.device.round_shots(shots);
+        if rounded != shots {
+            self.warn_submission(format!(
+                "Rounding the number of shots from {} to {}, the nearest number of shots \
+                 accepted by the device.",
+                shots, rounded
+            ));
+        }
+        rounded
+    }
+
+    /// Returns the warning for a submission with the given number of shots, if below the threshold
+    /// set with [Backend::set_min_shots_warning].
+    fn min_shots_warning(&self, shots: usize) -> Option<String> {
+        if shots < self.min_shots_warning {
+            Some(format!(
+                "Submitting a job with {} shots, fewer than the minimum of {}. The results may be \
+                 dominated by statistical noise.",
+                shots, self.min_shots_warning
+            ))
+        } else {
+            None
+        }
+    }
+
+    /// Send a request, retrying after network errors and server errors according to the retry
+    /// policy of the backend.
+    ///
+    /// Gives up when the next retry would end after the timeout of the backend.
+    fn send_with_retries(
+        &self,
+        request: impl Fn() -> RequestBuilder,
+    ) -> Result<Response, RoqoqoBackendError> {
+        let start_time = Instant::now();
+        let time_budget = Duration::from_secs_f64(self.timeout_secs);
+        let mut retries = 0;
+        loop {
+            let result = request().send();
+            let retryable = match &result {
+                Ok(response) => response.status().is_server_error(),
+                Err(_) => true,
+            };
+            let backoff = self.retry_policy.backoff(retries);
+            if !retryable
+                || retries >= self.retry_policy.max_retries
+                || start_time.elapsed() + backoff > time_budget
+            {
+                return result.map_err(|err| RoqoqoBackendError::NetworkError {
+                    msg: format!("Error during POST request: {:?}", err),
+                });
+            }
+            retries += 1;
+            thread::sleep(backoff);
+        }
+    }
+
+    /// Apply the decompositions enabled on the backend to a circuit.
+    fn decompose_circuit<'a>(
+        &self,
+        circuit: &'a Circuit,
+    ) -> Result<Cow<'a, Circuit>, IqmBackendError> {
+        let mut circuit = Cow::Borrowed(circuit);
+        if self.decompose_multiqubit {
+            let decomposed = decompose_multi_qubit_gates(&circuit, &self.device)?;
+            circuit = Cow::Owned(decomposed);
+        }
+        if self.auto_resonator_cz {
+            if let IqmDevice::DenebDevice(device) = &self.device {
+                let rewritten = implement_cz_with_resonator(&circuit, device)?;
+                circuit = Cow::Owned(rewritten);
+            }
+        }
+        Ok(circuit)
+    }
+
+    /// Collect the settings applied to the HTTP client.
+    fn client_config(&self) -> ClientConfig {
+        ClientConfig {
+            https_only: !self.allow_http,
+            pool_max_idle_per_host: self.pool_max_idle_per_host,
+            tcp_keepalive: self.tcp_keepalive,
+        }
+    }
+
+    /// Create the HTTP client used to communicate with the IQM server.
+    fn build_client(&self) -> Result<Client, RoqoqoBackendError> {
+        self.client_config()
+            .apply(Client::builder())
+            .build()
+            .map_err(|err| RoqoqoBackendError::NetworkError {
+                msg: format!("Could not create HTTPS client: {:?}", err),
+            })
+    }
+
+    /// Check that the device's connectivity is respected.
+    ///
+    /// # Arguments
+    ///
+    /// * `circuit` - The [roqoqo::Circuit] to be checked
+    ///
+    /// # Returns
+    ///
+    /// *`Err(RoqoqoBackendError)` - The circuit is invalid.
+    pub fn validate_circuit_connectivity(
+        &self,
+        circuit: &Circuit,
+    ) -> Result<(), RoqoqoBackendError> {
+        let allowed = [
+            "PragmaSetNumberOfMeasurements",
+            "PragmaRepeatedMeasurement",
+            "MeasureQubit",
+            "DefinitionBit",
+            "InputBit",
+            "PragmaGlobalPhase",
+            "PragmaStopParallelBlock",
+        ];
+
+        for op in circuit.iter() {
+            if allowed.contains(&op.hqslang()) {
+                continue;
+            }
+            if let Ok(inner_op) = SingleQubitOperation::try_from(op) {
+                if self
+                    .device
+                    .single_qubit_gate_time(inner_op.hqslang(), inner_op.qubit())
+                    .is_none()
+                {
+                    return Err(RoqoqoBackendError::OperationNotInBackend {
+                        backend: "IQM",
+                        hqslang: inner_op.hqslang(),
+                    });
+                }
+            } else if let Ok(inner_op) = TwoQubitOperation::try_from(op) {
+                if self
+                    .device
+                    .two_qubit_gate_time(inner_op.hqslang(), inner_op.control(), inner_op.target())
+                    .is_none()
+                {
+                    return Err(RoqoqoBackendError::OperationNotInBackend {
+                        backend: "IQM",
+                        hqslang: inner_op.hqslang(),
+                    });
+                }
+            } else if let Ok(inner_op) = MultiQubitOperation::try_from(op) {
+                if self
+                    .device
+                    .multi_qubit_gate_time(inner_op.hqslang(), inner_op.qubits())
+                    .is_none()
+                {
+                    return Err(RoqoqoBackendError::OperationNotInBackend {
+                        backend: "IQM",
+                        hqslang: inner_op.hqslang(),
+                    });
+                }
+            } else {
+                return Err(RoqoqoBackendError::OperationNotInBackend {
+                    backend: "IQM",
+                    hqslang: op.hqslang(),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Check that the operations of a decomposed circuit are supported by the device.
+    fn validate_circuit_on_device(&self, circuit: &Circuit) -> Result<(), IqmBackendError> {
+        // NOTE checking also the name is a workaround for a pyo3 deserialization bug that causes
+        // the if let to match even when the device is not Deneb. This issue should have been fixed
+        // by removing the bincode deserialization attempt in the device pyo3 files, but I leave the
+        // name check here for extra insurance.
+        if self.device.name() == "Deneb" {
+            if let IqmDevice::DenebDevice(device) = &self.device {
+                device.validate_circuit(circuit)?
+            }
+        } else {
+            self.validate_circuit_connectivity(circuit).map_err(|err| {
+                IqmBackendError::InvalidCircuit {
+                    msg: err.to_string(),
+                }
+            })?
+        }
+        Ok(())
+    }
+
+    /// Check if the circuit is well-defined according to the device specifications.
+    ///
+    /// The checks depend on the validation level of the backend, see
+    /// [Backend::set_validation_level].
+    ///
+    /// # Arguments
+    ///
+    /// * `circuit` - The [roqoqo::Circuit] to be checked
+    pub fn validate_circuit(&self, circuit: &Circuit) -> Result<(), IqmBackendError> {
+        if self.validation_level == ValidationLevel::Off {
+            return Ok(());
+        }
+        let circuit = self.decompose_circuit(circuit)?;
+        let circuit = circuit.as_ref();
+        if self.validation_level == ValidationLevel::Connectivity {
+            return self.validate_circuit_on_device(circuit);
+        }
+
+        // Check that the circuit doesn't contain more qubits than the device supports
+        let mut measured_qubits: Vec<usize> = vec![];
+        let number_qubits = _get_number_qubits(circuit).ok_or(IqmBackendError::EmptyCircuit)?;
+        _validate_no_feed_forward(circuit)?;
+        _validate_supported_measurements(circuit)?;
+
+        self.validate_circuit_on_device(circuit)?;
+
+        // Check that
+        // 1) Every qubit is measured exactly once
+        // 2) Output registers are large enough
+        let mut measured = false;
+        for op in circuit.iter() {
+            match op {
+                Operation::MeasureQubit(o) => {
+                    measured = true;
+                    let qubit = *o.qubit();
+                    if measured_qubits.contains(&qubit) {
+                        return Err(IqmBackendError::InvalidCircuit {
+                            msg: format!("Qubit {} is being measured multiple times.", qubit),
+                        });
+                    } else {
+                        measured_qubits.push(qubit)
+                    }
+                }
+                Operation::PragmaRepeatedMeasurement(o) => {
+                    measured = true;
+                    if !measured_qubits.is_empty() {
+                        return Err(IqmBackendError::InvalidCircuit {
+                            msg: "Qubits are being measured more than once. When using \
+                                PragmaRepeatedMeasurement, there should not be individual qubit \
+                                measurements, and the PragmaRepeatedMeasurement operation can \
+                                appear only once in the circuit."
+                                .to_string(),
+                        });
+                    } else {
+                        measured_qubits.extend(0..self.device.number_qubits())
+                    }
+
+                    let mut readout_length: usize = 0;
+                    for def in circuit.definitions() {
+                        if let Operation::DefinitionBit(reg) = def {
+                            if reg.name() == o.readout() {
+                                readout_length = *reg.length()
+                            }
+                        }
+                    }
+
+                    // Only the qubits actually written to the register need to fit into it
+                    let number_measured_qubits = match o.qubit_mapping() {
+                        Some(map) => map.len(),
+                        None if self.repeated_measurement_register_qubits => number_qubits,
+                        None => self.device.number_qubits(),
+                    };
+                    if number_measured_qubits > readout_length {
+                        return Err(IqmBackendError::RegisterTooSmall {
+                            name: o.readout().to_string(),
+                        });
+                    }
+                }
+                _ => (),
+            }
+        }
+        if !measured {
+            return Err(IqmBackendError::InvalidCircuit {
+                msg: "All circuits submitted need to have at least one measurement instruction."
+                    .to_string(),
+            });
+        }
+
+        if self.check_circuit_depth {
+            if let Some(max_circuit_depth) = self.max_circuit_depth() {
+                let (iqm_circuit, _) = call_circuit(
+                    circuit.iter(),
+                    self.repeated_measurement_qubits(circuit),
+                    None,
+                    0,
+                )?;
+                let depth = _native_circuit_depth(&iqm_circuit);
+                if depth > max_circuit_depth {
+                    return Err(IqmBackendError::InvalidCircuit {
+                        msg: format!(
+                            "The native circuit has a depth of {}, but the device allows a depth \
+                             of at most {}.",
+                            depth, max_circuit_depth
+                        ),
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Analyze a circuit without submitting it.
+    ///
+    /// Collects every problem found by [Backend::validate_circuit] and by the conversion of the
+    /// circuit, instead of stopping at the first one, together with warnings and statistics of
+    /// the circuit. Gates the device does not support count with a duration of zero.
+    ///
+    /// # Arguments
+    ///
+    /// * `circuit` - The [roqoqo::Circuit] to be analyzed
+    ///
+    /// # Returns
+    ///
+    /// * `CircuitAnalysis` - The errors, warnings and statistics of the circuit.
+    pub fn analyze_circuit(&self, circuit: &Circuit) -> CircuitAnalysis {
+        let mut analysis = CircuitAnalysis::default();
+        if let Err(err) = self.validate_circuit(circuit) {
+            analysis.errors.push(err.to_string())
+        }
+        let circuit = self
+            .decompose_circuit(circuit)
+            .unwrap_or(Cow::Borrowed(circuit));
+        if let Err(err) = call_circuit(
+            circuit.iter(),
+            self.repeated_measurement_qubits(&circuit),
+            self.number_measurements_internal,
+            0,
+        ) {
+            let msg = err.to_string();
+            if !analysis.errors.contains(&msg) {
+                analysis.errors.push(msg)
+            }
+        }
+
+        let mut gate_qubits: BTreeSet<usize> = BTreeSet::new();
+        let mut measured_qubits: BTreeSet<usize> = BTreeSet::new();
+        let mut measures_all_qubits = false;
+        // Time at which each qubit (false) or resonator (true) becomes free
+        let mut timeline: HashMap<(bool, usize), f64> = HashMap::new();
+        for op in circuit.iter() {
+            match op {
+                Operation::MeasureQubit(o) => {
+                    measured_qubits.insert(*o.qubit());
+                    continue;
+                }
+                Operation::PragmaRepeatedMeasurement(_) => {
+                    measures_all_qubits = true;
+                    continue;
+                }
+                _ => (),
+            }
+            if !op.tags().contains(&"GateOperation") {
+                continue;
+            }
+            analysis.gate_count += 1;
+            let (qubits, resonator) = match op {
+                Operation::CZQubitResonator(o) => (vec![*o.qubit()], Some(*o.mode())),
+                Operation::SingleExcitationLoad(o) => (vec![*o.qubit()], Some(*o.mode())),
+                Operation::SingleExcitationStore(o) => (vec![*o.qubit()], Some(*o.mode())),
+                _ => match op.involved_qubits() {
+                    InvolvedQubits::Set(qubits) => {
+                        let mut qubits: Vec<usize> = qubits.into_iter().collect();
+                        qubits.sort_unstable();
+                        (qubits, None)
+                    }
+                    _ => (vec![], None),
+                },
+            };
+            gate_qubits.extend(qubits.iter());
+            let gate_time = match (qubits.as_slice(), resonator) {
+                ([qubit], None) => self.device.single_qubit_gate_time(op.hqslang(), qubit),
+                ([qubit], Some(resonator)) => {
+                    self.device
+                        .two_qubit_gate_time(op.hqslang(), qubit, &resonator)
+                }
+                ([a, b], None) => self
+                    .device
+                    .two_qubit_gate_time(op.hqslang(), a, b)
+                    .or_else(|| self.device.two_qubit_gate_time(op.hqslang(), b, a)),
+                _ => None,
+            }
+            .unwrap_or(0.0);
+            let slots: Vec<(bool, usize)> = qubits
+                .iter()
+                .map(|qubit| (false, *qubit))
+                .chain(resonator.map(|resonator| (true, resonator)))
+                .collect();
+            let start = slots
+                .iter()
+                .map(|slot| timeline.get(slot).copied().unwrap_or(0.0))
+                .fold(0.0, f64::max);
+            for slot in slots {
+                timeline.insert(slot, start + gate_time);
+            }
+        }
+
+        if !measures_all_qubits {
+            for qubit in gate_qubits.difference(&measured_qubits) {
+                analysis.warnings.push(format!(
+                    "Qubit {} is acted on by gates but never measured.",
+                    qubit
+                ))
+            }
+        }
+        analysis.qubit_count = gate_qubits.union(&measured_qubits).count();
+        analysis.estimated_duration = timeline.values().copied().fold(0.0, f64::max);
+        analysis
+    }
+
+    /// Describe the native instructions a circuit is compiled to, with their timing.
+    ///
+    /// Lists each instruction submitted for the circuit with its qubits, its gate time on the
+    /// device and the time at which it ends. Instructions acting on disjoint qubits and resonators
+    /// are scheduled in parallel, and instructions without a gate time on the device (measurements,
+    /// barriers) are scheduled with a duration of zero and shown with `-`.
+    ///
+    /// # Arguments
+    ///
+    /// * `circuit` - The [roqoqo::Circuit] to be compiled.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(String)` - The listing of the native instructions.
+    /// * `Err(IqmBackendError)` - The circuit could not be converted.
+    pub fn explain_compilation(&self, circuit: &Circuit) -> Result<String, IqmBackendError> {
+        let circuit = self.decompose_circuit(circuit)?;
+        let (iqm_circuit, _) = call_circuit(
+            circuit.iter(),
+            self.repeated_measurement_qubits(&circuit),
+            self.number_measurements_internal,
+            0,
+        )?;
+
+        let mut lines = vec![format!(
+            "{:>4}  {:<8} {:<20} {:>8} {:>8}",
+            "#", "name", "qubits", "duration", "end"
+        )];
+        // Time at which each qubit or resonator becomes free
+        let mut timeline: HashMap<&str, f64> = HashMap::new();
+        for (index, instruction) in iqm_circuit.instructions.iter().enumerate() {
+            let gate_time = self.instruction_gate_time(instruction);
+            let start = instruction
+                .qubits
+                .iter()
+                .map(|qubit| timeline.get(qubit.as_str()).copied().unwrap_or(0.0))
+                .fold(0.0, f64::max);
+            let end = start + gate_time.unwrap_or(0.0);
+            for qubit in instruction.qubits.iter() {
+                timeline.insert(qubit, end);
+            }
+            lines.push(format!(
+                "{:>4}  {:<8} {:<20} {:>8} {:>8.2}",
+                index,
+                instruction.name,
+                instruction.qubits.join(","),
+                gate_time.map_or("-".to_string(), |time| format!("{:.2}", time)),
+                end
+            ));
+        }
+        Ok(lines.join("\n"))
+    }
+
+    /// Returns the gate time on the device of a native instruction, if it is a gate.
+    fn instruction_gate_time(&self, instruction: &IqmInstruction) -> Option<f64> {
+        let qubits: Vec<usize> = instruction
+            .qubits
+            .iter()
+            .filter_map(|qubit| _convert_qubit_name_iqm_to_qoqo(qubit))
+            .collect();
+        let resonators: Vec<usize> = instruction
+            .qubits
+            .iter()
+            .filter_map(|qubit| _convert_resonator_name_iqm_to_qoqo(qubit))
+            .collect();
+        match (
+            instruction.name.as_str(),
+            qubits.as_slice(),
+            resonators.as_slice(),
+        ) {
+            ("prx", [qubit], []) => self.device.single_qubit_gate_time("RotateXY", qubit),
+            ("cz", [control, target], []) => {
+                self.device
+                    .two_qubit_gate_time("ControlledPauliZ", control, target)
+            }
+            ("cz", [qubit], [resonator]) => {
+                self.device
+                    .two_qubit_gate_time("CZQubitResonator", qubit, resonator)
+            }
+            ("move", [qubit], [resonator]) => {
+                self.device
+                    .two_qubit_gate_time("SingleExcitationLoad", qubit, resonator)
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the map from measurement keys to register positions built when converting the
+    /// circuit for submission.
+    ///
+    /// For each output register written by a measurement, the map contains the register indices
+    /// the measured qubits are written to, in the order of the qubits of the corresponding
+    /// `measure` instruction, together with the length of the register.
+    ///
+    /// # Arguments
+    ///
+    /// * `circuit` - The [roqoqo::Circuit] to be converted.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(HashMap<String, (Vec<usize>, usize)>)` - The register positions and lengths by register name.
+    /// * `Err(IqmBackendError)` - The circuit could not be converted.
+    pub fn measurement_key_map(
+        &self,
+        circuit: &Circuit,
+    ) -> Result<HashMap<String, (Vec<usize>, usize)>, IqmBackendError> {
+        let circuit = self.decompose_circuit(circuit)?;
+        let (iqm_circuit, _) = call_circuit(
+            circuit.iter(),
+            self.repeated_measurement_qubits(&circuit),
+            self.number_measurements_internal,
+            0,
+        )?;
+        Ok(iqm_circuit.metadata.unwrap_or_default())
+    }
+
+    /// Returns the number of qubits measured into each output register of a circuit.
+    ///
+    /// The number can be smaller than the length of the register, in which case the bits that
+    /// are not measured are always false in the results. Output registers without measurements
+    /// have a count of zero.
+    ///
+    /// # Arguments
+    ///
+    /// * `circuit` - The [roqoqo::Circuit] to be converted.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(HashMap<String, usize>)` - The number of measured qubits by register name.
+    /// * `Err(IqmBackendError)` - The circuit could not be converted.
+    pub fn measured_qubit_counts(
+        &self,
+        circuit: &Circuit,
+    ) -> Result<HashMap<String, usize>, IqmBackendError> {
+        Ok(self
+            .measurement_key_map(circuit)?
+            .into_iter()
+            .map(|(name, (positions, _))| (name, positions.len()))
+            .collect())
+    }
+
+    /// Estimate the cost in credits of running a batch of circuits, using the cost model set with
+    /// [Backend::set_cost_model].
+    ///
+    /// Measurement instructions and barriers are not counted as gates. The number of shots of each
+    /// circuit is determined in the same way as when the batch is submitted.
+    ///
+    /// # Arguments
+    ///
+    /// * `circuit_batch` - The batch of circuits to be estimated.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(f64)` - The estimated cost of the batch.
+    /// * `Err(IqmBackendError)` - A circuit could not be converted.
+    pub fn estimate_cost(&self, circuit_batch: &[Circuit]) -> Result<f64, IqmBackendError> {
+        let mut cost = 0.0;
+        for (circuit_index, circuit) in circuit_batch.iter().enumerate() {
+            let circuit = self.decompose_circuit(circuit)?;
+            let (iqm_circuit, number_measurements) = call_circuit(
+                circuit.iter(),
+                self.repeated_measurement_qubits(&circuit),
+                self.overwritten_number_measurements(&circuit, self.number_measurements_internal),
+                circuit_index,
+            )?;
+            let number_gates = iqm_circuit
+                .instructions
+                .iter()
+                .filter(|instruction| {
+                    instruction.name != "measure" && instruction.name != "barrier"
+                })
+                .count();
+            cost += number_measurements as f64
+                * (self.cost_model.cost_per_shot
+                    + self.cost_model.cost_per_gate * number_gates as f64);
+        }
+        Ok(cost)
+    }
+
+    /// List the most recent jobs submitted to the endpoint of the device.
+    ///
+    /// Useful to recover the IDs of jobs whose results have not been retrieved.
+    ///
+    /// # Arguments
+    ///
+    /// * `limit` - The maximum number of jobs to list.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Vec<JobSummary>)` - The IDs, statuses and submission times of the recent jobs.
+    /// * `Err(RoqoqoBackendError)` - If something goes wrong with the HTTP request, or response is not formatted correctly.
+    pub fn list_jobs(&self, limit: usize) -> Result<Vec<JobSummary>, RoqoqoBackendError> {
+        let client = self.build_client()?;
+
+        let response = client
+            .get(self.device.remote_host())
+            .query(&[("limit", limit)])
+            .headers(_construct_headers(&self.current_access_token()))
+            .send()
+            .map_err(|e| RoqoqoBackendError::NetworkError {
+                msg: format!("Error during GET request: {:?}", e),
+            })?;
+
+        check_response_status(&response, &self.success_status_codes)?;
+
+        response
+            .json::<Vec<JobSummary>>()
+            .map_err(|err| RoqoqoBackendError::NetworkError {
+                msg: format!("Error during deserialisation of GET response: {:?}", err),
+            })
+    }
+
+    /// Query results of a submitted job.
+    ///
+    /// The results of jobs that are ready, failed or aborted are cached, see
+    /// [Backend::clear_result_cache].
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The job ID for the query.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(IqmRunResult)` - Result of the job (status can be pending).
+    /// * `Err(RoqoqoBackendError)` - If something goes wrong with the HTTP request, or response is not formatted correctly.
+    pub fn get_results(&self, id: String) -> Result<IqmRunResult, RoqoqoBackendError> {
+        if let Some(iqm_result) = self.result_cache.get(&id) {
+            return Ok(iqm_result);
+        }
+        let client = self.build_client()?;
+
+        let job_url = EndpointPaths::format(
+            &self.endpoint_paths.job_status,
+            &self.device.remote_host(),
+            &id,
+        );
+
+        let result = self.send_authenticated(|token| {
+            client
+                .get(job_url.clone())
+                .headers(_construct_headers(token))
+                .send()
+                .map_err(|e| RoqoqoBackendError::NetworkError {
+                    msg: format!("Error during GET request: {:?}", e),
+                })
+        })?;
+
+        let iqm_result =
+            result
+                .json::<IqmRunResult>()
+                .map_err(|err| RoqoqoBackendError::NetworkError {
+                    msg: format!("Error during deserialisation of GET response: {:?}", err),
+                })?;
+
+        Ok(self.record_results(id, iqm_result))
+    }
+
+    /// Print the warnings of fetched results and cache the results of finished jobs.
+    fn record_results(&self, id: String, iqm_result: IqmRunResult) -> IqmRunResult {
+        if let Some(warnings) = iqm_result.warnings.as_ref().filter(|_| self.print_warnings) {
+            eprintln!("Warnings: {:?}", warnings);
+        }
+        // The results of finished jobs do not change anymore, except for jobs reported as ready
+        // before their measurements are available
+        let finished = match iqm_result.status {
+            Status::Ready => iqm_result.measurements.is_some(),
+            Status::Failed | Status::Aborted => true,
+            _ => false,
+        };
+        if finished {
+            self.result_cache.insert(id, iqm_result.clone());
+        }
+        iqm_result
+    }
+
+    /// Query the status of a submitted job without waiting for it to finish.
+    ///
+    /// Unlike [Backend::get_results], the response is only deserialized into the status of the
+    /// job, so that the measurement results are not processed. Useful for custom polling loops.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The job ID for the query.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(String)` - The status of the job, e.g. `pending compilation` or `ready`.
+    /// * `Err(RoqoqoBackendError)` - If something goes wrong with the HTTP request, or the response
+    ///   is not formatted correctly.
+    pub fn get_status(&self, id: String) -> Result<String, RoqoqoBackendError> {
+        let status = match self.result_cache.get(&id) {
+            Some(iqm_result) => iqm_result.status,
+            None => {
+                let client = self.build_client()?;
+                let job_url = EndpointPaths::format(
+                    &self.endpoint_paths.job_status,
+                    &self.device.remote_host(),
+                    &id,
+                );
+                let response = self.send_authenticated(|token| {
+                    client
+                        .get(job_url.clone())
+                        .headers(_construct_headers(token))
+                        .send()
+                        .map_err(|e| RoqoqoBackendError::NetworkError {
+                            msg: format!("Error during GET request: {:?}", e),
+                        })
+                })?;
+                check_status_code(response.status(), &self.success_status_codes)?;
+                response
+                    .json::<IqmRunStatus>()
+                    .map_err(|err| RoqoqoBackendError::NetworkError {
+                        msg: format!("Error during deserialisation of GET response: {:?}", err),
+                    })?
+                    .status
+            }
+        };
+        serde_json::to_value(status)
+            .ok()
+            .and_then(|value| value.as_str().map(str::to_lowercase))
+            .ok_or_else(|| RoqoqoBackendError::GenericError {
+                msg: "Could not serialize the job status.".to_string(),
+            })
+    }
+
+    /// Clear the cache of the results of finished jobs.
+    ///
+    /// [Backend::get_results] caches the results of jobs that are ready, failed or aborted, so
+    /// that repeated queries for the same job are answered without a request to the server.
+    pub fn clear_result_cache(&self) {
+        self.result_cache.clear()
+    }
+
+    /// Query the compilation warnings of the individual circuits of a submitted job.
+    ///
+    /// Helps identifying which circuits of a large batch triggered warnings, e.g. because a gate
+    /// was decomposed sub-optimally. See [IqmRunResult::circuit_warnings].
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The job ID for the query.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(HashMap<String, Vec<String>>)` - The warnings of each circuit with warnings, keyed by
+    ///   circuit name.
+    /// * `Err(RoqoqoBackendError)` - If something goes wrong with the HTTP request, or response is not formatted correctly.
+    pub fn get_circuit_warnings(
+        &self,
+        id: String,
+    ) -> Result<HashMap<String, Vec<String>>, RoqoqoBackendError> {
+        Ok(self.get_results(id)?.circuit_warnings())
+    }
+
+    /// Poll results until job is either ready, failed, aborted or timed out.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The job ID for the query"Job failed with job ID: {}"
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(BatchResult)` - Result of the job if ready.
+    /// * `Err(IqmBackendError)` - If job failed, timed out or aborted, or IQM returned empty results.
+    ///   With [Backend::set_warnings_as_errors], also if the results carry warnings. With
+    ///   [Backend::set_calibration_set_id], also if the job used a different calibration set.
+    pub fn wait_for_results(&self, id: String) -> Result<IqmRunResult, IqmBackendError> {
+        let start_time = Instant::now();
+        let mut empty_result_retries = 0;
+
+        while start_time.elapsed().as_secs_f64() < self.timeout_secs {
+            let iqm_result = self.get_results(id.clone())?;
+            match self.finished_results(&id, iqm_result, &mut empty_result_retries) {
+                Some(result) => return result,
+                None => thread::sleep(Duration::from_secs_f64(self.polling_interval_secs)),
+            }
+        }
+        Err(self.timeout_error())
+    }
+
+    /// Returns the outcome of a job if it has finished, or None if it is still pending.
+    ///
+    /// `empty_result_retries` counts the results reported as ready without measurements that were
+    /// re-fetched according to the [EmptyResultPolicy].
+    fn finished_results(
+        &self,
+        id: &str,
+        mut iqm_result: IqmRunResult,
+        empty_result_retries: &mut usize,
+    ) -> Option<Result<IqmRunResult, IqmBackendError>> {
+        let id = id.to_string();
+        match iqm_result.status {
+            Status::Ready => {
+                if iqm_result.measurements.is_none() {
+                    match self.empty_result_policy {
+                        EmptyResultPolicy::Error => (),
+                        EmptyResultPolicy::RetryThenError => {
+                            if *empty_result_retries >= EMPTY_RESULT_RETRIES {
+                                return Some(Err(IqmBackendError::EmptyResult { id }));
+                            }
+                            *empty_result_retries += 1;
+                            return None;
+                        }
+                        EmptyResultPolicy::ReturnEmpty => iqm_result.measurements = Some(vec![]),
+                    }
+                }
+                self.last_warnings.set(iqm_result.warnings.clone());
+                if let Err(err) = self.check_calibration_set_id(&iqm_result) {
+                    return Some(Err(err));
+                }
+                let warnings = iqm_result.warnings();
+                if self.warnings_as_errors && !warnings.is_empty() {
+                    return Some(Err(IqmBackendError::WarningsPresent { id, warnings }));
+                }
+                Some(Ok(iqm_result))
+            }
+            Status::Failed => {
+                let msg = iqm_result.message.expect(
+                    "Job has failed but response message is
+                     empty. Something went wrong on the server side.",
+                );
+                Some(Err(_job_failed_error(id, msg)))
+            }
+            Status::Aborted => Some(Err(IqmBackendError::JobAborted { id })),
+            _ => None,
+        }
+    }
+
+    /// Returns the error of a job that did not finish before the timeout of the backend.
+    fn timeout_error(&self) -> IqmBackendError {
+        IqmBackendError::RoqoqoBackendError(RoqoqoBackendError::Timeout {
+            msg: format!("Job did not finish in {} seconds", self.timeout_secs),
+        })
+    }
+
+    /// Check that a job was executed with the calibration set set on the backend, if any.
+    fn check_calibration_set_id(&self, iqm_result: &IqmRunResult) -> Result<(), IqmBackendError> {
+        match (
+            &self.calibration_set_id,
+            &iqm_result.metadata.calibration_set_id,
+        ) {
+            (Some(expected), Some(actual)) if expected != actual => {
+                Err(IqmBackendError::MetadataError {
+                    msg: format!(
+                        "Job was executed with calibration set {} instead of the requested \
+                         calibration set {}.",
+                        actual, expected
+                    ),
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Wait for the results of a job and return them in the native layout of the server.
+    ///
+    /// Unlike with [results_to_registers], the results are not reshaped into registers, which is
+    /// useful for users processing the results with their own pipeline.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The job ID for the query.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(BatchResult)` - The per-shot results of each measurement of each circuit, in
+    ///   submission order.
+    /// * `Err(IqmBackendError)` - If the job failed, timed out or aborted, or IQM returned empty
+    ///   results.
+    pub fn get_raw_batch_result(&self, id: String) -> Result<BatchResult, IqmBackendError> {
+        let results = self.wait_for_results(id.clone())?;
+        let meas_results = results
+            .measurements
+            .ok_or(IqmBackendError::EmptyResult { id })?;
+        Ok(
+            _match_results_to_circuits(&results.metadata.request.circuits, meas_results)?
+                .into_iter()
+                .map(|(_, result)| result)
+                .collect(),
+        )
+    }
+
+    /// Poll the status of a job until it differs from the given status.
+    ///
+    /// Returns on the first status change, e.g. when the job moves from `pending compilation` to
+    /// `pending execution`, which allows tracking the progress of a job more closely than with
+    /// [Backend::wait_for_results].
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The job ID for the query.
+    /// * `from` - The status the job is expected to leave.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Status)` - The new status of the job.
+    /// * `Err(IqmBackendError)` - If the status did not change before the timeout or the request
+    ///   failed.
+    pub fn wait_for_status_change(
+        &self,
+        id: String,
+        from: Status,
+    ) -> Result<Status, IqmBackendError> {
+        let start_time = Instant::now();
+
+        while start_time.elapsed().as_secs_f64() < self.timeout_secs {
+            let status = self.get_results(id.clone())?.status;
+            if status != from {
+                return Ok(status);
+            }
+            let duration = Duration::from_secs_f64(self.polling_interval_secs);
+            thread::sleep(duration);
+        }
+        Err(IqmBackendError::RoqoqoBackendError(
+            RoqoqoBackendError::Timeout {
+                msg: format!(
+                    "Job status did not change from {:?} in {} seconds",
+                    from, self.timeout_secs
+                ),
+            },
+        ))
+    }
+
+    /// Estimate the time until a submitted job has finished from the queue metrics of the server.
+    ///
+    /// The estimate assumes that the jobs queued before the job and the job itself each take the
+    /// average execution time reported by the server. Jobs that have already finished, failed or
+    /// been aborted have an estimate of zero.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The job ID for the query.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Some(Duration))` - The estimated time until the job has finished.
+    /// * `Ok(None)` - The server does not report the queue position or the average execution time.
+    /// * `Err(RoqoqoBackendError)` - If something goes wrong with the HTTP request.
+    pub fn estimate_wait_time(&self, id: String) -> Result<Option<Duration>, RoqoqoBackendError> {
+        let iqm_result = self.get_results(id)?;
+        if matches!(
+            iqm_result.status,
+            Status::Ready | Status::Failed | Status::Aborted
+        ) {
+            return Ok(Some(Duration::ZERO));
+        }
+        match (iqm_result.queue_position, iqm_result.average_execution_time) {
+            (Some(queue_position), Some(average_execution_time))
+                if average_execution_time.is_finite() && average_execution_time >= 0.0 =>
+            {
+                Ok(Some(Duration::from_secs_f64(
+                    (queue_position + 1) as f64 * average_execution_time,
+                )))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// Abort a submitted job.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The ID of the job to abort.
+    ///
+    /// # Returns
+    ///
+    /// * `Err(RoqoqoBackendError)` - If the job abortion failed.
+    pub fn abort_job(&self, id: String) -> Result<(), IqmBackendError> {
+        let client = self.build_client()?;
+
+        let abort_url =
+            EndpointPaths::format(&self.endpoint_paths.abort, &self.device.remote_host(), &id);
+
+        let resp = client
+            .post(abort_url)
+            .headers(_construct_headers(&self.current_access_token()))
+            .send()
+            .map_err(|err| {
+                IqmBackendError::RoqoqoBackendError(RoqoqoBackendError::NetworkError {
+                    msg: format!("Error during POST request of abort_job: {:?}", err),
+                })
+            })?;
+
+        if check_response_status(&resp, &self.success_status_codes).is_ok() {
+            return Ok(());
+        }
+        let text = resp.text().unwrap_or_default();
+        Err(_abort_failed_error(id, &text))
+    }
+
+    /// Abort all unfinished jobs whose name starts with the given prefix.
+    ///
+    /// Useful together with [Backend::set_circuit_name_prefix] to abort all the jobs of a run.
+    /// The jobs are searched among the most recent jobs returned by [Backend::list_jobs]. Jobs
+    /// without a name and jobs that are already ready, failed or aborted are skipped.
+    ///
+    /// # Arguments
+    ///
+    /// * `prefix` - The prefix of the names of the jobs to abort.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(AbortResults)` - The ID of each matching job with the result of its abortion.
+    /// * `Err(IqmBackendError)` - The jobs could not be listed.
+    pub fn abort_jobs_by_prefix(&self, prefix: &str) -> Result<AbortResults, IqmBackendError> {
+        let jobs = self.list_jobs(ABORT_SEARCH_LIMIT)?;
+        Ok(jobs
+            .into_iter()
+            .filter(|job| {
+                job.name
+                    .as_ref()
+                    .map_or(false, |name| name.starts_with(prefix))
+                    && !matches!(job.status.as_str(), "ready" | "failed" | "aborted")
+            })
+            .map(|job| {
+                let result = self.abort_job(job.id.clone());
+                (job.id, result)
+            })
+            .collect())
+    }
+
+    /// Check that the access token is accepted by the IQM server.
+    ///
+    /// Makes a lightweight authenticated request to the quantum-architecture endpoint of the
+    /// device, so that an invalid token is detected before submitting any job.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` - The access token is accepted.
+    /// * `Err(RoqoqoBackendError::MissingAuthentication)` - The access token was rejected.
+    /// * `Err(RoqoqoBackendError::NetworkError)` - Something went wrong with the request.
+    pub fn check_authentication(&self) -> Result<(), RoqoqoBackendError> {
+        let client = self.build_client()?;
+
+        let response = client
+            .get(self.quantum_architecture_url())
+            .headers(_construct_headers(&self.current_access_token()))
+            .send()
+            .map_err(|e| RoqoqoBackendError::NetworkError {
+                msg: format!("Error during GET request: {:?}", e),
+            })?;
+        match response.status() {
+            reqwest::StatusCode::UNAUTHORIZED | reqwest::StatusCode::FORBIDDEN => {
+                Err(RoqoqoBackendError::MissingAuthentication {
+                    msg: format!(
+                        "IQM access token was rejected with status code: {}",
+                        response.status()
+                    ),
+                })
+            }
+            status if status.is_success() => Ok(()),
+            status => Err(RoqoqoBackendError::NetworkError {
+                msg: format!("GET request failed with status code: {:?}", status),
+            }),
+        }
+    }
+
+    /// Check that the IQM server is reachable and measure the round-trip time of a request.
+    ///
+    /// Makes a lightweight GET request to the quantum-architecture endpoint of the device. The
+    /// server is considered reachable as long as it answers without a server error, so that the
+    /// check does not depend on the access token, see [Backend::check_authentication].
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Duration)` - The round-trip time of the request.
+    /// * `Err(RoqoqoBackendError::NetworkError)` - The server is unreachable or answered with a
+    ///   server error.
+    pub fn ping(&self) -> Result<Duration, RoqoqoBackendError> {
+        let client = self.build_client()?;
+
+        let start_time = Instant::now();
+        let response = client
+            .get(self.quantum_architecture_url())
+            .headers(_construct_headers(&self.current_access_token()))
+            .send()
+            .map_err(|e| RoqoqoBackendError::NetworkError {
+                msg: format!("Error during GET request: {:?}", e),
+            })?;
+        let round_trip_time = start_time.elapsed();
+        if response.status().is_server_error() {
+            return Err(RoqoqoBackendError::NetworkError {
+                msg: format!(
+                    "GET request failed with status code: {:?}",
+                    response.status()
+                ),
+            });
+        }
+        Ok(round_trip_time)
+    }
+
+    /// Returns the URL of the quantum-architecture endpoint of the device.
+    fn quantum_architecture_url(&self) -> String {
+        EndpointPaths::format(
+            &self.endpoint_paths.quantum_architecture,
+            &self.device.remote_host(),
+            "",
+        )
+    }
+
+    /// Returns the URL of the endpoint serving the metrics of the latest calibration of the device.
+    fn calibration_metrics_url(&self) -> String {
+        EndpointPaths::format(
+            &self.endpoint_paths.calibration_metrics,
+            &self.device.remote_host(),
+            "",
+        )
+    }
+
+    /// Query the fidelities of the native gates from the latest calibration of the device.
+    ///
+    /// The fidelities are read from the calibration metrics whose name ends with `.fidelity`,
+    /// keyed by the metric name without this suffix, e.g. `prx.QB1` for the `prx` gate on qubit
+    /// `QB1` or `cz.QB1__QB2` for the `cz` gate on the edge between `QB1` and `QB2`. Useful to
+    /// select the qubits with the best fidelities for a circuit.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(HashMap<String, f64>)` - The fidelity of each calibrated gate.
+    /// * `Err(RoqoqoBackendError)` - If something goes wrong with the HTTP request, or the response
+    ///   is not formatted correctly.
+    pub fn get_gate_fidelities(&self) -> Result<HashMap<String, f64>, RoqoqoBackendError> {
+        let client = self.build_client()?;
+
+        let response = client
+            .get(self.calibration_metrics_url())
+            .headers(_construct_headers(&self.current_access_token()))
+            .send()
+            .map_err(|e| RoqoqoBackendError::NetworkError {
+                msg: format!("Error during GET request: {:?}", e),
+            })?;
+
+        check_response_status(&response, &self.success_status_codes)?;
+
+        let calibration = response.json::<CalibrationMetrics>().map_err(|err| {
+            RoqoqoBackendError::NetworkError {
+                msg: format!("Error during deserialisation of GET response: {:?}", err),
+            }
+        })?;
+
+        let mut fidelities = HashMap::new();
+        for (metric, value) in calibration.metrics.into_iter() {
+            if let Some(gate) = metric.strip_suffix(".fidelity") {
+                let fidelity = match &value {
+                    serde_json::Value::Number(x) => x.as_f64(),
+                    serde_json::Value::String(x) => x.parse::<f64>().ok(),
+                    _ => None,
+                }
+                .ok_or_else(|| RoqoqoBackendError::GenericError {
+                    msg: format!("Invalid value {} of calibration metric {}", value, metric),
+                })?;
+                fidelities.insert(gate.to_string(), fidelity);
+            }
+        }
+        Ok(fidelities)
+    }
+
+    /// Get information about the quantum architecture of the given device.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(String)` - Information about the quantum architecture of the device.
+    /// * `Err(RoqoqoBackendError)` - Error response from IQM server.
+    pub fn get_quantum_architecture(&self) -> Result<String, RoqoqoBackendError> {
+        let endpoint_url = self.quantum_architecture_url();
+
+        let client = self.build_client()?;
+
+        let response = client
+            .get(endpoint_url)
+            .headers(_construct_headers(&self.current_access_token()))
+            .send()
+            .map_err(|e| RoqoqoBackendError::NetworkError {
+                msg: format!("Error during GET request: {:?}", e),
+            })?;
+        if response.status().is_success() {
+            response
+                .text()
+                .map_err(|e| RoqoqoBackendError::NetworkError {
+                    msg: format!("Error during GET request: {:?}", e),
+                })
+        } else {
+            Err(RoqoqoBackendError::NetworkError {
+                msg: format!(
+                    "GET request failed with status code: {:?}",
+                    response.status()
+                ),
+            })
+        }
+    }
+
+    /// Get the quantum architecture of the given device, parsed into a [QuantumArchitecture].
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(QuantumArchitecture)` - The quantum architecture of the device.
+    /// * `Err(RoqoqoBackendError)` - Error response from IQM server, or the response could not be
+    ///   parsed.
+    pub fn get_quantum_architecture_parsed(
+        &self,
+    ) -> Result<QuantumArchitecture, RoqoqoBackendError> {
+        QuantumArchitecture::from_response(&self.get_quantum_architecture()?)
+    }
+
+    /// Build a device from the quantum architecture of the given device.
+    ///
+    /// Uses the qubits, couplings and native operations of the live hardware, see
+    /// [QuantumArchitecture::to_generic_device].
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(GenericDevice)` - The device described by the quantum architecture.
+    /// * `Err(RoqoqoBackendError)` - Error response from IQM server, or the quantum architecture
+    ///   could not be parsed.
+    pub fn device_from_quantum_architecture(&self) -> Result<GenericDevice, RoqoqoBackendError> {
+        self.get_quantum_architecture_parsed()?.to_generic_device()
+    }
+
+    /// Returns the maximum depth of the native circuits accepted by the device.
+    ///
+    /// The limit is read from the quantum architecture of the device, see
+    /// [Backend::get_quantum_architecture_parsed].
+    ///
+    /// # Returns
+    ///
+    /// * `Some(usize)` - The maximum circuit depth.
+    /// * `None` - The device does not limit the circuit depth, or the quantum architecture could
+    ///   not be fetched.
+    pub fn max_circuit_depth(&self) -> Option<usize> {
+        self.get_quantum_architecture_parsed()
+            .ok()
+            .and_then(|architecture| architecture.max_circuit_depth)
+    }
+
+    /// Validate the circuits run for a measurement before submitting them.
+    ///
+    /// Each circuit of the measurement is validated with [Backend::validate_circuit] after prepending
+    /// the constant circuit, as it is submitted by the backend. The returned error names the index
+    /// of the failing circuit, so that the offending component of the measurement can be found.
+    ///
+    /// # Arguments
+    ///
+    /// * `measurement` - The measurement to validate.
+    ///
+    /// # Returns
+    ///
+    /// * `Err(IqmBackendError::InvalidCircuit)` - A circuit of the measurement is invalid.
+    pub fn validate_measurement<T: Measure>(&self, measurement: &T) -> Result<(), IqmBackendError> {
+        let constant_circuit = measurement.constant_circuit().clone().unwrap_or_default();
+        let circuits: Vec<Circuit> = measurement
+            .circuits()
+            .map(|circuit| constant_circuit.clone() + circuit.clone())
+            .collect();
+        self.validate_measurement_circuits(&circuits)
+    }
+
+    /// Validate the circuits run for a measurement, naming the index of the failing circuit.
+    ///
+    /// The constant circuit of the measurement has to be prepended to the circuits already.
+    ///
+    /// # Arguments
+    ///
+    /// * `circuits` - The circuits of the measurement.
+    ///
+    /// # Returns
+    ///
+    /// * `Err(IqmBackendError::InvalidCircuit)` - A circuit of the measurement is invalid.
+    pub fn validate_measurement_circuits(
+        &self,
+        circuits: &[Circuit],
+    ) -> Result<(), IqmBackendError> {
+        for (index, circuit) in circuits.iter().enumerate() {
+            self.validate_circuit(circuit)
+                .map_err(|err| IqmBackendError::InvalidCircuit {
+                    msg: format!("Circuit {} of the measurement is invalid: {}", index, err),
+                })?;
+        }
+        Ok(())
+    }
+
+    /// Validate the batch of circuits to submit by checking that they all write to different output registers.
+    ///
+    /// Before validating the individual circuits, the qubits used by the whole batch are checked
+    /// against the device, so that a batch is rejected early with all the circuits acting on more
+    /// qubits than the device has. The output registers are only checked with
+    /// [ValidationLevel::Full] and nothing is checked with [ValidationLevel::Off].
+    ///
+    /// # Arguments
+    ///
+    /// * `circuit_batch` - The batch of circuits to validate.
+    ///
+    /// # Returns
+    ///
+    /// * `Err(IqmBackendError)` - The batch is invalid.
+    pub fn validate_circuit_batch(&self, circuit_batch: &[Circuit]) -> Result<(), IqmBackendError> {
+        if self.validation_level == ValidationLevel::Off {
+            return Ok(());
+        }
+        let device_number_qubits = self.device.number_qubits();
+        let circuit_number_qubits: Vec<usize> = circuit_batch
+            .iter()
+            .map(|circuit| _get_number_qubits(circuit).unwrap_or_default())
+            .collect();
+        let oversized: Vec<String> = circuit_number_qubits
+            .iter()
+            .enumerate()
+            .filter(|(_, number_qubits)| **number_qubits > device_number_qubits)
+            .map(|(index, number_qubits)| format!("{} ({} qubits)", index, number_qubits))
+            .collect();
+        if !oversized.is_empty() {
+            return Err(IqmBackendError::InvalidCircuit {
+                msg: format!(
+                    "The batch acts on up to {} qubits, but the device has only {} qubits. \
+                     Circuits exceeding the device: {}.",
+                    circuit_number_qubits.iter().max().unwrap_or(&0),
+                    device_number_qubits,
+                    oversized.join(", ")
+                ),
+            });
+        }
+
+        if self.validation_level != ValidationLevel::Full {
+            return circuit_batch
+                .iter()
+                .try_for_each(|circuit| self.validate_circuit(circuit));
+        }
+
+        let mut output_registers = HashSet::new();
         for circuit in circuit_batch.iter() {
             self.validate_circuit(circuit)?;
             let mut found_reg = false;
@@ -576,6 +3022,66 @@ impl Backend {
         Ok(())
     }
 
+    /// Validate each circuit of a batch separately.
+    ///
+    /// Unlike [Backend::validate_circuit_batch], the validation does not stop at the first invalid
+    /// circuit. With [ValidationLevel::Full], a circuit is also invalid if it does not write to an
+    /// output register or shares an output register with another circuit of the batch.
+    ///
+    /// # Arguments
+    ///
+    /// * `circuit_batch` - The batch of circuits to validate.
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<Result<(), IqmBackendError>>` - The validation result of each circuit of the batch.
+    pub fn validate_circuit_batch_detailed(
+        &self,
+        circuit_batch: &[Circuit],
+    ) -> Vec<Result<(), IqmBackendError>> {
+        let output_registers: Vec<Vec<String>> = circuit_batch
+            .iter()
+            .map(|circuit| {
+                circuit
+                    .iter()
+                    .filter_map(|op| match op {
+                        Operation::DefinitionBit(o) if *o.is_output() => Some(o.name().clone()),
+                        _ => None,
+                    })
+                    .collect()
+            })
+            .collect();
+        let mut register_counts: HashMap<&String, usize> = HashMap::new();
+        for name in output_registers.iter().flatten() {
+            *register_counts.entry(name).or_insert(0) += 1;
+        }
+
+        circuit_batch
+            .iter()
+            .zip(output_registers.iter())
+            .map(|(circuit, registers)| {
+                self.validate_circuit(circuit)?;
+                if self.validation_level != ValidationLevel::Full {
+                    return Ok(());
+                }
+                if registers.is_empty() {
+                    return Err(IqmBackendError::InvalidCircuit {
+                        msg: "Circuits need to write to at least one output register.".to_string(),
+                    });
+                }
+                if let Some(name) = registers.iter().find(|name| register_counts[name] > 1) {
+                    return Err(IqmBackendError::InvalidCircuit {
+                        msg: format!(
+                            "The output register {} is written to by another circuit of the batch.",
+                            name
+                        ),
+                    });
+                }
+                Ok(())
+            })
+            .collect()
+    }
+
     /// Submit a circuit batch to be executed on the IQM platform.
     ///
     /// # Arguments
@@ -585,23 +3091,118 @@ impl Backend {
     /// # Returns
     ///
     /// * `Ok(String)` - The ID of the submitted job.
+    /// * `Err(IqmBackendError::EmptyCircuit)` - A circuit does not contain any gate.
     /// * `Err(RoqoqoBackendError::NetworkError)` - Something went wrong when submitting the job.
     pub fn submit_circuit_batch(
         &self,
         circuit_batch: &[Circuit],
     ) -> Result<String, IqmBackendError> {
+        self._submit_circuit_batch(circuit_batch, self.number_measurements_internal)
+    }
+
+    /// Validate and submit a circuit batch, classifying the failures by their remedy.
+    ///
+    /// All circuits are validated with [Backend::validate_circuit_batch_detailed] before the
+    /// batch is converted and submitted, so that the errors of all invalid circuits are returned
+    /// at once.
+    ///
+    /// # Arguments
+    ///
+    /// * `circuit_batch` - The circuits to be submitted.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(String)` - The ID of the submitted job.
+    /// * `Err(SubmitError::Validation)` - The circuits are invalid, or the server rejected them.
+    /// * `Err(SubmitError::Network)` - Something went wrong with the request to the server.
+    pub fn submit_checked(&self, circuit_batch: &[Circuit]) -> Result<String, SubmitError> {
+        let validation_errors: Vec<IqmBackendError> = self
+            .validate_circuit_batch_detailed(circuit_batch)
+            .into_iter()
+            .filter_map(Result::err)
+            .collect();
+        if !validation_errors.is_empty() {
+            return Err(SubmitError::Validation(validation_errors));
+        }
+        self.submit_circuit_batch(circuit_batch)
+            .map_err(|err| match err {
+                IqmBackendError::RoqoqoBackendError(err) => SubmitError::Network(err),
+                err => SubmitError::Validation(vec![err]),
+            })
+    }
+
+    /// Submit a circuit batch, overwriting the number of measurements if
+    /// `number_measurements_internal` is set.
+    fn _submit_circuit_batch(
+        &self,
+        circuit_batch: &[Circuit],
+        number_measurements_internal: Option<usize>,
+    ) -> Result<String, IqmBackendError> {
+        let data = self._build_run_request(circuit_batch, number_measurements_internal)?;
+        self._post_run_request(&data)
+    }
+
+    /// Validate and convert a circuit batch into a run request, overwriting the number of
+    /// measurements if `number_measurements_internal` is set.
+    fn _build_run_request(
+        &self,
+        circuit_batch: &[Circuit],
+        number_measurements_internal: Option<usize>,
+    ) -> Result<IqmRunRequest, IqmBackendError> {
+        self.submission_warnings.set(None);
         self.validate_circuit_batch(circuit_batch)?;
 
         let mut circuits = vec![];
         let mut number_measurements_set = HashSet::new();
+        let mut circuit_names = HashSet::new();
 
         for (circuit_index, circuit) in circuit_batch.iter().enumerate() {
-            let (iqm_circuit, number_measurements) = call_circuit(
+            let circuit = self.decompose_circuit(circuit)?;
+            let (mut iqm_circuit, number_measurements) = call_circuit(
                 circuit.iter(),
-                self.device.number_qubits(),
-                self.number_measurements_internal,
+                self.repeated_measurement_qubits(&circuit),
+                self.overwritten_number_measurements(&circuit, number_measurements_internal),
                 circuit_index,
             )?;
+            // Circuits without gates, e.g. consisting only of pragmas and measurements, are
+            // rejected by the server
+            if !iqm_circuit
+                .instructions
+                .iter()
+                .any(|instruction| instruction.name != "measure" && instruction.name != "barrier")
+            {
+                return Err(IqmBackendError::EmptyCircuit);
+            }
+            if let Some((name, CalculatorFloat::Str(expression))) = iqm_circuit
+                .instructions
+                .iter()
+                .filter(|instruction| instruction.name != "measure")
+                .flat_map(|instruction| instruction.args.iter())
+                .find(|(_, value)| !value.is_float())
+            {
+                return Err(IqmBackendError::InvalidCircuit {
+                    msg: format!(
+                        "Circuit {} contains the symbolic parameter {} = {}. Substitute the \
+                         symbolic parameters before submission, e.g. with \
+                         Backend::run_parameter_sweep.",
+                        circuit_index, name, expression
+                    ),
+                });
+            }
+            iqm_circuit.name = sanitize_circuit_name(&format!(
+                "{}{}",
+                self.circuit_name_prefix.as_deref().unwrap_or_default(),
+                iqm_circuit.name
+            ));
+            if !circuit_names.insert(iqm_circuit.name.clone()) {
+                return Err(IqmBackendError::InvalidCircuit {
+                    msg: format!(
+                        "Several circuits of the batch are submitted with the name {} after \
+                         sanitization. Use a shorter circuit name prefix.",
+                        iqm_circuit.name
+                    ),
+                });
+            }
             circuits.push(iqm_circuit);
             number_measurements_set.insert(number_measurements);
         }
@@ -619,69 +3220,498 @@ impl Backend {
             .into_iter()
             .next()
             .expect("Number measurements set is unexpectedly empty.");
+        self.check_qubit_mapping(&circuits)?;
+
+        Ok(IqmRunRequest {
+            circuits,
+            shots: self.submitted_shots(number_measurements) as u16,
+            custom_settings: self.custom_settings(),
+            calibration_set_id: self.calibration_set_id.clone(),
+            qubit_mapping: self.qubit_mapping.clone(),
+            circuit_duration_check: self.circuit_duration_check,
+            heralding_mode: self.heralding_mode,
+            priority: self.job_priority.clone(),
+        })
+    }
+
+    /// Compile a circuit batch on the server without executing it.
+    ///
+    /// The circuits are converted as for [Backend::submit_circuit_batch] and sent with the
+    /// `compile_only` flag. Useful to inspect how the circuits are mapped to pulses by the server.
+    ///
+    /// # Arguments
+    ///
+    /// * `circuit_batch` - The list of circuits to be compiled.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(String)` - The compiled representation returned by the server, as JSON.
+    /// * `Err(RoqoqoBackendError)` - The circuits are invalid, or something goes wrong with the
+    ///   HTTP request.
+    pub fn compile_on_server(
+        &self,
+        circuit_batch: &[Circuit],
+    ) -> Result<String, RoqoqoBackendError> {
+        let data = self
+            ._build_run_request(circuit_batch, self.number_measurements_internal)
+            .map_err(|err| match err {
+                IqmBackendError::RoqoqoBackendError(err) => err,
+                _ => RoqoqoBackendError::GenericError {
+                    msg: err.to_string(),
+                },
+            })?;
+
+        let body = self.request_format.encode(&data)?;
+        let client = self.build_client()?;
+
+        let response = client
+            .post(self.device.remote_host())
+            .query(&[("compile_only", true)])
+            .headers(_construct_headers(&self.current_access_token()))
+            .header(CONTENT_TYPE, self.request_format.content_type())
+            .body(body)
+            .send()
+            .map_err(|err| RoqoqoBackendError::NetworkError {
+                msg: format!("Error during POST request: {:?}", err),
+            })?;
+
+        check_response_status(&response, &self.success_status_codes)?;
+
+        response
+            .text()
+            .map_err(|err| RoqoqoBackendError::NetworkError {
+                msg: format!("Error during POST request: {:?}", err),
+            })
+    }
+
+    /// Submit circuits given as native IQM instructions for asynchronous execution.
+    ///
+    /// The circuits are submitted as they are, without the conversion, decomposition and
+    /// validation applied to roqoqo circuits. The results can only be converted into registers
+    /// with [results_to_registers] if the circuits carry the measured qubits in their `metadata`.
+    ///
+    /// # Arguments
+    ///
+    /// * `circuits` - The circuits to submit.
+    /// * `shots` - The number of shots of each circuit.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(String)` - The ID of the submitted job.
+    /// * `Err(IqmBackendError::InvalidCircuit)` - A circuit has no `measure` instruction, or the
+    ///   number of shots is too large.
+    /// * `Err(IqmBackendError)` - Transparent propagation of errors from the request.
+    pub fn submit_iqm_circuits(
+        &self,
+        circuits: Vec<IqmCircuit>,
+        shots: usize,
+    ) -> Result<String, IqmBackendError> {
+        self.submission_warnings.set(None);
+        if circuits.is_empty() {
+            return Err(IqmBackendError::EmptyCircuit);
+        }
+        if let Some(circuit) = circuits
+            .iter()
+            .find(|circuit| !circuit.instructions.iter().any(|x| x.name == "measure"))
+        {
+            return Err(IqmBackendError::InvalidCircuit {
+                msg: format!(
+                    "Circuit {} does not contain a measure instruction.",
+                    circuit.name
+                ),
+            });
+        }
+        self.check_qubit_mapping(&circuits)?;
+        let shots = self.submitted_shots(shots);
+        let shots = u16::try_from(shots).map_err(|_| IqmBackendError::InvalidCircuit {
+            msg: format!(
+                "Number of shots {} exceeds the maximum of {}.",
+                shots,
+                u16::MAX
+            ),
+        })?;
 
         let data = IqmRunRequest {
             circuits,
-            shots: number_measurements as u16,
-            custom_settings: None,
-            calibration_set_id: None,
-            qubit_mapping: None,
-            circuit_duration_check: false,
-            heralding_mode: HeraldingMode::None,
+            shots,
+            custom_settings: self.custom_settings(),
+            calibration_set_id: self.calibration_set_id.clone(),
+            qubit_mapping: self.qubit_mapping.clone(),
+            circuit_duration_check: self.circuit_duration_check,
+            heralding_mode: self.heralding_mode,
+            priority: self.job_priority.clone(),
+        };
+
+        self._post_run_request(&data)
+    }
+
+    /// Resubmit the circuits of a batch job that failed on the server.
+    ///
+    /// Only the failed circuits are resubmitted, with the same settings as the original job, which
+    /// avoids re-running a whole batch because of a single bad circuit. Requires a server that
+    /// reports the status of the individual circuits of a batch.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The ID of the job with failed circuits.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(String)` - The ID of the job running the failed circuits.
+    /// * `Err(IqmBackendError::InvalidResults)` - The job has no failed circuits.
+    /// * `Err(IqmBackendError)` - Transparent propagation of errors from the requests.
+    pub fn rerun_failed_circuits(&self, id: String) -> Result<String, IqmBackendError> {
+        self.submission_warnings.set(None);
+        let iqm_result = self.get_results(id.clone())?;
+        let failed_circuits = iqm_result.failed_circuits();
+        if failed_circuits.is_empty() {
+            return Err(IqmBackendError::InvalidResults {
+                msg: format!("Job {} has no failed circuits to resubmit.", id),
+            });
+        }
+
+        let request = iqm_result.metadata.request;
+        let data = IqmRunRequest {
+            circuits: request
+                .circuits
+                .into_iter()
+                .enumerate()
+                .filter(|(index, _)| failed_circuits.contains(index))
+                .map(|(_, circuit)| circuit)
+                .collect(),
+            ..request
         };
 
-        let client = reqwest::blocking::Client::builder()
-            .https_only(true)
-            .build()
-            .map_err(|x| {
-                IqmBackendError::RoqoqoBackendError(RoqoqoBackendError::NetworkError {
-                    msg: format!("Could not create HTTPS client: {:?}", x),
+        self._post_run_request(&data)
+    }
+
+    /// Send a run request to the server and return the ID of the created job.
+    fn _post_run_request(&self, data: &IqmRunRequest) -> Result<String, IqmBackendError> {
+        if let Some(warning) = self.min_shots_warning(data.shots as usize) {
+            self.warn_submission(warning);
+        }
+        let client = self.build_client()?;
+
+        let idempotency_header = self.idempotency_header()?;
+        let body = self.request_format.encode(data)?;
+
+        let response = self.send_authenticated(|token| {
+            let headers = self.run_request_headers(token, &idempotency_header);
+            self.send_with_retries(|| {
+                client
+                    .post(self.device.remote_host())
+                    .headers(headers.clone())
+                    .body(body.clone())
+            })
+        })?;
+
+        let status = response.status();
+        let text = response
+            .text()
+            .map_err(|err| RoqoqoBackendError::NetworkError {
+                msg: format!("Error during POST request: {:?}", err),
+            })?;
+        self._job_id_from_response(data, status, &text)
+    }
+
+    /// Returns the Idempotency-Key header of a job submission, see [Backend::set_idempotency_key].
+    ///
+    /// The same key has to be sent with all the attempts of a submission.
+    fn idempotency_header(&self) -> Result<HeaderValue, IqmBackendError> {
+        let idempotency_key = self
+            .idempotency_key
+            .clone()
+            .unwrap_or_else(_generate_idempotency_key);
+        HeaderValue::from_str(&idempotency_key).map_err(|err| IqmBackendError::InvalidCircuit {
+            msg: format!("Invalid idempotency key {}: {}", idempotency_key, err),
+        })
+    }
+
+    /// Returns the headers of a run request.
+    fn run_request_headers(&self, token: &str, idempotency_header: &HeaderValue) -> HeaderMap {
+        let mut headers = _construct_headers(token);
+        headers.insert("Idempotency-Key", idempotency_header.clone());
+        headers.insert(
+            CONTENT_TYPE,
+            HeaderValue::from_static(self.request_format.content_type()),
+        );
+        headers
+    }
+
+    /// Extract the ID of the created job from the response to a run request.
+    fn _job_id_from_response(
+        &self,
+        data: &IqmRunRequest,
+        status: reqwest::StatusCode,
+        text: &str,
+    ) -> Result<String, IqmBackendError> {
+        if data.circuit_duration_check && status.is_client_error() {
+            return match serde_json::from_str::<DetailResponse>(text) {
+                Ok(DetailResponse { detail }) if detail.to_lowercase().contains("duration") => {
+                    Err(IqmBackendError::JobFailed {
+                        id: String::new(),
+                        msg: detail,
+                    })
+                }
+                _ => Err(IqmBackendError::RoqoqoBackendError(
+                    RoqoqoBackendError::NetworkError {
+                        msg: format!(
+                            "Received an error response with HTTP status code: {}\n{}",
+                            status, text
+                        ),
+                    },
+                )),
+            };
+        }
+
+        check_status_code(status, &self.success_status_codes).map_err(|err| {
+            IqmBackendError::RoqoqoBackendError(RoqoqoBackendError::NetworkError {
+                msg: format!("Received an invalid response: {:?}", err),
+            })
+        })?;
+
+        let job_id = serde_json::from_str::<ResponseBody>(text)
+            .expect("Something went wrong when deserializing the response to get the job ID.")
+            .id
+            .to_string();
+
+        Ok(job_id)
+    }
+
+    /// Run a list of circuits on the backend and wait for results.
+    ///
+    /// # Arguments
+    ///
+    /// * `circuit_batch` - The list of circuits to be run.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(Registers)` - The bit, float and complex registers containing the results.
+    /// `Err(RoqoqoBackendError)` - Transparent propagation of errors.
+    pub fn run_circuit_batch(
+        &self,
+        circuit_batch: &[Circuit],
+    ) -> Result<Registers, IqmBackendError> {
+        self.run_circuit_batch_with_warnings(circuit_batch)
+            .map(|(registers, _)| registers)
+    }
+
+    /// Run a list of circuits on the backend and also return the warnings of the IQM device.
+    ///
+    /// Runs the batch in the same way as [Backend::run_circuit_batch], but additionally returns the
+    /// warnings attached to the results by the IQM device (e.g. stale calibration).
+    ///
+    /// # Arguments
+    ///
+    /// * `circuit_batch` - The list of circuits to be run.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok((Registers, Vec<String>))` - The output registers and the warnings of the device.
+    /// * `Err(IqmBackendError)` - Something went wrong when running the batch.
+    pub fn run_circuit_batch_with_warnings(
+        &self,
+        circuit_batch: &[Circuit],
+    ) -> Result<(Registers, Vec<String>), IqmBackendError> {
+        let id = self.submit_circuit_batch(circuit_batch)?;
+        let results = self.wait_for_results(id.clone())?;
+        let warnings = results.warnings();
+
+        Ok((results_to_registers(results, id)?, warnings))
+    }
+
+    /// Measure the expectation values of products of Pauli operators on the state prepared by a
+    /// circuit.
+    ///
+    /// For each term, a copy of `base_circuit` followed by the measurements of the qubits of the
+    /// term in the corresponding bases is built with [crate::add_basis_measurement]. All the
+    /// circuits are submitted as a single batch, so that they share the same number of shots. The
+    /// expectation value of a term is the average over the shots of the parity of its measured
+    /// qubits, a term without qubits has the expectation value 1.
+    ///
+    /// # Arguments
+    ///
+    /// * `base_circuit` - The circuit preparing the state, without measurements.
+    /// * `pauli_terms` - The qubits and Pauli bases of each product, keyed by the name of the term.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(HashMap<String, f64>)` - The expectation value of each term.
+    /// * `Err(IqmBackendError)` - Something went wrong when running the batch.
+    pub fn run_pauli_measurement(
+        &self,
+        base_circuit: &Circuit,
+        pauli_terms: &HashMap<String, Vec<(usize, PauliBasis)>>,
+    ) -> Result<HashMap<String, f64>, IqmBackendError> {
+        let mut names: Vec<&String> = pauli_terms.keys().collect();
+        names.sort();
+
+        let mut expectation_values = HashMap::new();
+        let mut circuit_batch = vec![];
+        let mut term_registers = vec![];
+        for name in names {
+            let term = &pauli_terms[name];
+            if term.is_empty() {
+                expectation_values.insert(name.clone(), 1.0);
+                continue;
+            }
+            // Circuits of a batch cannot write to the same register
+            let register = format!("pauli_{}", circuit_batch.len());
+            let mut circuit = base_circuit.clone();
+            circuit += DefinitionBit::new(register.clone(), term.len(), true);
+            for (index, (qubit, basis)) in term.iter().enumerate() {
+                add_basis_measurement(&mut circuit, *qubit, *basis, &register, index);
+            }
+            circuit_batch.push(circuit);
+            term_registers.push((name.clone(), register));
+        }
+        if circuit_batch.is_empty() {
+            return Ok(expectation_values);
+        }
+
+        let (bit_registers, _, _) = self.run_circuit_batch(&circuit_batch)?;
+        for (name, register) in term_registers {
+            let shots = bit_registers
+                .get(&register)
+                .filter(|shots| !shots.is_empty())
+                .ok_or_else(|| IqmBackendError::InvalidResults {
+                    msg: format!("No results were returned for the Pauli term {}.", name),
+                })?;
+            let parity_sum: f64 = shots
+                .iter()
+                .map(|bits| {
+                    if bits.iter().filter(|bit| **bit).count() % 2 == 0 {
+                        1.0
+                    } else {
+                        -1.0
+                    }
                 })
-            })?;
+                .sum();
+            expectation_values.insert(name, parity_sum / shots.len() as f64);
+        }
+        Ok(expectation_values)
+    }
 
-        let response = client
-            .post(self.device.remote_host())
-            .headers(_construct_headers(&self.access_token))
-            .json(&data)
-            .send()
-            .map_err(|err| {
-                IqmBackendError::RoqoqoBackendError(RoqoqoBackendError::NetworkError {
-                    msg: format!("Error during POST request: {:?}", err),
+    /// Run a circuit with symbolic parameters for several sets of parameter values.
+    ///
+    /// The circuit is expanded client-side into one circuit per parameter set with
+    /// [expand_parameter_sweep], and all circuits are submitted as a single batch. Since the
+    /// circuits of a batch cannot write to the same registers, the registers are renamed for the
+    /// submission and returned under their original names. Useful for
+    /// sweeps of the amplitudes and phases of the `prx` gates of hardware-efficient ansätze.
+    ///
+    /// # Arguments
+    ///
+    /// * `circuit` - The circuit with symbolic parameters.
+    /// * `parameter_sets` - The value of each symbolic parameter, for each circuit of the sweep.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Vec<Registers>)` - The output registers of each parameter set, in the order of the
+    ///   parameter sets.
+    /// * `Err(IqmBackendError)` - A parameter set does not define all parameters, or something went
+    ///   wrong when running the batch.
+    pub fn run_parameter_sweep(
+        &self,
+        circuit: &Circuit,
+        parameter_sets: &[HashMap<String, f64>],
+    ) -> Result<Vec<Registers>, IqmBackendError> {
+        let circuit_batch: Vec<Circuit> = expand_parameter_sweep(circuit, parameter_sets)?
+            .iter()
+            .enumerate()
+            .map(|(index, circuit)| _rename_registers(circuit, &_sweep_register_suffix(index)))
+            .collect();
+        if circuit_batch.is_empty() {
+            return Ok(vec![]);
+        }
+        let id = self.submit_circuit_batch(&circuit_batch)?;
+        let results = self.wait_for_results(id.clone())?;
+        let mut registers = _results_to_registers_per_circuit(results, id)?;
+        for (index, (bit_registers, _, _)) in registers.iter_mut().enumerate() {
+            let suffix = _sweep_register_suffix(index);
+            *bit_registers = bit_registers
+                .drain()
+                .map(|(name, register)| match name.strip_suffix(&suffix) {
+                    Some(original_name) => (original_name.to_string(), register),
+                    None => (name, register),
                 })
-            })?;
-
-        check_response_status(&response).map_err(|err| {
-            IqmBackendError::RoqoqoBackendError(RoqoqoBackendError::NetworkError {
-                msg: format!("Received an invalid response: {:?}", err),
-            })
-        })?;
+                .collect();
+        }
+        Ok(registers)
+    }
 
-        let job_id = serde_json::from_str::<ResponseBody>(&response.text().unwrap())
-            .expect("Something went wrong when deserializing the response to get the job ID.")
-            .id
-            .to_string();
+    /// Run a circuit on the backend and measure the time spent in each stage of the run.
+    ///
+    /// Useful to compare the overhead of the client with the time spent on the server.
+    ///
+    /// # Arguments
+    ///
+    /// * `circuit` - The circuit to be run.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok((Registers, TimingBreakdown))` - The output registers and the time spent submitting
+    ///   the circuit, waiting for the results and processing them.
+    /// * `Err(IqmBackendError)` - Something went wrong when running the circuit.
+    pub fn run_circuit_timed(
+        &self,
+        circuit: &Circuit,
+    ) -> Result<(Registers, TimingBreakdown), IqmBackendError> {
+        let start = Instant::now();
+        let id = self.submit_circuit_batch(std::slice::from_ref(circuit))?;
+        let submitted = Instant::now();
+        let results = self.wait_for_results(id.clone())?;
+        let received = Instant::now();
+        let registers = results_to_registers(results, id)?;
 
-        Ok(job_id)
+        let timing = TimingBreakdown {
+            submit: submitted - start,
+            wait: received - submitted,
+            processing: received.elapsed(),
+        };
+        Ok((registers, timing))
     }
 
-    /// Run a list of circuits on the backend and wait for results.
+    /// Run a circuit for a total number of shots that can exceed the maximum number of shots per
+    /// job of the device.
+    ///
+    /// The shots are split into as many jobs as needed, each with at most
+    /// [IqmDevice::max_shots] shots. The jobs are run one after the other and the shots of all
+    /// jobs are concatenated in the output registers.
     ///
     /// # Arguments
     ///
-    /// * `circuit_batch` - The list of circuits to be run.
+    /// * `circuit` - The circuit to be run.
+    /// * `total_shots` - The total number of shots.
     ///
     /// # Returns
     ///
-    /// `Ok(Registers)` - The bit, float and complex registers containing the results.
-    /// `Err(RoqoqoBackendError)` - Transparent propagation of errors.
-    pub fn run_circuit_batch(
+    /// `Ok(Registers)` - The bit, float and complex registers containing the results of all jobs.
+    /// `Err(IqmBackendError)` - Transparent propagation of errors.
+    pub fn run_circuit_accumulate(
         &self,
-        circuit_batch: &[Circuit],
+        circuit: &Circuit,
+        total_shots: usize,
     ) -> Result<Registers, IqmBackendError> {
-        let id = self.submit_circuit_batch(circuit_batch)?;
-        let results = self.wait_for_results(id.clone())?;
+        let mut bit_registers: HashMap<String, BitOutputRegister> = HashMap::new();
+        let mut float_registers: HashMap<String, FloatOutputRegister> = HashMap::new();
+        let mut complex_registers: HashMap<String, ComplexOutputRegister> = HashMap::new();
 
-        results_to_registers(results, id)
+        for shots in _split_shots(total_shots, self.device.max_shots()) {
+            let id = self._submit_circuit_batch(std::slice::from_ref(circuit), Some(shots))?;
+            let results = self.wait_for_results(id.clone())?;
+            let (bits, floats, complexes) = results_to_registers(results, id)?;
+            for (name, register) in bits {
+                bit_registers.entry(name).or_default().extend(register);
+            }
+            for (name, register) in floats {
+                float_registers.entry(name).or_default().extend(register);
+            }
+            for (name, register) in complexes {
+                complex_registers.entry(name).or_default().extend(register);
+            }
+        }
+        Ok((bit_registers, float_registers, complex_registers))
     }
 }
 
@@ -705,64 +3735,543 @@ impl EvaluatingBackend for Backend {
     }
 }
 
+/// Returns the depth of a native circuit, i.e. the number of layers of instructions acting on
+/// disjoint qubits. Barriers synchronize their qubits without adding a layer.
+fn _native_circuit_depth(iqm_circuit: &IqmCircuit) -> usize {
+    let mut layers: HashMap<&String, usize> = HashMap::new();
+    for instruction in iqm_circuit.instructions.iter() {
+        let layer = instruction
+            .qubits
+            .iter()
+            .map(|qubit| layers.get(qubit).copied().unwrap_or_default())
+            .max()
+            .unwrap_or_default();
+        let layer = if instruction.name == "barrier" {
+            layer
+        } else {
+            layer + 1
+        };
+        for qubit in instruction.qubits.iter() {
+            layers.insert(qubit, layer);
+        }
+    }
+    layers.into_values().max().unwrap_or_default()
+}
+
+/// Returns the number of measurements defined by the pragmas of a circuit, if any.
+fn _circuit_number_measurements(circuit: &Circuit) -> Option<usize> {
+    circuit.iter().find_map(|op| match op {
+        Operation::PragmaSetNumberOfMeasurements(o) => Some(*o.number_measurements()),
+        Operation::PragmaRepeatedMeasurement(o) => Some(*o.number_measurements()),
+        _ => None,
+    })
+}
+
+/// Split a number of shots into chunks of at most `max_shots` shots.
+fn _split_shots(total_shots: usize, max_shots: usize) -> Vec<usize> {
+    let max_shots = max_shots.max(1);
+    let mut chunks = vec![max_shots; total_shots / max_shots];
+    if total_shots % max_shots != 0 {
+        chunks.push(total_shots % max_shots);
+    }
+    chunks
+}
+
+/// Status codes treated as success by default: 200 (OK), 201 (Created) and 202 (Accepted).
+fn default_success_status_codes() -> Vec<u16> {
+    vec![200, 201, 202]
+}
+
+fn default_timeout_secs() -> f64 {
+    TIMEOUT_SECS
+}
+
+fn default_polling_interval_secs() -> f64 {
+    SECONDS_BETWEEN_CALLS
+}
+
+fn default_print_warnings() -> bool {
+    true
+}
+
 /// Checks the status of the endpoint response after submission.
-fn check_response_status(response: &Response) -> Result<(), RoqoqoBackendError> {
-    let status = response.status();
-    match status {
-        reqwest::StatusCode::OK => (),
-        reqwest::StatusCode::CREATED => (),
-        reqwest::StatusCode::ACCEPTED => (),
-        _ => {
-            return Err(RoqoqoBackendError::NetworkError {
-                msg: format!(
-                    "Received an error response with HTTP status code: {}",
-                    status
-                ),
-            });
+fn check_response_status(
+    response: &Response,
+    success_status_codes: &[u16],
+) -> Result<(), RoqoqoBackendError> {
+    check_status_code(response.status(), success_status_codes)
+}
+
+/// Check that an HTTP status code is one of the status codes treated as success.
+fn check_status_code(
+    status: reqwest::StatusCode,
+    success_status_codes: &[u16],
+) -> Result<(), RoqoqoBackendError> {
+    if !success_status_codes.contains(&status.as_u16()) {
+        return Err(RoqoqoBackendError::NetworkError {
+            msg: format!(
+                "Received an error response with HTTP status code: {}",
+                status
+            ),
+        });
+    }
+    Ok(())
+}
+
+/// Construct the error for a job whose abortion was refused, from the body of the response.
+///
+/// Uses the detail field of the body if it is JSON and falls back to the raw body otherwise.
+fn _abort_failed_error(id: String, text: &str) -> IqmBackendError {
+    let msg = match serde_json::from_str::<DetailResponse>(text) {
+        Ok(response) => response.detail,
+        Err(_) => text.to_string(),
+    };
+    IqmBackendError::JobAbortionFailed { id, msg }
+}
+
+/// Construct the error for a failed job, parsing the message as structured error information if
+/// possible and falling back to the raw message otherwise.
+fn _job_failed_error(id: String, msg: String) -> IqmBackendError {
+    match serde_json::from_str::<IqmErrorDetail>(&msg) {
+        Ok(detail) => IqmBackendError::JobFailedWithDetails { id, detail },
+        Err(_) => IqmBackendError::JobFailed { id, msg },
+    }
+}
+
+#[inline]
+/// Parse the IqmRunResult received to create the MeasuredQubitsMap, which is needed to process the
+/// results by specifying which qubits have been measured for each register. The qubits measured in
+/// each circuit are stored in the `metadata` field of each circuit submitted, and a copy of the
+/// `IqmRunRequest` is contained in the `metadata` field of the `IqmRunResult`.
+fn get_measured_qubits_map(results: &IqmRunResult) -> Result<MeasuredQubitsMap, IqmBackendError> {
+    let mut measured_qubits_map = HashMap::new();
+    let circuits = &results.metadata.request.circuits;
+    for circuit in circuits.iter() {
+        let tmp_measured_qubits_map =
+            circuit
+                .metadata
+                .clone()
+                .ok_or(IqmBackendError::MetadataError {
+                    msg: "Missing metadata field in the copy of IqmRequest returned with the \
+                          results."
+                        .to_string(),
+                })?;
+        for (tmp_reg, tmp_map) in tmp_measured_qubits_map.iter() {
+            match measured_qubits_map.get(tmp_reg) {
+                Some(_) => {
+                    return Err(IqmBackendError::MetadataError {
+                        msg:
+                            "Measured qubits maps from different circuits contain entries for the \
+                          same register."
+                                .to_string(),
+                    })
+                }
+                None => {
+                    measured_qubits_map.insert(tmp_reg.clone(), tmp_map.clone());
+                }
+            }
+        }
+    }
+    Ok(measured_qubits_map)
+}
+
+/// Match the measurement results of a batch to the circuits of the copy of the request returned
+/// with the results.
+///
+/// The results are expected in submission order, but each result is matched to a circuit by its
+/// measurement keys, so that results reordered by the server are still assigned to the circuit
+/// they belong to. A result is kept at its position if the circuit submitted at that position
+/// measures all of its keys, and is otherwise matched to the single remaining circuit that does.
+/// Results without measurements are matched last, to any remaining circuit.
+///
+/// # Returns
+///
+/// `Ok(Vec<(usize, CircuitResult)>)` - The results with the index of their circuit in the batch,
+/// sorted by circuit index.
+/// `Err(IqmBackendError::InvalidResults)` - A result matches no circuit, or several circuits.
+fn _match_results_to_circuits(
+    circuits: &[IqmCircuit],
+    meas_results: BatchResult,
+) -> Result<Vec<(usize, CircuitResult)>, IqmBackendError> {
+    let circuit_keys: Vec<HashSet<&str>> = circuits
+        .iter()
+        .map(|circuit| {
+            let mut keys: HashSet<&str> = circuit
+                .metadata
+                .iter()
+                .flat_map(|map| map.keys().map(String::as_str))
+                .collect();
+            for instruction in circuit.instructions.iter().filter(|x| x.name == "measure") {
+                if let Some(CalculatorFloat::Str(key)) = instruction.args.get("key") {
+                    keys.insert(key);
+                }
+            }
+            keys
+        })
+        .collect();
+    let mut matched_circuits = vec![false; circuits.len()];
+    let mut matched_results: Vec<(usize, CircuitResult)> = Vec::with_capacity(meas_results.len());
+
+    // Results without measurement keys are placed last, as they can belong to any circuit
+    let (empty_results, keyed_results): (Vec<_>, Vec<_>) = meas_results
+        .into_iter()
+        .enumerate()
+        .partition(|(_, result)| result.is_empty());
+    for (position, result) in keyed_results.into_iter().chain(empty_results) {
+        let measures_result = |index: &usize| {
+            !matched_circuits[*index]
+                && result
+                    .keys()
+                    .all(|key| circuit_keys[*index].contains(key.as_str()))
+        };
+        let circuit_index = if position < circuits.len() && measures_result(&position) {
+            position
+        } else {
+            let mut candidates = (0..circuits.len()).filter(measures_result);
+            match (candidates.next(), candidates.next()) {
+                (Some(index), None) => index,
+                (Some(index), Some(_)) if result.is_empty() => index,
+                (None, _) => {
+                    return Err(IqmBackendError::InvalidResults {
+                        msg: format!(
+                            "The results at position {} do not match any submitted circuit.",
+                            position
+                        ),
+                    })
+                }
+                (Some(index), Some(other_index)) => {
+                    return Err(IqmBackendError::InvalidResults {
+                        msg: format!(
+                            "The results at position {} match several submitted circuits, e.g. \
+                             {} and {}.",
+                            position, circuits[index].name, circuits[other_index].name
+                        ),
+                    })
+                }
+            }
+        };
+        matched_circuits[circuit_index] = true;
+        matched_results.push((circuit_index, result));
+    }
+    matched_results.sort_by_key(|(circuit_index, _)| *circuit_index);
+    Ok(matched_results)
+}
+
+/// Helper function to convert the IQM result format into the classical register format used by
+/// Roqoqo.
+///
+/// # Arguments
+///
+/// * `result` - The result to be processed.
+/// * `id` - The job ID.
+///
+/// # Returns
+///
+/// `Ok(Registers)` - The output registers constructed by processing the results.
+/// `Err(IqmBackendError)` - Something went wrong with the processing of the results.
+pub fn results_to_registers(
+    results: IqmRunResult,
+    id: String,
+) -> Result<Registers, IqmBackendError> {
+    let measured_qubits_map = get_measured_qubits_map(&results)?;
+    _results_to_registers(results, id, measured_qubits_map)
+}
+
+/// Count the measured bitstrings of each register of the results.
+///
+/// The bitstrings contain one character (`0` or `1`) per bit of the register, with the bit at
+/// index 0 of the register as the first character, i.e. the same bit order as the registers
+/// returned by [results_to_registers]. Bits of a register that are not measured are `0`.
+///
+/// # Arguments
+///
+/// * `results` - The results to be processed.
+///
+/// # Returns
+///
+/// `Ok(HashMap<String, HashMap<String, usize>>)` - The number of shots for each measured
+/// bitstring, by register name.
+/// `Err(IqmBackendError)` - The results contain no measurements, or something went wrong with the
+/// processing of the results.
+pub fn results_to_counts(
+    results: &IqmRunResult,
+) -> Result<HashMap<String, HashMap<String, usize>>, IqmBackendError> {
+    let measured_qubits_map = get_measured_qubits_map(results)?;
+    let meas_results =
+        results
+            .measurements
+            .as_ref()
+            .ok_or_else(|| IqmBackendError::InvalidResults {
+                msg: "The results do not contain any measurements.".to_string(),
+            })?;
+
+    let mut counts: HashMap<String, HashMap<String, usize>> = HashMap::new();
+    for result in meas_results.iter() {
+        for (reg, reg_result) in result.iter() {
+            if counts.contains_key(reg) {
+                return Err(IqmBackendError::InvalidResults {
+                    msg: "Backend results contain multiple entries for the same register."
+                        .to_string(),
+                });
+            }
+            let output_reg = _register_result_to_bits(reg, reg_result, &measured_qubits_map)?;
+            counts.insert(reg.clone(), bit_register_to_counts(&output_reg, false));
+        }
+    }
+    Ok(counts)
+}
+
+/// Convert the IQM result format into roqoqo bit registers one circuit at a time.
+///
+/// Unlike [results_to_registers], the registers are not collected into a single [Registers]
+/// value. The results of each circuit are converted and passed to `callback` together with the
+/// index of the circuit, then dropped before the next circuit is processed, so that only the
+/// registers of one circuit are held in memory at a time. Useful for jobs with a very large number
+/// of shots.
+///
+/// # Arguments
+///
+/// * `results` - The result to be processed.
+/// * `id` - The job ID.
+/// * `callback` - Called with the circuit index, the register name and the register of every
+///   register in the results.
+///
+/// # Returns
+///
+/// `Ok(())` - All registers have been passed to `callback`.
+/// `Err(IqmBackendError)` - Something went wrong with the processing of the results, or
+/// `callback` returned an error.
+pub fn stream_results_to_registers<F>(
+    results: IqmRunResult,
+    id: String,
+    mut callback: F,
+) -> Result<(), IqmBackendError>
+where
+    F: FnMut(usize, String, BitOutputRegister) -> Result<(), IqmBackendError>,
+{
+    let measured_qubits_map = get_measured_qubits_map(&results)?;
+    let meas_results = results
+        .measurements
+        .ok_or(IqmBackendError::EmptyResult { id })?;
+    let meas_results =
+        _match_results_to_circuits(&results.metadata.request.circuits, meas_results)?;
+
+    for (circuit_index, result) in meas_results.into_iter() {
+        for (reg, reg_result) in result.into_iter() {
+            let output_reg = _register_result_to_bits(&reg, &reg_result, &measured_qubits_map)?;
+            drop(reg_result);
+            callback(circuit_index, reg, output_reg)?;
         }
     }
     Ok(())
 }
 
-#[inline]
-/// Parse the IqmRunResult received to create the MeasuredQubitsMap, which is needed to process the
-/// results by specifying which qubits have been measured for each register. The qubits measured in
-/// each circuit are stored in the `metadata` field of each circuit submitted, and a copy of the
-/// `IqmRunRequest` is contained in the `metadata` field of the `IqmRunResult`.
-fn get_measured_qubits_map(results: &IqmRunResult) -> Result<MeasuredQubitsMap, IqmBackendError> {
-    let mut measured_qubits_map = HashMap::new();
-    let circuits = &results.metadata.request.circuits;
-    for circuit in circuits.iter() {
-        let tmp_measured_qubits_map =
-            circuit
-                .metadata
-                .clone()
-                .ok_or(IqmBackendError::MetadataError {
-                    msg: "Missing metadata field in the copy of IqmRequest returned with the \
-                          results."
-                        .to_string(),
-                })?;
-        for (tmp_reg, tmp_map) in tmp_measured_qubits_map.iter() {
-            match measured_qubits_map.get(tmp_reg) {
-                Some(_) => {
-                    return Err(IqmBackendError::MetadataError {
-                        msg:
-                            "Measured qubits maps from different circuits contain entries for the \
-                          same register."
-                                .to_string(),
-                    })
-                }
-                None => {
-                    measured_qubits_map.insert(tmp_reg.clone(), tmp_map.clone());
-                }
+/// Expand a circuit with symbolic parameters into one circuit per set of parameter values.
+///
+/// # Arguments
+///
+/// * `circuit` - The circuit with symbolic parameters.
+/// * `parameter_sets` - The value of each symbolic parameter, for each circuit of the sweep.
+///
+/// # Returns
+///
+/// `Ok(Vec<Circuit>)` - The circuits with substituted parameters, in the order of the parameter
+/// sets.
+/// `Err(IqmBackendError::InvalidCircuit)` - A parameter set does not define all parameters.
+pub fn expand_parameter_sweep(
+    circuit: &Circuit,
+    parameter_sets: &[HashMap<String, f64>],
+) -> Result<Vec<Circuit>, IqmBackendError> {
+    parameter_sets
+        .iter()
+        .enumerate()
+        .map(|(index, parameters)| {
+            let mut calculator = Calculator::new();
+            for (name, value) in parameters.iter() {
+                calculator.set_variable(name, *value);
+            }
+            circuit.substitute_parameters(&calculator).map_err(|err| {
+                IqmBackendError::InvalidCircuit {
+                    msg: format!(
+                        "Could not substitute the parameters of parameter set {}: {}",
+                        index, err
+                    ),
+                }
+            })
+        })
+        .collect()
+}
+
+/// Aggregate the shots of a bit register into a histogram of the measured bitstrings.
+///
+/// Each bitstring contains one character (`0` or `1`) per bit of the register. By default the
+/// bit at index 0 of the register is the first character of the bitstring. With `big_endian`
+/// the order is reversed, so that the bit at index 0 is the last character, i.e. the least
+/// significant bit when the bitstring is read as a binary number.
+///
+/// # Arguments
+///
+/// * `register` - The bit register containing the results of each shot.
+/// * `big_endian` - Whether the bit at index 0 is the last character of the bitstrings.
+///
+/// # Returns
+///
+/// `HashMap<String, usize>` - The number of shots for each measured bitstring.
+pub fn bit_register_to_counts(
+    register: &BitOutputRegister,
+    big_endian: bool,
+) -> HashMap<String, usize> {
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for shot in register.iter() {
+        let bits = shot.iter().map(|bit| if *bit { '1' } else { '0' });
+        let bitstring: String = if big_endian {
+            bits.rev().collect()
+        } else {
+            bits.collect()
+        };
+        *counts.entry(bitstring).or_insert(0) += 1;
+    }
+    counts
+}
+
+/// Export the shots of a bit register as CSV, e.g. for spreadsheet applications.
+///
+/// The CSV has a header row `shot,bit_0,bit_1,...` followed by one row per shot, with the index
+/// of the shot and the value (`0` or `1`) of each bit of the register.
+///
+/// # Arguments
+///
+/// * `registers` - The registers returned by running a circuit.
+/// * `register_name` - The name of the bit register to export.
+///
+/// # Returns
+///
+/// `String` - The CSV, empty if there is no bit register with the given name.
+pub fn registers_to_csv(registers: &Registers, register_name: &str) -> String {
+    let register = match registers.0.get(register_name) {
+        Some(register) => register,
+        None => return String::new(),
+    };
+    let number_bits = register.iter().map(|shot| shot.len()).max().unwrap_or(0);
+    let mut csv = String::from("shot");
+    for bit in 0..number_bits {
+        csv.push_str(&format!(",bit_{}", bit));
+    }
+    csv.push('\n');
+    for (shot_index, shot) in register.iter().enumerate() {
+        csv.push_str(&shot_index.to_string());
+        for bit in shot.iter() {
+            csv.push_str(if *bit { ",1" } else { ",0" });
+        }
+        csv.push('\n');
+    }
+    csv
+}
+
+/// Measurement statistics of a single bit of a register, see [qubit_statistics].
+#[derive(PartialEq, Debug, Clone, Serialize, Deserialize)]
+pub struct QubitStat {
+    /// Index of the bit in the register, i.e. of the qubit measured into it
+    pub qubit: usize,
+    /// Number of shots measuring 1
+    pub ones: usize,
+    /// Number of shots measuring 0
+    pub zeros: usize,
+    /// Fraction of shots measuring 1, 0 if the register contains no shots
+    pub mean: f64,
+}
+
+/// Compute the number of ones and zeros measured for each bit of a bit register.
+///
+/// Useful as a quick diagnostic of the readout, e.g. to spot qubits that are stuck in one state.
+///
+/// # Arguments
+///
+/// * `registers` - The registers returned by running a circuit.
+/// * `register_name` - The name of the bit register to analyse.
+///
+/// # Returns
+///
+/// `Vec<QubitStat>` - The statistics of each bit of the register, in register order. Empty if
+/// there is no bit register with the given name.
+pub fn qubit_statistics(registers: &Registers, register_name: &str) -> Vec<QubitStat> {
+    let register = match registers.0.get(register_name) {
+        Some(register) => register,
+        None => return vec![],
+    };
+    let number_bits = register.iter().map(|shot| shot.len()).max().unwrap_or(0);
+    (0..number_bits)
+        .map(|qubit| {
+            let (ones, zeros) =
+                register
+                    .iter()
+                    .fold((0, 0), |(ones, zeros), shot| match shot.get(qubit) {
+                        Some(true) => (ones + 1, zeros),
+                        Some(false) => (ones, zeros + 1),
+                        None => (ones, zeros),
+                    });
+            let shots = ones + zeros;
+            QubitStat {
+                qubit,
+                ones,
+                zeros,
+                mean: if shots == 0 {
+                    0.0
+                } else {
+                    ones as f64 / shots as f64
+                },
+            }
+        })
+        .collect()
+}
+
+/// Helper function to convert the IQM result format into the classical register format used by
+/// Roqoqo, together with a mask of the measured bits.
+///
+/// Bits of the output registers that are not written by any measurement are initialized to
+/// `false`, which cannot be distinguished from a measured `false` in the registers alone. The
+/// returned mask contains, for each output register, a `true` entry for every bit that was
+/// written by a measurement and a `false` entry for every bit that kept its default value.
+///
+/// # Arguments
+///
+/// * `result` - The result to be processed.
+/// * `id` - The job ID.
+///
+/// # Returns
+///
+/// `Ok((Registers, HashMap<String, Vec<bool>>))` - The output registers and the measured mask.
+/// `Err(IqmBackendError)` - Something went wrong with the processing of the results.
+pub fn results_to_registers_with_mask(
+    results: IqmRunResult,
+    id: String,
+) -> Result<(Registers, HashMap<String, Vec<bool>>), IqmBackendError> {
+    let measured_qubits_map = get_measured_qubits_map(&results)?;
+    let mut mask: HashMap<String, Vec<bool>> = HashMap::new();
+    for (reg, (readout_indices, reg_length)) in measured_qubits_map.iter() {
+        let mut reg_mask = vec![false; *reg_length];
+        for index in readout_indices.iter() {
+            if let Some(measured) = reg_mask.get_mut(*index) {
+                *measured = true
             }
         }
+        mask.insert(reg.clone(), reg_mask);
     }
-    Ok(measured_qubits_map)
+    Ok((
+        _results_to_registers(results, id, measured_qubits_map)?,
+        mask,
+    ))
 }
 
 /// Helper function to convert the IQM result format into the classical register format used by
-/// Roqoqo.
+/// Roqoqo, keying the results by the logical (qoqo) qubit indices.
+///
+/// When the job was submitted with a `qubit_mapping`, the results for each measurement are
+/// returned ordered by the physical qubits they were executed on. This function uses the mapping
+/// stored in the copy of the request returned with the results to reorder them, so that each
+/// outcome is written to the register position of the logical qubit it was measured from,
+/// regardless of the physical placement of that qubit. Without a stored mapping the output is identical to
+/// [results_to_registers].
 ///
 /// # Arguments
 ///
@@ -773,15 +4282,60 @@ fn get_measured_qubits_map(results: &IqmRunResult) -> Result<MeasuredQubitsMap,
 ///
 /// `Ok(Registers)` - The output registers constructed by processing the results.
 /// `Err(IqmBackendError)` - Something went wrong with the processing of the results.
-pub fn results_to_registers(
+pub fn results_to_registers_logical(
+    results: IqmRunResult,
+    id: String,
+) -> Result<Registers, IqmBackendError> {
+    let mut measured_qubits_map = get_measured_qubits_map(&results)?;
+    if let Some(mapping) = &results.metadata.request.qubit_mapping {
+        let mut physical_qubits: HashMap<&str, usize> = HashMap::new();
+        for single_mapping in mapping.iter() {
+            let physical = _convert_qubit_name_iqm_to_qoqo(&single_mapping.physical_name)
+                .ok_or_else(|| IqmBackendError::MetadataError {
+                    msg: format!(
+                        "Invalid physical qubit name {} in the qubit mapping.",
+                        single_mapping.physical_name
+                    ),
+                })?;
+            physical_qubits.insert(&single_mapping.logical_name, physical);
+        }
+        for circuit in results.metadata.request.circuits.iter() {
+            for instruction in circuit.instructions.iter().filter(|x| x.name == "measure") {
+                let key = match instruction.args.get("key") {
+                    Some(CalculatorFloat::Str(key)) => key,
+                    _ => continue,
+                };
+                if let Some((readout_indices, _)) = measured_qubits_map.get_mut(key) {
+                    let mut placed_indices = Vec::with_capacity(readout_indices.len());
+                    for (qubit, index) in instruction.qubits.iter().zip(readout_indices.iter()) {
+                        let physical = physical_qubits.get(qubit.as_str()).ok_or_else(|| {
+                            IqmBackendError::MetadataError {
+                                msg: format!("Qubit {} is missing from the qubit mapping.", qubit),
+                            }
+                        })?;
+                        placed_indices.push((*physical, *index));
+                    }
+                    // The results of each measurement are ordered by physical qubit
+                    placed_indices.sort();
+                    *readout_indices = placed_indices.into_iter().map(|(_, i)| i).collect();
+                }
+            }
+        }
+    }
+    _results_to_registers(results, id, measured_qubits_map)
+}
+
+/// Fill the output registers from the results, writing the j-th measured value of each register
+/// into the position given by the j-th entry of the measured qubits in `measured_qubits_map`.
+fn _results_to_registers(
     results: IqmRunResult,
     id: String,
+    measured_qubits_map: MeasuredQubitsMap,
 ) -> Result<Registers, IqmBackendError> {
     let mut bit_registers: HashMap<String, BitOutputRegister> = HashMap::new();
     let float_registers: HashMap<String, FloatOutputRegister> = HashMap::new();
     let complex_registers: HashMap<String, ComplexOutputRegister> = HashMap::new();
 
-    let measured_qubits_map = get_measured_qubits_map(&results)?;
     let meas_results = results
         .measurements
         .ok_or(IqmBackendError::EmptyResult { id })?;
@@ -789,42 +4343,120 @@ pub fn results_to_registers(
     // there is a separate result for every circuit submitted
     for result in meas_results.iter() {
         for (reg, reg_result) in result.iter() {
-            let (measured_qubits, reg_length) =
-                measured_qubits_map
-                    .get(reg)
-                    .ok_or(IqmBackendError::InvalidResults {
-                        msg: "Backend results contain registers that are not present in the \
-                                measured_qubits_map."
-                            .to_string(),
-                    })?;
+            if bit_registers.contains_key(reg) {
+                return Err(IqmBackendError::InvalidResults {
+                    msg: "Backend results contain multiple entries for the same register."
+                        .to_string(),
+                });
+            }
+            let output_reg = _register_result_to_bits(reg, reg_result, &measured_qubits_map)?;
+            bit_registers.insert(reg.clone(), output_reg);
+        }
+    }
+    Ok((bit_registers, float_registers, complex_registers))
+}
 
-            let number_measurements = reg_result.len();
-            match bit_registers.get(reg) {
-                None => {
-                    let initialized_reg = vec![false; *reg_length];
-                    let initialized_reg = vec![initialized_reg; number_measurements];
-                    bit_registers.insert(reg.clone(), initialized_reg);
-                }
-                Some(_) => {
-                    return Err(IqmBackendError::InvalidResults {
-                        msg: "Backend results contain multiple entries for the same register."
-                            .to_string(),
-                    })
-                }
+/// Suffix of the registers of the circuit with the given index of a parameter sweep.
+fn _sweep_register_suffix(index: usize) -> String {
+    format!("__sweep_{}", index)
+}
+
+/// Append a suffix to the names of the registers defined and written to by a circuit.
+fn _rename_registers(circuit: &Circuit, suffix: &str) -> Circuit {
+    let rename = |name: &String| format!("{}{}", name, suffix);
+    let mut renamed = Circuit::new();
+    for operation in circuit.iter() {
+        renamed += match operation {
+            Operation::DefinitionBit(op) => {
+                DefinitionBit::new(rename(op.name()), *op.length(), *op.is_output()).into()
+            }
+            Operation::MeasureQubit(op) => {
+                MeasureQubit::new(*op.qubit(), rename(op.readout()), *op.readout_index()).into()
             }
+            Operation::PragmaRepeatedMeasurement(op) => PragmaRepeatedMeasurement::new(
+                rename(op.readout()),
+                *op.number_measurements(),
+                op.qubit_mapping().clone(),
+            )
+            .into(),
+            _ => operation.clone(),
+        };
+    }
+    renamed
+}
 
-            let output_reg = bit_registers
-                .get_mut(reg)
-                .expect("Something went wrong when initializing the registers.");
-            for (shot_index, shot_result) in reg_result.iter().enumerate() {
-                for (j, qubit) in measured_qubits.iter().enumerate() {
-                    // turn 0 into false and 1 into true
-                    output_reg[shot_index][*qubit] ^= shot_result[j] != 0
-                }
+/// Convert the IQM result format into separate roqoqo registers for each circuit of the batch.
+///
+fn _results_to_registers_per_circuit(
+    results: IqmRunResult,
+    id: String,
+) -> Result<Vec<Registers>, IqmBackendError> {
+    let circuits = results.metadata.request.circuits;
+    let meas_results = results
+        .measurements
+        .ok_or(IqmBackendError::EmptyResult { id })?;
+
+    _match_results_to_circuits(&circuits, meas_results)?
+        .into_iter()
+        .map(|(circuit_index, result)| {
+            let measured_qubits_map = circuits
+                .get(circuit_index)
+                .and_then(|circuit| circuit.metadata.clone())
+                .ok_or(IqmBackendError::MetadataError {
+                    msg: format!(
+                        "Missing metadata of circuit {} in the copy of IqmRequest returned with \
+                         the results.",
+                        circuit_index
+                    ),
+                })?;
+            let mut bit_registers: HashMap<String, BitOutputRegister> = HashMap::new();
+            for (reg, reg_result) in result.iter() {
+                let output_reg = _register_result_to_bits(reg, reg_result, &measured_qubits_map)?;
+                bit_registers.insert(reg.clone(), output_reg);
             }
+            Ok((bit_registers, HashMap::new(), HashMap::new()))
+        })
+        .collect()
+}
+
+/// Helper function to convert the IQM result of one register into a roqoqo bit register.
+fn _register_result_to_bits(
+    reg: &str,
+    reg_result: &[Vec<u8>],
+    measured_qubits_map: &MeasuredQubitsMap,
+) -> Result<BitOutputRegister, IqmBackendError> {
+    let (measured_qubits, reg_length) =
+        measured_qubits_map
+            .get(reg)
+            .ok_or(IqmBackendError::InvalidResults {
+                msg: "Backend results contain registers that are not present in the \
+                      measured_qubits_map."
+                    .to_string(),
+            })?;
+
+    let mut output_reg = vec![vec![false; *reg_length]; reg_result.len()];
+    for (shot_index, shot_result) in reg_result.iter().enumerate() {
+        for (j, qubit) in measured_qubits.iter().enumerate() {
+            // turn 0 into false and 1 into true
+            output_reg[shot_index][*qubit] ^= shot_result[j] != 0
         }
     }
-    Ok((bit_registers, float_registers, complex_registers))
+    Ok(output_reg)
+}
+
+/// Generate an idempotency key that is unique for each submission.
+fn _generate_idempotency_key() -> String {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|x| x.as_nanos())
+        .unwrap_or_default();
+    format!(
+        "qoqo-iqm-{}-{:x}-{}",
+        std::process::id(),
+        nanos,
+        COUNTER.fetch_add(1, Ordering::Relaxed)
+    )
 }
 
 #[inline]
@@ -845,6 +4477,38 @@ fn _construct_headers(token: &str) -> HeaderMap {
     headers
 }
 
+/// Read the access token and the optional endpoint URL of a profile from the content of a
+/// credentials file. Values can be quoted, lines starting with `#` or `;` are comments.
+fn _parse_credentials_profile(
+    content: &str,
+    profile_name: &str,
+) -> Option<(String, Option<String>)> {
+    let mut in_profile = false;
+    let mut token = None;
+    let mut endpoint = None;
+    for line in content.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+            continue;
+        }
+        if let Some(section) = line.strip_prefix('[').and_then(|x| x.strip_suffix(']')) {
+            in_profile = section.trim() == profile_name;
+            continue;
+        }
+        if !in_profile {
+            continue;
+        }
+        if let Some((key, value)) = line.split_once('=') {
+            let value = value.trim().trim_matches('"').to_string();
+            match key.trim() {
+                "token" => token = Some(value),
+                "endpoint" => endpoint = Some(value),
+                _ => (),
+            }
+        }
+    }
+    token.map(|token| (token, endpoint))
+}
+
 fn _get_token_from_env_var() -> Result<String, TokenError> {
     let token: String = var("IQM_TOKEN").map_err(|_| TokenError {
         msg: "Could not retrieve token from environment variable IQM_TOKEN.".to_string(),
@@ -852,6 +4516,70 @@ fn _get_token_from_env_var() -> Result<String, TokenError> {
     Ok(token)
 }
 
+fn _get_token_from_file() -> Result<Token, TokenError> {
+    let path: String = var("IQM_TOKENS_FILE").map_err(|_| TokenError {
+        msg: "Environment variable IQM_TOKENS_FILE is not set.".to_string(),
+    })?;
+    _read_token_file(Path::new(&path))
+}
+
+/// Read the tokens from a JSON tokens file as written by the IQM token manager.
+fn _read_token_file(path: &Path) -> Result<Token, TokenError> {
+    let content = fs::read_to_string(path).map_err(|err| TokenError {
+        msg: format!("Could not read tokens file {}: {}", path.display(), err),
+    })?;
+    let token: Token = serde_json::from_str(&content).map_err(|err| TokenError {
+        msg: format!("Could not parse tokens file {}: {}", path.display(), err),
+    })?;
+    Ok(token)
+}
+
+/// Check that the circuit does not contain classical control (feed-forward) constructs, which
+/// are not supported by the backend. Circuits in loops are checked as well.
+fn _validate_no_feed_forward(circuit: &Circuit) -> Result<(), IqmBackendError> {
+    for op in circuit.iter() {
+        match op {
+            Operation::PragmaConditional(o) => {
+                return Err(IqmBackendError::InvalidCircuit {
+                    msg: format!(
+                        "Operations conditioned on the classical register {} were found, but \
+                         feed-forward is not supported by this backend version.",
+                        o.condition_register()
+                    ),
+                })
+            }
+            Operation::PragmaLoop(o) => _validate_no_feed_forward(o.circuit())?,
+            _ => (),
+        }
+    }
+    Ok(())
+}
+
+/// Check that the circuit does not contain measurement operations that cannot be run on hardware,
+/// such as the readout of the state vector or of occupation probabilities. Only `MeasureQubit` and
+/// `PragmaRepeatedMeasurement` are supported. Circuits in loops are checked as well.
+fn _validate_supported_measurements(circuit: &Circuit) -> Result<(), IqmBackendError> {
+    for op in circuit.iter() {
+        match op {
+            Operation::PragmaGetStateVector(_)
+            | Operation::PragmaGetDensityMatrix(_)
+            | Operation::PragmaGetOccupationProbability(_)
+            | Operation::PragmaGetPauliProduct(_) => {
+                return Err(IqmBackendError::InvalidCircuit {
+                    msg: format!(
+                        "Measurement operation {} is not supported by the IQM backend, only \
+                         MeasureQubit and PragmaRepeatedMeasurement can be used.",
+                        op.hqslang()
+                    ),
+                })
+            }
+            Operation::PragmaLoop(o) => _validate_supported_measurements(o.circuit())?,
+            _ => (),
+        }
+    }
+    Ok(())
+}
+
 // Helper function to get number of qubits in a qoqo Circuit
 fn _get_number_qubits(qc: &Circuit) -> Option<usize> {
     let mut number_qubits_vec: Vec<usize> = vec![];
@@ -868,26 +4596,103 @@ fn _get_number_qubits(qc: &Circuit) -> Option<usize> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{GarnetDevice, IqmInstruction};
     use std::f64::consts::PI;
 
-    #[inline]
-    fn _convert_qubit_name_iqm_to_qoqo(name: String) -> usize {
-        let qubit_number = name
-            .chars()
-            .last()
-            .expect("Passed empty qubit name string to conversion function.")
-            .to_digit(10)
-            .expect("Last digit of qubit name in the IQM format should be a number.");
+    #[test]
+    fn test_qubit_name_conversion_iqm_to_qoqo() {
+        assert_eq!(_convert_qubit_name_iqm_to_qoqo("QB2").unwrap(), 1);
+        assert_eq!(_convert_qubit_name_iqm_to_qoqo("QB10").unwrap(), 9);
+    }
+
+    #[test]
+    fn test_read_token_file() {
+        let path =
+            std::env::temp_dir().join(format!("qoqo_iqm_tokens_{}.json", std::process::id()));
+        fs::write(
+            &path,
+            r#"{
+                "pid": 1234,
+                "timestamp": "2024-01-01T00:00:00",
+                "refresh_status": "SUCCESS",
+                "access_token": "file_access_token",
+                "refresh_token": "file_refresh_token",
+                "auth_server_url": "https://auth.example.com"
+            }"#,
+        )
+        .unwrap();
+        let token = _read_token_file(&path).unwrap();
+        assert_eq!(token.access_token, "file_access_token");
+        assert_eq!(token.refresh_token, "file_refresh_token");
+        assert_eq!(token.auth_server_url, "https://auth.example.com");
+
+        fs::write(&path, r#"{"access_token": "incomplete"}"#).unwrap();
+        assert!(_read_token_file(&path).is_err());
 
-        qubit_number as usize - 1
+        fs::remove_file(&path).unwrap();
+        assert!(_read_token_file(&path).is_err());
     }
 
     #[test]
-    fn test_qubit_name_conversion_iqm_to_qoqo() {
-        let qubit = String::from("QB2");
-        let converted_name = _convert_qubit_name_iqm_to_qoqo(qubit);
+    fn test_client_config() {
+        let mut backend =
+            Backend::new(GarnetDevice::new().into(), Some("dummy".to_string())).unwrap();
+        assert_eq!(
+            backend.client_config(),
+            ClientConfig {
+                https_only: true,
+                pool_max_idle_per_host: None,
+                tcp_keepalive: None,
+            }
+        );
+
+        backend.set_pool_max_idle_per_host(4);
+        backend.set_tcp_keepalive(Duration::from_secs(30));
+        backend.set_allow_http(true);
+        assert_eq!(
+            backend.client_config(),
+            ClientConfig {
+                https_only: false,
+                pool_max_idle_per_host: Some(4),
+                tcp_keepalive: Some(Duration::from_secs(30)),
+            }
+        );
+        assert!(backend.build_client().is_ok());
+    }
+
+    #[test]
+    fn test_retry_backoff() {
+        let policy = RetryPolicy {
+            max_retries: 10,
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(5),
+        };
+        assert_eq!(policy.backoff(0), Duration::from_secs(1));
+        assert_eq!(policy.backoff(1), Duration::from_secs(2));
+        assert_eq!(policy.backoff(2), Duration::from_secs(4));
+        assert_eq!(policy.backoff(3), Duration::from_secs(5));
+        assert_eq!(policy.backoff(100), Duration::from_secs(5));
+        assert_eq!(policy.backoff(usize::MAX), Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_min_shots_warning() {
+        let mut backend =
+            Backend::new(GarnetDevice::new().into(), Some("dummy".to_string())).unwrap();
+        assert!(backend.min_shots_warning(1).is_none());
 
-        assert_eq!(converted_name, 1)
+        backend.set_min_shots_warning(100);
+        assert!(backend.min_shots_warning(99).is_some());
+        assert!(backend.min_shots_warning(100).is_none());
+        assert!(backend.min_shots_warning(1000).is_none());
+    }
+
+    #[test]
+    fn test_split_shots() {
+        assert_eq!(_split_shots(30000, 10000), vec![10000, 10000, 10000]);
+        assert_eq!(_split_shots(25000, 10000), vec![10000, 10000, 5000]);
+        assert_eq!(_split_shots(500, 10000), vec![500]);
+        assert!(_split_shots(0, 10000).is_empty());
     }
 
     #[test]
@@ -935,6 +4740,300 @@ mod tests {
         assert_eq!(bit_registers, output_registers);
     }
 
+    #[test]
+    fn test_qubit_statistics() {
+        let mut iqm_results: HashMap<String, Vec<Vec<u8>>> = HashMap::new();
+        iqm_results.insert(
+            "ro".to_string(),
+            vec![vec![1, 0, 1], vec![1, 0, 0], vec![1, 1, 0], vec![1, 0, 0]],
+        );
+        let mut measured_qubits_map = HashMap::new();
+        measured_qubits_map.insert("ro".to_string(), (vec![0, 1, 2], 3));
+
+        let results = create_mock_run_results(iqm_results, &[measured_qubits_map]);
+        let registers = results_to_registers(results, String::new()).unwrap();
+        assert_eq!(
+            qubit_statistics(&registers, "ro"),
+            vec![
+                QubitStat {
+                    qubit: 0,
+                    ones: 4,
+                    zeros: 0,
+                    mean: 1.0
+                },
+                QubitStat {
+                    qubit: 1,
+                    ones: 1,
+                    zeros: 3,
+                    mean: 0.25
+                },
+                QubitStat {
+                    qubit: 2,
+                    ones: 1,
+                    zeros: 3,
+                    mean: 0.25
+                },
+            ]
+        );
+        assert!(qubit_statistics(&registers, "missing").is_empty());
+    }
+
+    #[test]
+    fn test_results_to_registers_heralding() {
+        let mut iqm_results: HashMap<String, Vec<Vec<u8>>> = HashMap::new();
+        iqm_results.insert("ro".to_string(), vec![vec![1, 0], vec![0, 1]]);
+        let mut measured_qubits_map = HashMap::new();
+        measured_qubits_map.insert("ro".to_string(), (vec![0, 1], 2));
+
+        // Heralding discarded two of the four requested shots
+        let mut results = create_mock_run_results(iqm_results, &[measured_qubits_map]);
+        results.metadata.request.shots = 4;
+        results.metadata.request.heralding_mode = HeraldingMode::Zeros;
+        let (bit_registers, _, _) = results_to_registers(results, String::new()).unwrap();
+        assert_eq!(
+            bit_registers.get("ro").unwrap(),
+            &vec![vec![true, false], vec![false, true]]
+        );
+    }
+
+    #[test]
+    fn test_stream_results_to_registers() {
+        let mut measured_qubits_map_1 = HashMap::new();
+        let mut measured_qubits_map_2 = HashMap::new();
+        measured_qubits_map_1.insert("reg1".to_string(), (vec![0, 2, 4], 5));
+        measured_qubits_map_1.insert("reg2".to_string(), (vec![1], 2));
+        measured_qubits_map_2.insert("reg3".to_string(), (vec![1, 2], 3));
+        let metadata = vec![measured_qubits_map_1, measured_qubits_map_2];
+
+        let mut results = create_mock_run_results(HashMap::new(), &metadata);
+        results.measurements = Some(vec![
+            HashMap::from([
+                ("reg1".to_string(), vec![vec![0, 1, 0], vec![1, 1, 0]]),
+                ("reg2".to_string(), vec![vec![1], vec![0]]),
+            ]),
+            HashMap::from([("reg3".to_string(), vec![vec![1, 1], vec![1, 0]])]),
+        ]);
+
+        let mut streamed: HashMap<String, BitOutputRegister> = HashMap::new();
+        let mut circuit_indices: HashMap<String, usize> = HashMap::new();
+        stream_results_to_registers(results.clone(), String::new(), |index, reg, register| {
+            circuit_indices.insert(reg.clone(), index);
+            streamed.insert(reg, register);
+            Ok(())
+        })
+        .unwrap();
+
+        let (bit_registers, _, _) = results_to_registers(results, String::new()).unwrap();
+        assert_eq!(streamed, bit_registers);
+        assert_eq!(
+            circuit_indices,
+            HashMap::from([
+                ("reg1".to_string(), 0),
+                ("reg2".to_string(), 0),
+                ("reg3".to_string(), 1)
+            ])
+        );
+    }
+
+    #[test]
+    fn test_results_to_counts() {
+        let mut iqm_results: HashMap<String, Vec<Vec<u8>>> = HashMap::new();
+        iqm_results.insert("reg1".to_string(), vec![vec![1, 0], vec![1, 0], vec![0, 0]]);
+        iqm_results.insert("reg2".to_string(), vec![vec![1], vec![1], vec![1]]);
+        let mut measured_qubits_map = HashMap::new();
+        measured_qubits_map.insert("reg1".to_string(), (vec![0, 2], 4));
+        measured_qubits_map.insert("reg2".to_string(), (vec![1], 2));
+        let mut results = create_mock_run_results(iqm_results, &[measured_qubits_map]);
+
+        let counts = results_to_counts(&results).unwrap();
+        assert_eq!(
+            counts,
+            HashMap::from([
+                (
+                    "reg1".to_string(),
+                    HashMap::from([("1000".to_string(), 2), ("0000".to_string(), 1)])
+                ),
+                ("reg2".to_string(), HashMap::from([("01".to_string(), 3)])),
+            ])
+        );
+
+        // The bitstrings follow the bit order of the registers
+        let (bit_registers, _, _) = results_to_registers(results.clone(), String::new()).unwrap();
+        for (reg, register) in bit_registers.iter() {
+            assert_eq!(counts[reg], bit_register_to_counts(register, false));
+        }
+
+        results.measurements = None;
+        assert!(matches!(
+            results_to_counts(&results),
+            Err(IqmBackendError::InvalidResults { .. })
+        ));
+    }
+
+    #[test]
+    fn test_match_results_to_circuits() {
+        let metadata = vec![
+            HashMap::from([("reg1".to_string(), (vec![0], 1))]),
+            HashMap::from([("reg2".to_string(), (vec![0], 1))]),
+            HashMap::from([("reg3".to_string(), (vec![0], 1))]),
+        ];
+        let results = create_mock_run_results(HashMap::new(), &metadata);
+        let circuits = &results.metadata.request.circuits;
+        let result = |reg: &str| HashMap::from([(reg.to_string(), vec![vec![1]])]);
+
+        // Results in reversed order are sorted by the submitted circuits
+        let matched = _match_results_to_circuits(
+            circuits,
+            vec![result("reg3"), result("reg2"), result("reg1")],
+        )
+        .unwrap();
+        assert_eq!(
+            matched,
+            vec![
+                (0, result("reg1")),
+                (1, result("reg2")),
+                (2, result("reg3"))
+            ]
+        );
+
+        // Results of circuits without measurements keep their position
+        let matched =
+            _match_results_to_circuits(circuits, vec![HashMap::new(), result("reg1")]).unwrap();
+        assert_eq!(matched, vec![(0, result("reg1")), (1, HashMap::new())]);
+
+        assert!(matches!(
+            _match_results_to_circuits(circuits, vec![result("reg1"), result("reg4")]),
+            Err(IqmBackendError::InvalidResults { .. })
+        ));
+        assert!(matches!(
+            _match_results_to_circuits(circuits, vec![result("reg1"), result("reg1")]),
+            Err(IqmBackendError::InvalidResults { .. })
+        ));
+    }
+
+    #[test]
+    fn test_results_to_registers_logical() {
+        let mut iqm_results: HashMap<String, Vec<Vec<u8>>> = HashMap::new();
+        iqm_results.insert("ro".to_string(), vec![vec![1, 1, 0], vec![0, 0, 1]]);
+        let mut measured_qubits_map = HashMap::new();
+        measured_qubits_map.insert("ro".to_string(), (vec![0, 1, 2], 3));
+
+        let mut results = create_mock_run_results(iqm_results, &[measured_qubits_map]);
+        results.metadata.request.circuits[0].instructions = vec![IqmInstruction {
+            name: "measure".to_string(),
+            qubits: vec!["QB1".to_string(), "QB2".to_string(), "QB3".to_string()],
+            args: HashMap::from([("key".to_string(), CalculatorFloat::from("ro"))]),
+        }];
+
+        // Without a qubit mapping both conversions agree
+        assert_eq!(
+            results_to_registers(results.clone(), String::new()).unwrap(),
+            results_to_registers_logical(results.clone(), String::new()).unwrap()
+        );
+
+        results.metadata.request.qubit_mapping = Some(
+            [("QB1", "QB3"), ("QB2", "QB1"), ("QB3", "QB2")]
+                .iter()
+                .map(|(logical, physical)| SingleQubitMapping {
+                    logical_name: logical.to_string(),
+                    physical_name: physical.to_string(),
+                })
+                .collect(),
+        );
+
+        // Results are ordered by physical qubit: QB1 (logical QB2), QB2 (logical QB3) and
+        // QB3 (logical QB1)
+        let (bit_registers, _, _) =
+            results_to_registers_logical(results.clone(), String::new()).unwrap();
+        assert_eq!(
+            bit_registers["ro"],
+            vec![vec![false, true, true], vec![true, false, false]]
+        );
+
+        // Missing logical qubits in the mapping are reported
+        results
+            .metadata
+            .request
+            .qubit_mapping
+            .as_mut()
+            .unwrap()
+            .pop();
+        assert!(matches!(
+            results_to_registers_logical(results, String::new()),
+            Err(IqmBackendError::MetadataError { .. })
+        ));
+    }
+
+    #[test]
+    fn test_results_to_registers_with_mask() {
+        let mut iqm_results: HashMap<String, Vec<Vec<u8>>> = HashMap::new();
+        iqm_results.insert("ro".to_string(), vec![vec![0, 1], vec![0, 0]]);
+        let mut measured_qubits_map = HashMap::new();
+        measured_qubits_map.insert("ro".to_string(), (vec![1, 3], 4));
+
+        let results = create_mock_run_results(iqm_results, &[measured_qubits_map]);
+        let ((bit_registers, _, _), mask) =
+            results_to_registers_with_mask(results, String::new()).unwrap();
+
+        assert_eq!(
+            bit_registers["ro"],
+            vec![vec![false, false, false, true], vec![false; 4]]
+        );
+        assert_eq!(mask["ro"], vec![false, true, false, true]);
+    }
+
+    #[test]
+    fn test_bit_register_to_counts() {
+        let register = vec![
+            vec![true, false, false],
+            vec![false, false, false],
+            vec![true, false, false],
+        ];
+        let counts = bit_register_to_counts(&register, false);
+        assert_eq!(
+            counts,
+            HashMap::from([("100".to_string(), 2), ("000".to_string(), 1)])
+        );
+
+        let counts = bit_register_to_counts(&register, true);
+        assert_eq!(
+            counts,
+            HashMap::from([("001".to_string(), 2), ("000".to_string(), 1)])
+        );
+    }
+
+    #[test]
+    fn test_job_failed_structured_message() {
+        let msg =
+            r#"{"code": "CALIBRATION_EXPIRED", "message": "Stale calibration", "qubits": ["QB2"]}"#;
+        let err = _job_failed_error("id".to_string(), msg.to_string());
+        match err {
+            IqmBackendError::JobFailedWithDetails { id, detail } => {
+                assert_eq!(id, "id");
+                assert_eq!(detail.code, "CALIBRATION_EXPIRED");
+                assert_eq!(detail.message, "Stale calibration");
+                assert_eq!(detail.qubits, vec!["QB2".to_string()]);
+            }
+            _ => panic!("Structured message was not parsed: {:?}", err),
+        }
+    }
+
+    #[test]
+    fn test_job_failed_plain_message() {
+        let err = _job_failed_error("id".to_string(), "Something went wrong".to_string());
+        match err {
+            IqmBackendError::JobFailed { id, msg } => {
+                assert_eq!(id, "id");
+                assert_eq!(msg, "Something went wrong");
+            }
+            _ => panic!("Plain message was not kept: {:?}", err),
+        }
+
+        // JSON without an error code is kept as a plain message
+        let err = _job_failed_error("id".to_string(), r#"{"message": "oops"}"#.to_string());
+        assert!(matches!(err, IqmBackendError::JobFailed { .. }));
+    }
+
     /// Helper function to create mocked result data structures
     fn create_mock_run_results(
         iqm_results: HashMap<String, Vec<Vec<u8>>>,
@@ -957,14 +5056,22 @@ mod tests {
             shots: 1,
             circuit_duration_check: false,
             heralding_mode: HeraldingMode::None,
+            priority: None,
+        };
+        let metadata = Metadata {
+            calibration_set_id: None,
+            request,
         };
-        let metadata = Metadata { request };
         IqmRunResult {
             status: Status::Ready,
             measurements: Some(vec![iqm_results]),
             message: None,
             metadata,
             warnings: None,
+            circuit_statuses: None,
+            circuit_warnings: None,
+            queue_position: None,
+            average_execution_time: None,
         }
     }
 }
@@ -0,0 +1,314 @@
+// Copyright © 2020-2023 HQS Quantum Simulations GmbH. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the
+// License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either
+// express or implied. See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+use reqwest::{Client, RequestBuilder, Response};
+use roqoqo::{Circuit, RoqoqoBackendError};
+
+use super::{
+    _abort_failed_error, _construct_headers, check_status_code, Backend, EndpointPaths,
+    IqmRunResult,
+};
+use crate::IqmBackendError;
+
+/// Asynchronous IQM backend.
+///
+/// Provides the job functions of [Backend] on top of the async reqwest client, so that many jobs
+/// can be handled from an async runtime without blocking its threads. The circuits are validated
+/// and converted, and all settings are applied, by the wrapped [Backend]. Requires the `async`
+/// feature and a tokio runtime.
+#[derive(PartialEq, Debug, Clone)]
+pub struct AsyncBackend {
+    backend: Backend,
+}
+
+impl From<Backend> for AsyncBackend {
+    fn from(backend: Backend) -> Self {
+        Self::new(backend)
+    }
+}
+
+impl AsyncBackend {
+    /// Creates a new asynchronous IQM backend.
+    ///
+    /// # Arguments
+    ///
+    /// * `backend` - The backend providing the device, the access token and the settings.
+    pub fn new(backend: Backend) -> Self {
+        Self { backend }
+    }
+
+    /// Returns the wrapped backend.
+    pub fn backend(&self) -> &Backend {
+        &self.backend
+    }
+
+    /// Returns the wrapped backend mutably, e.g. to change its settings.
+    pub fn backend_mut(&mut self) -> &mut Backend {
+        &mut self.backend
+    }
+
+    /// Submit a circuit batch to be executed on the IQM platform.
+    ///
+    /// See [Backend::submit_circuit_batch].
+    ///
+    /// # Arguments
+    ///
+    /// * `circuit_batch` - The circuits to be submitted.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(String)` - The ID of the submitted job.
+    /// * `Err(IqmBackendError)` - The circuits are invalid or something went wrong when submitting
+    ///   the job.
+    pub async fn submit_circuit_batch(
+        &self,
+        circuit_batch: &[Circuit],
+    ) -> Result<String, IqmBackendError> {
+        let backend = &self.backend;
+        let data =
+            backend._build_run_request(circuit_batch, backend.number_measurements_internal)?;
+        if let Some(warning) = backend.min_shots_warning(data.shots as usize) {
+            backend.warn_submission(warning);
+        }
+        let client = &self.build_client()?;
+        let idempotency_header = &backend.idempotency_header()?;
+        let body = &backend.request_format.encode(&data)?;
+        let remote_host = &backend.device.remote_host();
+
+        let response = self
+            .send_authenticated(|token| async move {
+                let headers = backend.run_request_headers(&token, idempotency_header);
+                self.send_with_retries(|| {
+                    client
+                        .post(remote_host)
+                        .headers(headers.clone())
+                        .body(body.clone())
+                })
+                .await
+            })
+            .await?;
+
+        let status = response.status();
+        let text = response
+            .text()
+            .await
+            .map_err(|err| RoqoqoBackendError::NetworkError {
+                msg: format!("Error during POST request: {:?}", err),
+            })?;
+        backend._job_id_from_response(&data, status, &text)
+    }
+
+    /// Get the results of a submitted job without waiting for it to finish.
+    ///
+    /// See [Backend::get_results]. The results of finished jobs are cached in the cache shared
+    /// with the wrapped backend.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The job ID for the query.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(IqmRunResult)` - The current results of the job.
+    /// * `Err(RoqoqoBackendError)` - If something goes wrong with the HTTP request, or the
+    ///   response is not formatted correctly.
+    pub async fn get_results(&self, id: String) -> Result<IqmRunResult, RoqoqoBackendError> {
+        let backend = &self.backend;
+        if let Some(iqm_result) = backend.result_cache.get(&id) {
+            return Ok(iqm_result);
+        }
+        let client = &self.build_client()?;
+        let job_url = &EndpointPaths::format(
+            &backend.endpoint_paths.job_status,
+            &backend.device.remote_host(),
+            &id,
+        );
+
+        let response = self
+            .send_authenticated(|token| async move {
+                client
+                    .get(job_url)
+                    .headers(_construct_headers(&token))
+                    .send()
+                    .await
+                    .map_err(|e| RoqoqoBackendError::NetworkError {
+                        msg: format!("Error during GET request: {:?}", e),
+                    })
+            })
+            .await?;
+
+        let iqm_result = response.json::<IqmRunResult>().await.map_err(|err| {
+            RoqoqoBackendError::NetworkError {
+                msg: format!("Error during deserialisation of GET response: {:?}", err),
+            }
+        })?;
+        Ok(backend.record_results(id, iqm_result))
+    }
+
+    /// Poll results until job is either ready, failed, aborted or timed out.
+    ///
+    /// See [Backend::wait_for_results]. Uses the timeout and the polling interval of the wrapped
+    /// backend and sleeps with [tokio::time::sleep] between two queries.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The job ID for the query.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(IqmRunResult)` - Result of the job if ready.
+    /// * `Err(IqmBackendError)` - If the job failed, timed out or aborted.
+    pub async fn wait_for_results(&self, id: String) -> Result<IqmRunResult, IqmBackendError> {
+        let backend = &self.backend;
+        let start_time = Instant::now();
+        let mut empty_result_retries = 0;
+
+        while start_time.elapsed().as_secs_f64() < backend.timeout_secs {
+            let iqm_result = self.get_results(id.clone()).await?;
+            match backend.finished_results(&id, iqm_result, &mut empty_result_retries) {
+                Some(result) => return result,
+                None => {
+                    tokio::time::sleep(Duration::from_secs_f64(backend.polling_interval_secs)).await
+                }
+            }
+        }
+        Err(backend.timeout_error())
+    }
+
+    /// Abort a submitted job.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The ID of the job to abort.
+    ///
+    /// # Returns
+    ///
+    /// * `Err(IqmBackendError)` - If the job abortion failed.
+    pub async fn abort_job(&self, id: String) -> Result<(), IqmBackendError> {
+        let backend = &self.backend;
+        let client = self.build_client()?;
+        let abort_url = EndpointPaths::format(
+            &backend.endpoint_paths.abort,
+            &backend.device.remote_host(),
+            &id,
+        );
+
+        let resp = client
+            .post(abort_url)
+            .headers(_construct_headers(&backend.current_access_token()))
+            .send()
+            .await
+            .map_err(|err| {
+                IqmBackendError::RoqoqoBackendError(RoqoqoBackendError::NetworkError {
+                    msg: format!("Error during POST request of abort_job: {:?}", err),
+                })
+            })?;
+
+        if check_status_code(resp.status(), &backend.success_status_codes).is_ok() {
+            return Ok(());
+        }
+        let text = resp.text().await.unwrap_or_default();
+        Err(_abort_failed_error(id, &text))
+    }
+
+    /// Create the async HTTP client with the settings of the wrapped backend.
+    fn build_client(&self) -> Result<Client, RoqoqoBackendError> {
+        let config = self.backend.client_config();
+        let mut builder = Client::builder().https_only(config.https_only);
+        if let Some(max_idle) = config.pool_max_idle_per_host {
+            builder = builder.pool_max_idle_per_host(max_idle);
+        }
+        if let Some(interval) = config.tcp_keepalive {
+            builder = builder.tcp_keepalive(interval);
+        }
+        builder
+            .build()
+            .map_err(|err| RoqoqoBackendError::NetworkError {
+                msg: format!("Could not create HTTPS client: {:?}", err),
+            })
+    }
+
+    /// Send a request, retrying according to the retry policy of the wrapped backend.
+    ///
+    /// See [Backend::set_retry_policy].
+    async fn send_with_retries(
+        &self,
+        request: impl Fn() -> RequestBuilder,
+    ) -> Result<Response, RoqoqoBackendError> {
+        let backend = &self.backend;
+        let start_time = Instant::now();
+        let time_budget = Duration::from_secs_f64(backend.timeout_secs);
+        let mut retries = 0;
+        loop {
+            let result = request().send().await;
+            let retryable = match &result {
+                Ok(response) => response.status().is_server_error(),
+                Err(_) => true,
+            };
+            let backoff = backend.retry_policy.backoff(retries);
+            if !retryable
+                || retries >= backend.retry_policy.max_retries
+                || start_time.elapsed() + backoff > time_budget
+            {
+                return result.map_err(|err| RoqoqoBackendError::NetworkError {
+                    msg: format!("Error during POST request: {:?}", err),
+                });
+            }
+            retries += 1;
+            tokio::time::sleep(backoff).await;
+        }
+    }
+
+    /// Send a request with the current access token, refreshing the token and sending the request
+    /// once more if the server rejects it with HTTP status 401 and refresh credentials are set.
+    async fn send_authenticated<F, Fut>(&self, send: F) -> Result<Response, RoqoqoBackendError>
+    where
+        F: Fn(String) -> Fut,
+        Fut: Future<Output = Result<Response, RoqoqoBackendError>>,
+    {
+        let backend = &self.backend;
+        let response = send(backend.current_access_token()).await?;
+        if response.status() == reqwest::StatusCode::UNAUTHORIZED
+            && backend.refresh_credentials.is_some()
+        {
+            self.refresh_access_token().await?;
+            return send(backend.current_access_token()).await;
+        }
+        Ok(response)
+    }
+
+    /// Exchange the refresh token for a new access token at the auth server.
+    async fn refresh_access_token(&self) -> Result<(), RoqoqoBackendError> {
+        let (token_url, form) = self.backend.refresh_request()?;
+        let response = self
+            .build_client()?
+            .post(token_url)
+            .form(&form)
+            .send()
+            .await
+            .map_err(|err| RoqoqoBackendError::MissingAuthentication {
+                msg: format!("Could not refresh the IQM access token: {:?}", err),
+            })?;
+        let status = response.status();
+        let text =
+            response
+                .text()
+                .await
+                .map_err(|err| RoqoqoBackendError::MissingAuthentication {
+                    msg: format!("Could not refresh the IQM access token: {:?}", err),
+                })?;
+        self.backend.store_refreshed_token(status, &text)
+    }
+}
@@ -0,0 +1,406 @@
+// Copyright © 2020-2023 HQS Quantum Simulations GmbH. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the
+// License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either
+// express or implied. See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::f64::consts::PI;
+
+use roqoqo::devices::Device;
+use roqoqo::operations::*;
+use roqoqo::Circuit;
+
+use crate::{DenebDevice, IqmBackendError};
+
+/// Decompose the multi-qubit gates of a circuit into `RotateXY` and `ControlledPauliZ` gates.
+///
+/// Currently only `ControlledControlledPauliZ` is decomposed. The circuits of `PragmaLoop`
+/// operations are decomposed as well, all other operations are kept as they are.
+///
+/// # Arguments
+///
+/// * `circuit` - The circuit to decompose.
+/// * `device` - The device whose connectivity the decomposition has to respect.
+///
+/// # Returns
+///
+/// * `Ok(Circuit)` - The decomposed circuit.
+/// * `Err(IqmBackendError::InvalidCircuit)` - A gate cannot be decomposed on the device.
+pub(crate) fn decompose_multi_qubit_gates<T: Device>(
+    circuit: &Circuit,
+    device: &T,
+) -> Result<Circuit, IqmBackendError> {
+    let mut decomposed = Circuit::new();
+    for op in circuit.iter() {
+        match op {
+            Operation::ControlledControlledPauliZ(o) => {
+                for decomposed_op in decompose_controlled_controlled_pauli_z(o, device)? {
+                    decomposed += decomposed_op
+                }
+            }
+            Operation::PragmaLoop(o) => {
+                decomposed += PragmaLoop::new(
+                    o.repetitions().clone(),
+                    decompose_multi_qubit_gates(o.circuit(), device)?,
+                )
+            }
+            _ => decomposed += op.clone(),
+        }
+    }
+    Ok(decomposed)
+}
+
+/// Transpile a circuit compiled for the `ResonatorFreeDevice` to the `DenebDevice`.
+///
+/// Each `ControlledPauliZ` gate between two qubits is implemented via the central resonator of
+/// the Deneb device: the excitation of the target qubit is stored in the resonator with a
+/// `SingleExcitationStore`, a `CZQubitResonator` acts on the control qubit and the resonator, and
+/// the excitation is loaded back into the target qubit with a `SingleExcitationLoad`. The circuits
+/// of `PragmaLoop` operations are transpiled as well, all other operations are kept as they are.
+///
+/// # Arguments
+///
+/// * `circuit` - The circuit compiled for the `ResonatorFreeDevice`.
+///
+/// # Returns
+///
+/// * `Ok(Circuit)` - The circuit for the `DenebDevice`.
+/// * `Err(IqmBackendError::InvalidCircuit)` - A `ControlledPauliZ` gate cannot be implemented on
+///   the Deneb device.
+pub fn transpile_resonator_free_to_deneb(circuit: &Circuit) -> Result<Circuit, IqmBackendError> {
+    implement_cz_with_resonator(circuit, &DenebDevice::new())
+}
+
+/// Implement the `ControlledPauliZ` gates of a circuit via the resonators of a Deneb device.
+///
+/// Each gate is replaced by a `SingleExcitationStore` of the target qubit, a `CZQubitResonator`
+/// on the control qubit and a `SingleExcitationLoad` of the target qubit, using the first
+/// resonator coupled to both qubits. The circuits of `PragmaLoop` operations are rewritten as
+/// well, all other operations are kept as they are.
+///
+/// # Arguments
+///
+/// * `circuit` - The circuit to rewrite.
+/// * `device` - The Deneb device providing the resonators.
+///
+/// # Returns
+///
+/// * `Ok(Circuit)` - The rewritten circuit.
+/// * `Err(IqmBackendError::InvalidCircuit)` - The two qubits of a gate are not coupled to a
+///   common resonator.
+pub(crate) fn implement_cz_with_resonator(
+    circuit: &Circuit,
+    device: &DenebDevice,
+) -> Result<Circuit, IqmBackendError> {
+    let mut rewritten = Circuit::new();
+    for op in circuit.iter() {
+        match op {
+            Operation::ControlledPauliZ(o) => {
+                let (control, target) = (*o.control(), *o.target());
+                let resonator = device
+                    .resonator_couplings()
+                    .iter()
+                    .position(|qubits| qubits.contains(&control) && qubits.contains(&target))
+                    .filter(|_| control != target)
+                    .ok_or_else(|| IqmBackendError::InvalidCircuit {
+                        msg: format!(
+                            "ControlledPauliZ acting on qubits {} and {} cannot be implemented \
+                             on the Deneb device: the qubits are not coupled to a common \
+                             resonator.",
+                            control, target
+                        ),
+                    })?;
+                rewritten += SingleExcitationStore::new(target, resonator);
+                rewritten += CZQubitResonator::new(control, resonator);
+                rewritten += SingleExcitationLoad::new(target, resonator);
+            }
+            Operation::PragmaLoop(o) => {
+                rewritten += PragmaLoop::new(
+                    o.repetitions().clone(),
+                    implement_cz_with_resonator(o.circuit(), device)?,
+                )
+            }
+            _ => rewritten += op.clone(),
+        }
+    }
+    Ok(rewritten)
+}
+
+/// Decompose a `ControlledControlledPauliZ` gate into `RotateXY` and `ControlledPauliZ` gates.
+///
+/// The gate is symmetric in its three qubits, so it only requires one of the qubits to be
+/// coupled to the two others. The decomposition applies the phases of the gate to the parities of
+/// the three qubits with eight CNOTs between the central qubit and the outer ones, each built from
+/// a `ControlledPauliZ` and two `RotateXY` gates. The Z rotations are implemented with three
+/// `RotateXY` gates, since IQM devices do not have a native Z rotation.
+fn decompose_controlled_controlled_pauli_z<T: Device>(
+    op: &ControlledControlledPauliZ,
+    device: &T,
+) -> Result<Vec<Operation>, IqmBackendError> {
+    let qubits = [*op.control_0(), *op.control_1(), *op.target()];
+    let coupled = |x: usize, y: usize| {
+        device
+            .two_qubit_gate_time("ControlledPauliZ", &x, &y)
+            .is_some()
+    };
+    let (a, b, center) = [(0, 1, 2), (0, 2, 1), (1, 2, 0)]
+        .iter()
+        .map(|(i, j, k)| (qubits[*i], qubits[*j], qubits[*k]))
+        .find(|(a, b, center)| coupled(*a, *center) && coupled(*b, *center))
+        .ok_or_else(|| IqmBackendError::InvalidCircuit {
+            msg: format!(
+                "ControlledControlledPauliZ acting on qubits {}, {} and {} cannot be decomposed \
+                 on the device: none of the qubits is coupled to the two others.",
+                qubits[0], qubits[1], qubits[2]
+            ),
+        })?;
+
+    let t = PI / 4.0;
+    let mut ops = vec![];
+    ops.extend(rotate_z(a, t));
+    ops.extend(rotate_z(b, t));
+    ops.extend(rotate_z(center, t));
+    // center: a ^ center
+    ops.extend(cnot(a, center));
+    ops.extend(rotate_z(center, -t));
+    // b: a ^ b ^ center
+    ops.extend(cnot(center, b));
+    ops.extend(rotate_z(b, t));
+    // center: center, b: a ^ b
+    ops.extend(cnot(a, center));
+    ops.extend(cnot(center, b));
+    ops.extend(rotate_z(b, -t));
+    // center: a ^ center, b: b ^ center
+    ops.extend(cnot(a, center));
+    ops.extend(cnot(center, b));
+    ops.extend(rotate_z(b, -t));
+    // center: center, b: b
+    ops.extend(cnot(a, center));
+    ops.extend(cnot(center, b));
+    Ok(ops)
+}
+
+/// Rotation around the Z axis built from `RotateXY` gates.
+fn rotate_z(qubit: usize, theta: f64) -> [Operation; 3] {
+    [
+        RotateXY::new(qubit, (PI / 2.0).into(), 0.0.into()).into(),
+        RotateXY::new(qubit, theta.into(), (PI / 2.0).into()).into(),
+        RotateXY::new(qubit, (-PI / 2.0).into(), 0.0.into()).into(),
+    ]
+}
+
+/// CNOT gate built from a `ControlledPauliZ` and `RotateXY` gates on the target qubit.
+fn cnot(control: usize, target: usize) -> [Operation; 3] {
+    [
+        RotateXY::new(target, (-PI / 2.0).into(), (PI / 2.0).into()).into(),
+        ControlledPauliZ::new(control, target).into(),
+        RotateXY::new(target, (PI / 2.0).into(), (PI / 2.0).into()).into(),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::GarnetDevice;
+    use itertools::Itertools;
+    use num_complex::Complex64;
+    use std::collections::HashMap;
+
+    // Computes the unitary of a circuit of RotateXY and ControlledPauliZ gates acting on the given
+    // qubits, with the qubit at position i of `qubits` as bit i of the basis state index. With
+    // `resonator`, the resonator 0 is truncated to a single excitation and added as the last bit,
+    // with moves between qubits and the resonator acting as swaps.
+    fn circuit_unitary(
+        circuit: &Circuit,
+        qubits: &[usize],
+        resonator: bool,
+    ) -> Vec<Vec<Complex64>> {
+        let position: HashMap<usize, usize> =
+            qubits.iter().enumerate().map(|(i, q)| (*q, i)).collect();
+        let resonator_bit = 1 << qubits.len();
+        let dim = if resonator {
+            1 << (qubits.len() + 1)
+        } else {
+            1 << qubits.len()
+        };
+        let mut columns = vec![];
+        for k in 0..dim {
+            let mut state = vec![Complex64::new(0.0, 0.0); dim];
+            state[k] = Complex64::new(1.0, 0.0);
+            for op in circuit.iter() {
+                match op {
+                    Operation::RotateXY(o) => {
+                        let matrix = o.unitary_matrix().unwrap();
+                        let bit = 1 << position[o.qubit()];
+                        for i in (0..dim).filter(|i| i & bit == 0) {
+                            let (a, b) = (state[i], state[i | bit]);
+                            state[i] = matrix[[0, 0]] * a + matrix[[0, 1]] * b;
+                            state[i | bit] = matrix[[1, 0]] * a + matrix[[1, 1]] * b;
+                        }
+                    }
+                    Operation::ControlledPauliZ(o) => {
+                        let bits = (1 << position[o.control()]) | (1 << position[o.target()]);
+                        for (i, amplitude) in state.iter_mut().enumerate() {
+                            if i & bits == bits {
+                                *amplitude = -*amplitude
+                            }
+                        }
+                    }
+                    Operation::CZQubitResonator(o) if resonator => {
+                        let bits = (1 << position[o.qubit()]) | resonator_bit;
+                        for (i, amplitude) in state.iter_mut().enumerate() {
+                            if i & bits == bits {
+                                *amplitude = -*amplitude
+                            }
+                        }
+                    }
+                    Operation::SingleExcitationLoad(_) | Operation::SingleExcitationStore(_)
+                        if resonator =>
+                    {
+                        let qubit = match op {
+                            Operation::SingleExcitationLoad(o) => o.qubit(),
+                            Operation::SingleExcitationStore(o) => o.qubit(),
+                            _ => unreachable!(),
+                        };
+                        let bit = 1 << position[qubit];
+                        for i in (0..dim).filter(|i| i & bit != 0 && i & resonator_bit == 0) {
+                            state.swap(i, i ^ bit ^ resonator_bit);
+                        }
+                    }
+                    _ => panic!("Unexpected operation in decomposition: {:?}", op),
+                }
+            }
+            columns.push(state);
+        }
+        columns
+    }
+
+    #[test]
+    fn test_decompose_controlled_controlled_pauli_z() {
+        let device = GarnetDevice::new();
+        // Qubits 1 and 5 are both coupled to qubit 2, but not to each other
+        for qubits in [1, 2, 5].iter().permutations(3) {
+            let mut circuit = Circuit::new();
+            circuit += ControlledControlledPauliZ::new(*qubits[0], *qubits[1], *qubits[2]);
+            let decomposed = decompose_multi_qubit_gates(&circuit, &device).unwrap();
+
+            for op in decomposed.iter() {
+                if let Operation::ControlledPauliZ(o) = op {
+                    assert!(device
+                        .two_qubit_gate_time("ControlledPauliZ", o.control(), o.target())
+                        .is_some());
+                }
+            }
+
+            // Equal to the diagonal unitary of the gate up to a global phase
+            let unitary = circuit_unitary(&decomposed, &[1, 2, 5], false);
+            let phase = unitary[0][0];
+            for (column, state) in unitary.iter().enumerate() {
+                for (row, amplitude) in state.iter().enumerate() {
+                    let expected = match (row == column, row == 7) {
+                        (false, _) => Complex64::new(0.0, 0.0),
+                        (true, false) => phase,
+                        (true, true) => -phase,
+                    };
+                    assert!((amplitude - expected).norm() < 1e-10);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_decompose_controlled_controlled_pauli_z_disconnected() {
+        let device = GarnetDevice::new();
+        let mut circuit = Circuit::new();
+        circuit += ControlledControlledPauliZ::new(1, 5, 7);
+        assert!(matches!(
+            decompose_multi_qubit_gates(&circuit, &device),
+            Err(IqmBackendError::InvalidCircuit { .. })
+        ));
+    }
+
+    #[test]
+    fn test_transpile_resonator_free_to_deneb() {
+        let mut inner_circuit = Circuit::new();
+        inner_circuit += ControlledPauliZ::new(2, 0);
+        let mut circuit = Circuit::new();
+        circuit += RotateXY::new(0, 1.0.into(), 0.5.into());
+        circuit += ControlledPauliZ::new(0, 1);
+        circuit += RotateXY::new(1, 0.3.into(), 0.2.into());
+        circuit += ControlledPauliZ::new(1, 2);
+        circuit += PragmaLoop::new(2.into(), inner_circuit);
+
+        let transpiled = transpile_resonator_free_to_deneb(&circuit).unwrap();
+
+        // The loop is unrolled to compare the unitaries
+        let mut flat_circuit = Circuit::new();
+        let mut flat_transpiled = Circuit::new();
+        for (source, flat) in [
+            (&circuit, &mut flat_circuit),
+            (&transpiled, &mut flat_transpiled),
+        ] {
+            for op in source.iter() {
+                match op {
+                    Operation::PragmaLoop(o) => {
+                        for _ in 0..2 {
+                            *flat += o.circuit().clone();
+                        }
+                    }
+                    _ => *flat += op.clone(),
+                }
+            }
+        }
+
+        DenebDevice::new()
+            .validate_circuit(&flat_transpiled)
+            .unwrap();
+
+        // Equal on the subspace with an empty resonator, which stays empty
+        let expected = circuit_unitary(&flat_circuit, &[0, 1, 2], false);
+        let unitary = circuit_unitary(&flat_transpiled, &[0, 1, 2], true);
+        for (column, state) in expected.iter().enumerate() {
+            for (row, amplitude) in unitary[column].iter().enumerate() {
+                let expected_amplitude = state.get(row).copied().unwrap_or_default();
+                assert!((amplitude - expected_amplitude).norm() < 1e-10);
+            }
+        }
+    }
+
+    #[test]
+    fn test_transpile_resonator_free_to_deneb_invalid() {
+        let mut circuit = Circuit::new();
+        circuit += ControlledPauliZ::new(1, 6);
+        assert!(matches!(
+            transpile_resonator_free_to_deneb(&circuit),
+            Err(IqmBackendError::InvalidCircuit { .. })
+        ));
+    }
+
+    #[test]
+    fn test_implement_cz_with_resonator_couplings() {
+        let mut device = DenebDevice::new();
+        device.set_resonator_couplings(vec![vec![0, 1, 2], vec![2, 3, 4]]);
+
+        let mut circuit = Circuit::new();
+        circuit += ControlledPauliZ::new(3, 2);
+        let rewritten = implement_cz_with_resonator(&circuit, &device).unwrap();
+        let mut expected = Circuit::new();
+        expected += SingleExcitationStore::new(2, 1);
+        expected += CZQubitResonator::new(3, 1);
+        expected += SingleExcitationLoad::new(2, 1);
+        assert_eq!(rewritten, expected);
+
+        let mut circuit = Circuit::new();
+        circuit += ControlledPauliZ::new(0, 4);
+        assert!(matches!(
+            implement_cz_with_resonator(&circuit, &device),
+            Err(IqmBackendError::InvalidCircuit { .. })
+        ));
+    }
+}
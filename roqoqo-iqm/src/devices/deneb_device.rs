@@ -15,16 +15,26 @@ use roqoqo::devices::{Device, GenericDevice};
 use roqoqo::operations::{Operation, SingleQubitOperation};
 use roqoqo::prelude::*;
 use roqoqo::{Circuit, RoqoqoBackendError};
+use std::collections::HashMap;
 
 use crate::IqmBackendError;
 
 /// IQM Deneb device
 ///
 /// A hardware device composed of six qubits each coupled to a central resonator.
+///
+/// The resonator couplings can be changed with [DenebDevice::set_resonator_couplings] to describe
+/// devices with several computational resonators.
 #[derive(Debug, PartialEq, Eq, Clone, serde::Serialize, serde::Deserialize)]
 pub struct DenebDevice {
     url: String,
     name: String,
+    /// For each computational resonator, the qubits coupled to it
+    #[serde(default = "default_resonator_couplings")]
+    resonator_couplings: Vec<Vec<usize>>,
+    /// Maximum number of moves between qubits and resonators allowed in a circuit
+    #[serde(default)]
+    max_resonator_moves: Option<usize>,
 }
 
 impl DenebDevice {
@@ -33,6 +43,8 @@ impl DenebDevice {
         Self {
             url: "https://cocos.resonance.meetiqm.com/deneb/jobs".to_string(),
             name: "Deneb".to_string(),
+            resonator_couplings: default_resonator_couplings(),
+            max_resonator_moves: None,
         }
     }
 
@@ -55,12 +67,81 @@ impl DenebDevice {
         self.url = new_url
     }
 
+    /// Returns the number of computational resonators of the device.
+    pub fn number_resonators(&self) -> usize {
+        self.resonator_couplings.len()
+    }
+
+    /// Returns, for each computational resonator, the qubits coupled to it.
+    pub fn resonator_couplings(&self) -> &[Vec<usize>] {
+        &self.resonator_couplings
+    }
+
+    /// Change the computational resonators of the device and the qubits coupled to them.
+    ///
+    /// The resonators are identified by their index in `resonator_couplings`. The default is a
+    /// single resonator coupled to all six qubits.
+    ///
+    /// # Arguments
+    ///
+    /// * `resonator_couplings` - For each resonator, the qubits coupled to it.
+    pub fn set_resonator_couplings(&mut self, resonator_couplings: Vec<Vec<usize>>) {
+        self.resonator_couplings = resonator_couplings
+    }
+
+    /// Returns the maximum number of moves between qubits and resonators allowed in a circuit.
+    pub fn max_resonator_moves(&self) -> Option<usize> {
+        self.max_resonator_moves
+    }
+
+    /// Limit the number of moves between qubits and resonators (`SingleExcitationLoad` and
+    /// `SingleExcitationStore` operations) allowed in a circuit.
+    ///
+    /// Circuits with many moves are dominated by decoherence. By default there is no limit.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_resonator_moves` - The maximum number of moves, or None for no limit.
+    pub fn set_max_resonator_moves(&mut self, max_resonator_moves: Option<usize>) {
+        self.max_resonator_moves = max_resonator_moves
+    }
+
+    /// Check that a qubit-resonator operation acts on an existing resonator coupled to the qubit.
+    fn validate_resonator_coupling(
+        &self,
+        hqslang: &str,
+        qubit: usize,
+        resonator: usize,
+    ) -> Result<(), IqmBackendError> {
+        match self.resonator_couplings.get(resonator) {
+            None => Err(IqmBackendError::InvalidCircuit {
+                msg: format!(
+                    "Wrong resonator index {} in operation {}. DenebDevice has {} resonator(s).",
+                    resonator,
+                    hqslang,
+                    self.number_resonators()
+                ),
+            }),
+            Some(coupled_qubits) if !coupled_qubits.contains(&qubit) => {
+                Err(IqmBackendError::InvalidCircuit {
+                    msg: format!(
+                        "Qubit {} in operation {} is not coupled to resonator {}.",
+                        qubit, hqslang, resonator
+                    ),
+                })
+            }
+            Some(_) => Ok(()),
+        }
+    }
+
     /// Validate the circuit to be run for Deneb's architecture.
     ///
     /// This involves checking
     /// 1) The device's connectivity
     /// 2) The presence of subsequent Load operations or subsequent Store operations, which are not
-    /// allowed since only a single excitation can be stored in the resonator at any time.
+    ///    allowed since only a single excitation can be stored in the resonator at any time.
+    /// 3) The number of Load and Store operations, if a limit is set with
+    ///    [DenebDevice::set_max_resonator_moves].
     ///
     /// # Arguments
     ///
@@ -72,6 +153,33 @@ impl DenebDevice {
     pub fn validate_circuit(&self, circuit: &Circuit) -> Result<(), IqmBackendError> {
         self.validate_circuit_connectivity(circuit)?;
         self.validate_circuit_load_store(circuit)?;
+        self.validate_circuit_resonator_moves(circuit)?;
+        Ok(())
+    }
+
+    /// Validate that the circuit does not exceed the maximum number of moves between qubits and
+    /// resonators.
+    ///
+    /// # Arguments
+    ///
+    /// * `circuit` - The circuit to be validated.
+    ///
+    /// # Returns
+    ///
+    /// * `Err(RoqoqoBackendError)` - The circuit is invalid.
+    fn validate_circuit_resonator_moves(&self, circuit: &Circuit) -> Result<(), IqmBackendError> {
+        if let Some(max_moves) = self.max_resonator_moves {
+            let moves = count_resonator_moves(circuit);
+            if moves > max_moves {
+                return Err(IqmBackendError::InvalidCircuit {
+                    msg: format!(
+                        "Circuit contains {} moves between qubits and resonators, but at most {} \
+                         are allowed on the device.",
+                        moves, max_moves
+                    ),
+                });
+            }
+        }
         Ok(())
     }
 
@@ -81,7 +189,9 @@ impl DenebDevice {
     /// 1) Multiple subsequent SingleExcitatoinLoad.
     /// 2) Multiple subsequent SingleExcitatoinStore.
     /// 3) A combination like Store - RotateXY - Load where the qubit involved in all three
-    /// operations is the same.
+    ///    operations is the same.
+    /// 4) A Load from a resonator before any Store to it, since the resonators are empty at the
+    ///    start of the circuit.
     ///
     /// # Arguments
     ///
@@ -97,26 +207,35 @@ impl DenebDevice {
             Zero,
         }
 
-        let mut state = State::Zero;
-        let mut stored_qubit: Option<usize> = None;
-        let mut qubit_rotated = false;
+        // Each resonator can hold a single excitation, so the state is tracked per resonator
+        struct ResonatorState {
+            state: State,
+            stored_qubit: Option<usize>,
+            qubit_rotated: bool,
+        }
+
+        let mut resonators: HashMap<usize, ResonatorState> = HashMap::new();
 
         for op in circuit.iter() {
             match op {
                 Operation::SingleExcitationLoad(o) => {
-                    match state {
-                        State::FoundStore => {
-                            if qubit_rotated {
-                                let loaded_qubit = o.qubit();
-                                if let Some(stored) = stored_qubit {
-                                    if *loaded_qubit == stored {
-                                        return Err(IqmBackendError::InvalidCircuit {
-                                            msg: format!(
-                                                "Circuit tries to rotate qubit {} before loading an \
-                                                 excitation into it from the resonator.",
-                                                loaded_qubit)
-                                        });
-                                    }
+                    let resonator = resonators.entry(*o.mode()).or_insert(ResonatorState {
+                        state: State::Zero,
+                        stored_qubit: None,
+                        qubit_rotated: false,
+                    });
+                    match resonator.state {
+                        State::FoundStore if resonator.qubit_rotated => {
+                            let loaded_qubit = o.qubit();
+                            if let Some(stored) = resonator.stored_qubit {
+                                if *loaded_qubit == stored {
+                                    return Err(IqmBackendError::InvalidCircuit {
+                                        msg: format!(
+                                            "Circuit tries to rotate qubit {} before loading an \
+                                             excitation into it from the resonator.",
+                                            loaded_qubit
+                                        ),
+                                    });
                                 }
                             }
                         }
@@ -126,26 +245,44 @@ impl DenebDevice {
                                     .to_string(),
                             })
                         }
+                        // The resonator is empty at the start of the circuit
+                        State::Zero => {
+                            return Err(IqmBackendError::InvalidCircuit {
+                                msg: format!(
+                                    "Circuit tries to load from resonator {} before storing an \
+                                     excitation in it.",
+                                    o.mode()
+                                ),
+                            })
+                        }
                         _ => {}
                     }
-                    state = State::FoundLoad;
+                    resonator.state = State::FoundLoad;
                 }
                 Operation::SingleExcitationStore(o) => {
-                    if let State::FoundStore = state {
+                    let resonator = resonators.entry(*o.mode()).or_insert(ResonatorState {
+                        state: State::Zero,
+                        stored_qubit: None,
+                        qubit_rotated: false,
+                    });
+                    if let State::FoundStore = resonator.state {
                         return Err(IqmBackendError::InvalidCircuit {
                             msg: "Circuit tries to store two excitations in the resonator."
                                 .to_string(),
                         });
                     }
-                    stored_qubit = Some(*o.qubit());
-                    state = State::FoundStore;
+                    resonator.stored_qubit = Some(*o.qubit());
+                    resonator.qubit_rotated = false;
+                    resonator.state = State::FoundStore;
                 }
+                // Virtual Z rotations do not act on the qubit, see [crate::call_circuit]
+                Operation::RotateZ(_) => (),
                 _ => {
-                    if let Some(stored) = stored_qubit {
-                        if let Ok(inner_op) = SingleQubitOperation::try_from(op) {
-                            let qubit = inner_op.qubit();
-                            if stored == *qubit {
-                                qubit_rotated = true;
+                    if let Ok(inner_op) = SingleQubitOperation::try_from(op) {
+                        let qubit = inner_op.qubit();
+                        for resonator in resonators.values_mut() {
+                            if resonator.stored_qubit == Some(*qubit) {
+                                resonator.qubit_rotated = true;
                             }
                         }
                     }
@@ -155,6 +292,23 @@ impl DenebDevice {
         Ok(())
     }
 
+    /// Check that a qubit an operation acts on is part of the device.
+    fn validate_qubit(&self, hqslang: &str, qubit: usize) -> Result<(), IqmBackendError> {
+        if qubit >= self.number_qubits() {
+            return Err(IqmBackendError::InvalidCircuit {
+                msg: format!(
+                    "Too many qubits involved in the circuit: 
+                                    Found {} acting on qubit: {} 
+                                    Qubits in Deneb device: {}",
+                    hqslang,
+                    qubit,
+                    self.number_qubits()
+                ),
+            });
+        }
+        Ok(())
+    }
+
     /// Validate the circuit's connectivity for Deneb's architecture.
     ///
     /// # Arguments
@@ -171,73 +325,23 @@ impl DenebDevice {
             "MeasureQubit",
             "DefinitionBit",
             "InputBit",
+            "PragmaStopParallelBlock",
         ];
 
         for op in circuit.iter() {
             match op {
-                Operation::RotateXY(o) => {
-                    let qubit = *o.qubit();
-                    if qubit >= self.number_qubits() {
-                        return Err(IqmBackendError::InvalidCircuit {
-                            msg: format!(
-                                "Too many qubits involved in the circuit: 
-                                    Found {} acting on qubit: {} 
-                                    Qubits in Deneb device: {}",
-                                op.hqslang(),
-                                qubit,
-                                self.number_qubits()
-                            ),
-                        });
-                    }
-                }
+                Operation::RotateXY(o) => self.validate_qubit(op.hqslang(), *o.qubit())?,
+                Operation::RotateZ(o) => self.validate_qubit(op.hqslang(), *o.qubit())?,
                 Operation::CZQubitResonator(o) => {
                     let qubit = *o.qubit();
-                    let resonator = *o.mode();
-                    if qubit >= self.number_qubits() {
-                        return Err(IqmBackendError::InvalidCircuit {
-                            msg: format!(
-                                "Too many qubits involved in the circuit: 
-                                    Found {} acting on qubit: {} 
-                                    Qubits in Deneb device: {}",
-                                op.hqslang(),
-                                qubit,
-                                self.number_qubits()
-                            ),
-                        });
-                    }
-                    if resonator != 0 {
-                        return Err(IqmBackendError::InvalidCircuit {
-                            msg: format!(
-                                "Wrong resonator index in operation {}. DenebDevice has a single \
-                                resonator with index 0.",
-                                op.hqslang()
-                            ),
-                        });
-                    }
+                    self.validate_qubit(op.hqslang(), qubit)?;
+                    self.validate_resonator_coupling(op.hqslang(), qubit, *o.mode())?;
                 }
                 Operation::SingleExcitationLoad(o) => {
-                    let resonator = *o.mode();
-                    if resonator != 0 {
-                        return Err(IqmBackendError::InvalidCircuit {
-                            msg: format!(
-                                "Wrong resonator index in operation {}. DenebDevice has a single \
-                                resonator with index 0.",
-                                op.hqslang()
-                            ),
-                        });
-                    }
+                    self.validate_resonator_coupling(op.hqslang(), *o.qubit(), *o.mode())?;
                 }
                 Operation::SingleExcitationStore(o) => {
-                    let resonator = *o.mode();
-                    if resonator != 0 {
-                        return Err(IqmBackendError::InvalidCircuit {
-                            msg: format!(
-                                "Wrong resonator index in operation {}. DenebDevice has a single \
-                                resonator with index 0.",
-                                op.hqslang()
-                            ),
-                        });
-                    }
+                    self.validate_resonator_coupling(op.hqslang(), *o.qubit(), *o.mode())?;
                 }
                 _ => {
                     if !allowed_measurement_ops.contains(&op.hqslang()) {
@@ -255,6 +359,29 @@ impl DenebDevice {
     }
 }
 
+/// The single computational resonator of Deneb, coupled to all six qubits.
+///
+/// Also used when deserializing devices serialized before the resonator couplings were added.
+fn default_resonator_couplings() -> Vec<Vec<usize>> {
+    vec![(0..6).collect()]
+}
+
+/// Count the SingleExcitationLoad and SingleExcitationStore operations of a circuit, including
+/// the repetitions of loops.
+fn count_resonator_moves(circuit: &Circuit) -> usize {
+    circuit
+        .iter()
+        .map(|op| match op {
+            Operation::SingleExcitationLoad(_) | Operation::SingleExcitationStore(_) => 1,
+            Operation::PragmaLoop(o) => {
+                let repetitions = o.repetitions().float().map(|x| *x as usize).unwrap_or(1);
+                repetitions * count_resonator_moves(o.circuit())
+            }
+            _ => 0,
+        })
+        .sum()
+}
+
 /// Implements the Device trait for DenebDevice.
 ///
 /// Defines standard functions available for roqoqo-iqm devices.
@@ -272,10 +399,14 @@ impl Device for DenebDevice {
     /// * `Some<f64>` - The gate time.
     /// * `None` - The gate is not available on the device.
     fn single_qubit_gate_time(&self, hqslang: &str, qubit: &usize) -> Option<f64> {
-        if hqslang == "RotateXY" && qubit < &self.number_qubits() {
-            Some(1.0)
-        } else {
-            None
+        if qubit >= &self.number_qubits() {
+            return None;
+        }
+        match hqslang {
+            "RotateXY" => Some(1.0),
+            // Z rotations are virtual, see [crate::call_circuit]
+            "RotateZ" => Some(0.0),
+            _ => None,
         }
     }
 
@@ -286,7 +417,7 @@ impl Device for DenebDevice {
     /// Returns the gate time of a qubit-resonator operation if the operation is available on device.
     ///
     /// Note that in this method the control qubit is the actual qubit involved, while the target
-    /// qubit corresponds to the central resonator of the Deneb device.
+    /// qubit corresponds to the index of the computational resonator of the Deneb device.
     ///
     /// # Arguments
     ///
@@ -299,33 +430,15 @@ impl Device for DenebDevice {
     /// * `Some<f64>` - The gate time.
     /// * `None` - The gate is not available on the device.
     fn two_qubit_gate_time(&self, hqslang: &str, control: &usize, target: &usize) -> Option<f64> {
-        if target == &0_usize {
-            match hqslang {
-                "CZQubitResonator" => {
-                    if control < &self.number_qubits() {
-                        Some(1.0)
-                    } else {
-                        None
-                    }
-                }
-                "SingleExcitationLoad" => {
-                    if control < &self.number_qubits() {
-                        Some(1.0)
-                    } else {
-                        None
-                    }
-                }
-                "SingleExcitationStore" => {
-                    if control < &self.number_qubits() {
-                        Some(1.0)
-                    } else {
-                        None
-                    }
-                }
-                _ => None,
+        let coupled = self
+            .resonator_couplings
+            .get(*target)
+            .map_or(false, |qubits| qubits.contains(control));
+        match hqslang {
+            "CZQubitResonator" | "SingleExcitationLoad" | "SingleExcitationStore" if coupled => {
+                Some(1.0)
             }
-        } else {
-            None
+            _ => None,
         }
     }
 
@@ -478,7 +591,7 @@ impl Default for DenebDevice {
 mod tests {
     use super::*;
     use roqoqo::operations::{
-        CZQubitResonator, DefinitionBit, InputBit, RotateXY, SingleExcitationLoad,
+        CZQubitResonator, DefinitionBit, InputBit, PragmaLoop, RotateXY, SingleExcitationLoad,
         SingleExcitationStore,
     };
 
@@ -531,6 +644,111 @@ mod tests {
         assert!(err.is_err());
     }
 
+    #[test]
+    fn test_validate_circuit_max_resonator_moves() {
+        let mut device = DenebDevice::new();
+        assert_eq!(device.max_resonator_moves(), None);
+
+        let mut circuit = Circuit::new();
+        circuit += SingleExcitationStore::new(0, 0);
+        circuit += CZQubitResonator::new(1, 0);
+        circuit += SingleExcitationLoad::new(0, 0);
+        circuit += SingleExcitationStore::new(2, 0);
+        circuit += CZQubitResonator::new(3, 0);
+        circuit += SingleExcitationLoad::new(2, 0);
+        assert!(device.validate_circuit(&circuit).is_ok());
+
+        device.set_max_resonator_moves(Some(3));
+        assert!(matches!(
+            device.validate_circuit(&circuit),
+            Err(IqmBackendError::InvalidCircuit { .. })
+        ));
+
+        device.set_max_resonator_moves(Some(4));
+        assert!(device.validate_circuit(&circuit).is_ok());
+
+        // Moves in loops are counted for each repetition
+        let mut loop_circuit = Circuit::new();
+        loop_circuit += SingleExcitationStore::new(0, 0);
+        loop_circuit += SingleExcitationLoad::new(0, 0);
+        let mut circuit = Circuit::new();
+        circuit += PragmaLoop::new(4.into(), loop_circuit);
+        assert!(device.validate_circuit(&circuit).is_err());
+    }
+
+    #[test]
+    fn test_validate_circuit_two_resonators() {
+        let mut device = DenebDevice::new();
+        device.set_resonator_couplings(vec![vec![0, 1, 2], vec![3, 4, 5]]);
+        assert_eq!(device.number_resonators(), 2);
+
+        // Each resonator can hold an excitation at the same time
+        let mut circuit = Circuit::new();
+        circuit += RotateXY::new(0, 0.5.into(), 0.2.into());
+        circuit += SingleExcitationStore::new(0, 0);
+        circuit += SingleExcitationStore::new(4, 1);
+        circuit += CZQubitResonator::new(2, 0);
+        circuit += CZQubitResonator::new(3, 1);
+        circuit += SingleExcitationLoad::new(0, 0);
+        circuit += SingleExcitationLoad::new(4, 1);
+        assert!(device.validate_circuit(&circuit).is_ok());
+        assert_eq!(
+            device.two_qubit_gate_time("CZQubitResonator", &3, &1),
+            Some(1.0)
+        );
+        assert_eq!(device.two_qubit_gate_time("CZQubitResonator", &3, &0), None);
+
+        // Qubit 3 is not coupled to resonator 0
+        let mut circuit = Circuit::new();
+        circuit += CZQubitResonator::new(3, 0);
+        assert!(device.validate_circuit(&circuit).is_err());
+
+        // There is no third resonator
+        let mut circuit = Circuit::new();
+        circuit += SingleExcitationStore::new(0, 2);
+        assert!(device.validate_circuit(&circuit).is_err());
+
+        // Storing twice in the same resonator is still invalid
+        let mut circuit = Circuit::new();
+        circuit += SingleExcitationStore::new(3, 1);
+        circuit += SingleExcitationStore::new(4, 1);
+        assert!(device.validate_circuit(&circuit).is_err());
+    }
+
+    #[test]
+    fn test_validate_circuit_resonator_index() {
+        let mut circuit = Circuit::new();
+        circuit += CZQubitResonator::new(0, 1);
+
+        // Only resonator 0 exists by default
+        let mut device = DenebDevice::new();
+        assert!(matches!(
+            device.validate_circuit(&circuit),
+            Err(IqmBackendError::InvalidCircuit { .. })
+        ));
+
+        device.set_resonator_couplings(vec![(0..6).collect(), (0..6).collect()]);
+        assert!(device.validate_circuit(&circuit).is_ok());
+    }
+
+    #[test]
+    fn test_validate_circuit_leading_load() {
+        let device = DenebDevice::new();
+        let mut circuit = Circuit::new();
+
+        circuit += RotateXY::new(5, 0.5.into(), 0.5.into());
+        circuit += SingleExcitationLoad::new(5, 0);
+        circuit += RotateXY::new(5, 0.5.into(), 0.3.into());
+        circuit += SingleExcitationStore::new(5, 0);
+
+        match device.validate_circuit(&circuit) {
+            Err(IqmBackendError::InvalidCircuit { msg }) => {
+                assert!(msg.contains("before storing an excitation"))
+            }
+            _ => panic!("Expected an InvalidCircuit error"),
+        }
+    }
+
     #[test]
     fn test_validate_circuit_invalid_rotation() {
         let device = DenebDevice::new();
@@ -544,4 +762,13 @@ mod tests {
         let err = device.validate_circuit(&circuit);
         assert!(err.is_err());
     }
+
+    #[test]
+    fn test_deserialize_without_resonator_couplings() {
+        let serialized =
+            r#"{"url":"https://cocos.resonance.meetiqm.com/deneb/jobs","name":"Deneb"}"#;
+        let device: DenebDevice = serde_json::from_str(serialized).unwrap();
+        assert_eq!(device, DenebDevice::new());
+        assert_eq!(device.number_resonators(), 1);
+    }
 }
@@ -68,10 +68,14 @@ impl Device for GarnetDevice {
     /// * `Some<f64>` - The gate time.
     /// * `None` - The gate is not available on the device.
     fn single_qubit_gate_time(&self, hqslang: &str, qubit: &usize) -> Option<f64> {
-        if hqslang == "RotateXY" && qubit < &self.number_qubits() {
-            Some(1.0)
-        } else {
-            None
+        if qubit >= &self.number_qubits() {
+            return None;
+        }
+        match hqslang {
+            "RotateXY" => Some(1.0),
+            // Z rotations are virtual, see [crate::call_circuit]
+            "RotateZ" => Some(0.0),
+            _ => None,
         }
     }
 
@@ -47,6 +47,24 @@ impl IqmDevice {
         }
     }
 
+    /// Replace the endpoint URL of the device, unless it has been changed from the default URL of
+    /// the device with `set_endpoint_url`.
+    pub(crate) fn override_default_endpoint_url(&mut self, new_url: String) {
+        match self {
+            IqmDevice::DenebDevice(x) => {
+                if x.remote_host() == DenebDevice::new().remote_host() {
+                    x.set_endpoint_url(new_url)
+                }
+            }
+            IqmDevice::ResonatorFreeDevice(_) => (),
+            IqmDevice::GarnetDevice(x) => {
+                if x.remote_host() == GarnetDevice::new().remote_host() {
+                    x.set_endpoint_url(new_url)
+                }
+            }
+        }
+    }
+
     /// Returns the name of the device.
     pub fn name(&self) -> String {
         match self {
@@ -55,6 +73,63 @@ impl IqmDevice {
             IqmDevice::GarnetDevice(x) => x.name(),
         }
     }
+
+    /// Returns the maximum number of shots accepted by the device in a single job.
+    pub fn max_shots(&self) -> usize {
+        match self {
+            IqmDevice::DenebDevice(_) => 10000,
+            IqmDevice::ResonatorFreeDevice(_) => 10000,
+            IqmDevice::GarnetDevice(_) => 10000,
+        }
+    }
+
+    /// Returns the hqslang name of the native two-qubit gate of the device.
+    ///
+    /// Useful for transpilers targeting the device. On Deneb, qubits interact only through the
+    /// computational resonator.
+    pub fn native_two_qubit_gate(&self) -> &'static str {
+        match self {
+            IqmDevice::DenebDevice(_) => "CZQubitResonator",
+            IqmDevice::ResonatorFreeDevice(_) => "ControlledPauliZ",
+            IqmDevice::GarnetDevice(_) => "ControlledPauliZ",
+        }
+    }
+
+    /// Returns the granularity of the shot counts accepted by the device.
+    ///
+    /// The device only accepts jobs whose number of shots is a multiple of the granularity.
+    pub fn shot_granularity(&self) -> usize {
+        match self {
+            IqmDevice::DenebDevice(_) => 100,
+            IqmDevice::ResonatorFreeDevice(_) => 1,
+            IqmDevice::GarnetDevice(_) => 1,
+        }
+    }
+
+    /// Round a number of shots to the nearest number of shots accepted by the device.
+    ///
+    /// The result is a multiple of [IqmDevice::shot_granularity], at least one granularity step and
+    /// at most [IqmDevice::max_shots].
+    ///
+    /// # Arguments
+    ///
+    /// * `requested` - The requested number of shots.
+    pub fn round_shots(&self, requested: usize) -> usize {
+        let granularity = self.shot_granularity();
+        let rounded = (requested + granularity / 2) / granularity * granularity;
+        rounded
+            .max(granularity)
+            .min(self.max_shots() / granularity * granularity)
+    }
+
+    /// Returns the number of shots used for circuits that do not define a number of measurements.
+    pub fn default_shots(&self) -> usize {
+        match self {
+            IqmDevice::DenebDevice(_) => 1000,
+            IqmDevice::ResonatorFreeDevice(_) => 100,
+            IqmDevice::GarnetDevice(_) => 1000,
+        }
+    }
 }
 
 impl From<&DenebDevice> for IqmDevice {
@@ -267,3 +342,25 @@ impl Device for IqmDevice {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_override_default_endpoint_url() {
+        let url = "https://staging.example.com/garnet/jobs".to_string();
+
+        // The default URL of the device is replaced
+        let mut device: IqmDevice = GarnetDevice::new().into();
+        device.override_default_endpoint_url(url.clone());
+        assert_eq!(device.remote_host(), url);
+
+        // An explicitly set URL takes precedence over the override
+        let mut deneb = DenebDevice::new();
+        deneb.set_endpoint_url("https://other.example.com/deneb/jobs".to_string());
+        let mut device: IqmDevice = deneb.into();
+        device.override_default_endpoint_url(url);
+        assert_eq!(device.remote_host(), "https://other.example.com/deneb/jobs");
+    }
+}
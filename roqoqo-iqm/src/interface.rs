@@ -12,12 +12,12 @@
 
 use itertools::Itertools;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::f64::consts::PI;
 
 use qoqo_calculator::CalculatorFloat;
 use roqoqo::operations::*;
-use roqoqo::RoqoqoBackendError;
+use roqoqo::{Circuit, RoqoqoBackendError};
 
 use crate::IqmBackendError;
 
@@ -27,9 +27,8 @@ use crate::IqmBackendError;
 pub(crate) type MeasuredQubitsMap = HashMap<String, (Vec<usize>, usize)>;
 
 // Pragma operations that are ignored by backend and do not throw an error
-const ALLOWED_OPERATIONS: &[&str; 8] = &[
+const ALLOWED_OPERATIONS: &[&str; 7] = &[
     "PragmaBoostNoise",
-    "PragmaStopParallelBlock",
     "PragmaGlobalPhase",
     "InputSymbolic",
     "InputBit",
@@ -68,20 +67,30 @@ pub struct IqmInstruction {
 
 /// Converts all operations in a [roqoqo::Circuit] into instructions for IQM Hardware.
 ///
+/// [roqoqo::operations::RotateZ] operations are not submitted but applied as virtual Z rotations:
+/// the angle of the Z rotations acting on each qubit is accumulated and subtracted from the phase
+/// of all subsequent `prx` instructions on the qubit, since `RotateZ(alpha)` followed by
+/// `RotateXY(theta, phi)` equals `RotateXY(theta, phi - alpha)` followed by `RotateZ(alpha)`. The
+/// remaining Z rotations commute with `cz` instructions and measurements and are dropped. The
+/// accumulated angle of a qubit moves with its excitation into a computational resonator and
+/// back.
+///
 /// # Arguments
 ///
 /// * `circuit` - The [roqoqo::Circuit] that is converted
 /// * `device_number_qubits` - The number of qubits of the backend device. It is used to know how
-///    many qubits to measure with [roqoqo::operations::PragmaRepeatedMeasurement]
+///   many qubits to measure with [roqoqo::operations::PragmaRepeatedMeasurement], whose register
+///   must be at least as long when no qubit mapping is given. Qubits measured with
+///   [roqoqo::operations::MeasureQubit] must be smaller than this number
 /// * `number_measurements_internal` - If set, the number of measurements that has been overwritten
-/// in the backend
+///   in the backend
 /// * `circuit_index` - Index of the circuit in the batch, needed to assign a unique name to the circuit.
 ///
 /// # Returns
 ///
 /// * `Ok(IqmCircuit, usize)` - Converted circuit and number of measurements
-/// * `Err(RoqoqoBackendError::OperationNotInBackend)` - Error when [roqoqo::operations::Operation]
-///    can not be converted
+/// * `Err(IqmBackendError::InvalidCircuit)` - Error when [roqoqo::operations::Operation] can not be
+///   converted, naming the position of the operation in the circuit
 pub fn call_circuit<'a>(
     circuit: impl Iterator<Item = &'a Operation>,
     device_number_qubits: usize,
@@ -92,16 +101,38 @@ pub fn call_circuit<'a>(
     let mut number_measurements: usize = 1;
     let mut measured_qubits: Vec<usize> = vec![];
     let mut measured_qubits_map: MeasuredQubitsMap = HashMap::new();
+    // Index in circuit_vec of the measure instruction of each register
+    let mut measure_indices: HashMap<String, usize> = HashMap::new();
+    // Registers that are defined but not marked as output, only used for error messages
+    let mut non_output_registers: HashSet<String> = HashSet::new();
+    let mut virtual_z = VirtualZPhases::default();
 
-    for op in circuit {
+    for (position, op) in circuit.enumerate() {
         match op {
             Operation::DefinitionBit(o) => {
                 let name = (*o).name().to_string();
                 if *o.is_output() {
                     measured_qubits_map.insert(name, (vec![], *o.length()));
+                } else {
+                    non_output_registers.insert(name);
                 }
             }
             Operation::MeasureQubit(o) => {
+                if *o.qubit() >= device_number_qubits {
+                    return Err(IqmBackendError::InvalidCircuit {
+                        msg: format!(
+                            "MeasureQubit acts on qubit {}, but the device has only {} qubits.",
+                            o.qubit(),
+                            device_number_qubits
+                        ),
+                    });
+                }
+                _check_output_register(
+                    op,
+                    o.readout(),
+                    &measured_qubits_map,
+                    &non_output_registers,
+                )?;
                 let readout = o.readout().clone();
                 measured_qubits.push(*o.qubit());
 
@@ -114,37 +145,29 @@ pub fn call_circuit<'a>(
                         })
                     }
                 }
-                let mut found: bool = false;
                 // Check if we already have a measurement to the same register
                 // if yes, add the qubit being measured to that measurement
-                for instr in circuit_vec.iter_mut().filter(|x| x.name == "measure") {
-                    let meas_readout = instr.args.get("key").expect(
-                        "An IqmInstruction measurement must contain a `key` entry in \
-                         the `args` field.",
-                    );
-                    if let CalculatorFloat::Str(s) = meas_readout {
-                        if s == &readout {
-                            found = true;
-                            let iqm_qubit = _convert_qubit_name_qoqo_to_iqm(*o.qubit());
-                            if !instr.qubits.contains(&iqm_qubit) {
-                                instr.qubits.push(iqm_qubit);
-                            } else {
-                                return Err(IqmBackendError::InvalidCircuit {
-                                    msg: format!("Qubit {} is being measured twice.", *o.qubit()),
-                                });
-                            }
-                            break;
+                let iqm_qubit = _convert_qubit_name_qoqo_to_iqm(*o.qubit());
+                match measure_indices.get(&readout) {
+                    Some(index) => {
+                        let instr = &mut circuit_vec[*index];
+                        if instr.qubits.contains(&iqm_qubit) {
+                            return Err(IqmBackendError::InvalidCircuit {
+                                msg: format!("Qubit {} is being measured twice.", *o.qubit()),
+                            });
                         }
+                        instr.qubits.push(iqm_qubit);
+                    }
+                    None => {
+                        // If no measurement to the same register was found, create a new IqmInstruction
+                        measure_indices.insert(readout.clone(), circuit_vec.len());
+                        let meas = IqmInstruction {
+                            name: "measure".to_string(),
+                            qubits: vec![iqm_qubit],
+                            args: HashMap::from([("key".to_string(), readout.into())]),
+                        };
+                        circuit_vec.push(meas)
                     }
-                }
-                if !found {
-                    // If no measurement to the same register was found, create a new IqmInstruction
-                    let meas = IqmInstruction {
-                        name: "measure".to_string(),
-                        qubits: vec![_convert_qubit_name_qoqo_to_iqm(*o.qubit())],
-                        args: HashMap::from([("key".to_string(), readout.into())]),
-                    };
-                    circuit_vec.push(meas)
                 }
             }
             Operation::PragmaSetNumberOfMeasurements(o) => {
@@ -157,6 +180,12 @@ pub fn call_circuit<'a>(
                 }
                 number_measurements = *o.number_measurements();
 
+                _check_output_register(
+                    op,
+                    o.readout(),
+                    &measured_qubits_map,
+                    &non_output_registers,
+                )?;
                 let readout = o.readout().clone();
                 let readout_register = measured_qubits_map.get(&readout);
 
@@ -193,6 +222,7 @@ pub fn call_circuit<'a>(
                             .insert(readout.clone(), (measured_qubits.clone(), readout_length));
 
                         // add single measurement instruction for all the qubits that were measured with MeasureQubit
+                        measure_indices = HashMap::from([(readout.clone(), circuit_vec.len())]);
                         let meas = IqmInstruction {
                             name: "measure".to_string(),
                             qubits: measured_qubits
@@ -220,6 +250,12 @@ pub fn call_circuit<'a>(
                     });
                 }
 
+                _check_output_register(
+                    op,
+                    o.readout(),
+                    &measured_qubits_map,
+                    &non_output_registers,
+                )?;
                 number_measurements = *o.number_measurements();
                 let readout = o.readout().clone();
 
@@ -234,6 +270,12 @@ pub fn call_circuit<'a>(
                         }
                         Some(reg) => {
                             let readout_length = reg.1;
+                            // All the measured qubits need a position in the register
+                            if readout_length < device_number_qubits {
+                                return Err(IqmBackendError::RegisterTooSmall {
+                                    name: readout.clone(),
+                                });
+                            }
                             let readout_name = o.readout().to_string();
                             measured_qubits_map.insert(
                                 readout_name,
@@ -257,6 +299,7 @@ pub fn call_circuit<'a>(
                     },
                 }
 
+                measure_indices.insert(readout.clone(), circuit_vec.len());
                 let measure_all = IqmInstruction {
                     name: "measure".to_string(),
                     qubits: _convert_all_qubit_names(device_number_qubits),
@@ -277,14 +320,18 @@ pub fn call_circuit<'a>(
 
                 for _ in 0..reps {
                     for i in o.circuit().iter() {
-                        if let Some(instruction) = call_operation(i)? {
+                        if let Some(instruction) =
+                            virtual_z.call_operation_at(i, position, circuit_index)?
+                        {
                             circuit_vec.push(instruction);
                         }
                     }
                 }
             }
             _ => {
-                if let Some(instruction) = call_operation(op)? {
+                if let Some(instruction) =
+                    virtual_z.call_operation_at(op, position, circuit_index)?
+                {
                     circuit_vec.push(instruction)
                 }
             }
@@ -304,6 +351,274 @@ pub fn call_circuit<'a>(
     Ok((iqm_circuit, number_measurements))
 }
 
+/// Converts an [IqmCircuit] back into a [roqoqo::Circuit], reversing [call_circuit].
+///
+/// `prx` instructions are converted into [roqoqo::operations::RotateXY], `cz` instructions into
+/// [roqoqo::operations::ControlledPauliZ] or [roqoqo::operations::CZQubitResonator], `move`
+/// instructions into [roqoqo::operations::SingleExcitationStore] or
+/// [roqoqo::operations::SingleExcitationLoad] (depending on whether the resonator is occupied) and
+/// `measure` instructions into [roqoqo::operations::MeasureQubit]. `barrier` instructions are
+/// converted into [roqoqo::operations::PragmaStopParallelBlock] on their qubits, without the
+/// computational resonators. The output registers and readout indices are taken from the metadata
+/// of the circuit if available, otherwise the qubits of each measurement are written to consecutive
+/// positions of the register.
+///
+/// # Arguments
+///
+/// * `iqm_circuit` - The [IqmCircuit] that is converted
+///
+/// # Returns
+///
+/// * `Ok(Circuit)` - Converted circuit
+/// * `Err(IqmBackendError::InvalidCircuit)` - An instruction or a qubit name is not recognized
+pub fn iqm_circuit_to_circuit(iqm_circuit: &IqmCircuit) -> Result<Circuit, IqmBackendError> {
+    let metadata = iqm_circuit.metadata.clone().unwrap_or_default();
+    let invalid = |msg: String| IqmBackendError::InvalidCircuit { msg };
+    let qubit = |name: &String| {
+        _convert_qubit_name_iqm_to_qoqo(name)
+            .ok_or_else(|| invalid(format!("Unknown qubit name {}.", name)))
+    };
+    let angle = |instruction: &IqmInstruction, name: &str| {
+        instruction
+            .args
+            .get(name)
+            .map(|value| value.clone() * (2.0 * PI))
+            .ok_or_else(|| invalid(format!("Instruction prx is missing the {} argument.", name)))
+    };
+
+    let mut circuit = Circuit::new();
+    // Number of measured positions of each register
+    let mut register_lengths: HashMap<String, usize> = HashMap::new();
+    // Qubit stored in each resonator
+    let mut occupied_resonators: HashMap<usize, usize> = HashMap::new();
+    let mut operations = Circuit::new();
+    for instruction in iqm_circuit.instructions.iter() {
+        match instruction.name.as_str() {
+            "prx" => {
+                let target = match instruction.qubits.as_slice() {
+                    [target] => qubit(target)?,
+                    _ => {
+                        return Err(invalid(
+                            "Instruction prx must act on one qubit.".to_string(),
+                        ))
+                    }
+                };
+                operations += RotateXY::new(
+                    target,
+                    angle(instruction, "angle_t")?,
+                    angle(instruction, "phase_t")?,
+                );
+            }
+            "cz" => {
+                let (control, target) = _instruction_qubit_pair(instruction)?;
+                match _convert_resonator_name_iqm_to_qoqo(target) {
+                    Some(resonator) => {
+                        operations += CZQubitResonator::new(qubit(control)?, resonator)
+                    }
+                    None => operations += ControlledPauliZ::new(qubit(control)?, qubit(target)?),
+                }
+            }
+            "move" => {
+                let (control, target) = _instruction_qubit_pair(instruction)?;
+                let control = qubit(control)?;
+                let resonator = _convert_resonator_name_iqm_to_qoqo(target)
+                    .ok_or_else(|| invalid(format!("Unknown resonator name {}.", target)))?;
+                if occupied_resonators.remove(&resonator).is_some() {
+                    operations += SingleExcitationLoad::new(control, resonator);
+                } else {
+                    occupied_resonators.insert(resonator, control);
+                    operations += SingleExcitationStore::new(control, resonator);
+                }
+            }
+            "measure" => {
+                let readout = match instruction.args.get("key") {
+                    Some(CalculatorFloat::Str(key)) => key.clone(),
+                    _ => {
+                        return Err(invalid(
+                            "Instruction measure is missing the key argument.".to_string(),
+                        ))
+                    }
+                };
+                let offset = register_lengths.entry(readout.clone()).or_insert(0);
+                for name in instruction.qubits.iter() {
+                    let readout_index = metadata
+                        .get(&readout)
+                        .and_then(|(indices, _)| indices.get(*offset).copied())
+                        .unwrap_or(*offset);
+                    operations += MeasureQubit::new(qubit(name)?, readout.clone(), readout_index);
+                    *offset += 1;
+                }
+            }
+            "barrier" => {
+                let qubits = instruction
+                    .qubits
+                    .iter()
+                    .filter(|name| _convert_resonator_name_iqm_to_qoqo(name).is_none())
+                    .map(qubit)
+                    .collect::<Result<Vec<usize>, IqmBackendError>>()?;
+                operations += PragmaStopParallelBlock::new(qubits, 0.0.into());
+            }
+            name => return Err(invalid(format!("Unknown instruction {}.", name))),
+        }
+    }
+
+    for (name, (_, length)) in metadata.iter().sorted() {
+        circuit += DefinitionBit::new(name.clone(), *length, true);
+    }
+    for (name, length) in register_lengths.into_iter().sorted() {
+        if !metadata.contains_key(&name) {
+            circuit += DefinitionBit::new(name, length, true);
+        }
+    }
+    Ok(circuit + operations)
+}
+
+/// Check that a measurement does not write to a register that is defined, but not as an output
+/// register, which would otherwise be reported as an undefined register.
+fn _check_output_register(
+    operation: &Operation,
+    readout: &str,
+    measured_qubits_map: &MeasuredQubitsMap,
+    non_output_registers: &HashSet<String>,
+) -> Result<(), IqmBackendError> {
+    if !measured_qubits_map.contains_key(readout) && non_output_registers.contains(readout) {
+        return Err(IqmBackendError::InvalidCircuit {
+            msg: format!(
+                "{} writes to register {}, which is not an output register. Set is_output to \
+                 true in the DefinitionBit of the register.",
+                operation.hqslang(),
+                readout
+            ),
+        });
+    }
+    Ok(())
+}
+
+/// Returns the two qubits an instruction acts on.
+fn _instruction_qubit_pair(
+    instruction: &IqmInstruction,
+) -> Result<(&String, &String), IqmBackendError> {
+    match instruction.qubits.as_slice() {
+        [first, second] => Ok((first, second)),
+        _ => Err(IqmBackendError::InvalidCircuit {
+            msg: format!("Instruction {} must act on two qubits.", instruction.name),
+        }),
+    }
+}
+
+/// Accumulated angles of the virtual Z rotations of a circuit, see [call_circuit].
+#[derive(Debug, Default)]
+struct VirtualZPhases {
+    /// Angle of the Z rotations not yet applied to each qubit
+    qubits: HashMap<usize, CalculatorFloat>,
+    /// Angle of the Z rotations of the excitation stored in each computational resonator
+    resonators: HashMap<usize, CalculatorFloat>,
+}
+
+impl VirtualZPhases {
+    /// Convert an operation with [_call_operation_at], applying the virtual Z rotations.
+    ///
+    /// Z rotations are accumulated and return no instruction, and the accumulated angle of a qubit
+    /// is subtracted from the phase of its `RotateXY` operations.
+    fn call_operation_at(
+        &mut self,
+        operation: &Operation,
+        position: usize,
+        circuit_index: usize,
+    ) -> Result<Option<IqmInstruction>, IqmBackendError> {
+        match operation {
+            Operation::RotateZ(op) => {
+                let angle = match self.qubits.remove(op.qubit()) {
+                    Some(angle) => angle + op.theta(),
+                    None => op.theta().clone(),
+                };
+                self.qubits.insert(*op.qubit(), angle);
+                return Ok(None);
+            }
+            Operation::RotateXY(op) => {
+                if let Some(angle) = self.qubits.get(op.qubit()) {
+                    let shifted: Operation =
+                        RotateXY::new(*op.qubit(), op.theta().clone(), op.phi().clone() - angle)
+                            .into();
+                    return _call_operation_at(&shifted, position, circuit_index);
+                }
+            }
+            Operation::SingleExcitationStore(op) => {
+                match self.qubits.remove(op.qubit()) {
+                    Some(angle) => self.resonators.insert(*op.mode(), angle),
+                    None => self.resonators.remove(op.mode()),
+                };
+            }
+            Operation::SingleExcitationLoad(op) => {
+                match self.resonators.remove(op.mode()) {
+                    Some(angle) => self.qubits.insert(*op.qubit(), angle),
+                    None => self.qubits.remove(op.qubit()),
+                };
+            }
+            _ => (),
+        }
+        _call_operation_at(operation, position, circuit_index)
+    }
+}
+
+/// Convert an operation with [call_operation], naming the position of the operation in the circuit
+/// if it is not supported by the backend.
+fn _call_operation_at(
+    operation: &Operation,
+    position: usize,
+    circuit_index: usize,
+) -> Result<Option<IqmInstruction>, IqmBackendError> {
+    call_operation(operation).map_err(|err| match err {
+        RoqoqoBackendError::OperationNotInBackend { hqslang, .. } => {
+            let qubits = match operation.involved_qubits() {
+                InvolvedQubits::Set(qubits) => {
+                    format!(
+                        " on qubits {:?}",
+                        qubits.into_iter().sorted().collect::<Vec<usize>>()
+                    )
+                }
+                InvolvedQubits::All => " on all qubits".to_string(),
+                InvolvedQubits::None => String::new(),
+            };
+            IqmBackendError::InvalidCircuit {
+                msg: format!(
+                    "Circuit {}: operation #{} ({}{}) is not supported by the backend.",
+                    circuit_index, position, hqslang, qubits
+                ),
+            }
+        }
+        _ => err.into(),
+    })
+}
+
+/// Maximum length of the circuit names submitted to the IQM REST API.
+pub const MAX_CIRCUIT_NAME_LENGTH: usize = 64;
+
+/// Restrict a circuit name to the characters accepted by the IQM REST API.
+///
+/// Characters other than ASCII letters, digits, `_`, `-` and `.` are replaced by `_` and the name
+/// is truncated to [MAX_CIRCUIT_NAME_LENGTH] characters.
+///
+/// # Arguments
+///
+/// * `name` - The circuit name to sanitize.
+///
+/// # Returns
+///
+/// * `String` - The sanitized circuit name.
+pub fn sanitize_circuit_name(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '_' || c == '-' || c == '.' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .take(MAX_CIRCUIT_NAME_LENGTH)
+        .collect()
+}
+
 /// Converts a [roqoqo::operations::Operation] into a native instruction for IQM Hardware
 ///
 /// # Arguments
@@ -314,20 +629,18 @@ pub fn call_circuit<'a>(
 ///
 /// * `Ok(IqmInstruction)` - Converted instruction  
 /// * `Err(RoqoqoBackendError::OperationNotInBackend)` - Error when [roqoqo::operations::Operation] can not be converted
+///
+/// [roqoqo::operations::RotateZ] is not supported on its own, since it is only applied as virtual
+/// Z rotation when converting a whole circuit with [call_circuit].
 pub fn call_operation(operation: &Operation) -> Result<Option<IqmInstruction>, RoqoqoBackendError> {
     let mut op_parameters = HashMap::new();
 
     match operation {
         Operation::RotateXY(op) => {
-            // Angles are measured in units of 2*PI in the IQM API
-            op_parameters.insert(
-                "angle_t".to_string(),
-                CalculatorFloat::Float(*op.theta().float()? / (2.0 * PI)),
-            );
-            op_parameters.insert(
-                "phase_t".to_string(),
-                CalculatorFloat::Float(*op.phi().float()? / (2.0 * PI)),
-            );
+            // Angles are measured in units of 2*PI in the IQM API. Symbolic angles are kept as
+            // expressions, to be substituted before submission
+            op_parameters.insert("angle_t".to_string(), op.theta().clone() / (2.0 * PI));
+            op_parameters.insert("phase_t".to_string(), op.phi().clone() / (2.0 * PI));
 
             Ok(Some(IqmInstruction {
                 name: "prx".to_string(),
@@ -346,6 +659,8 @@ pub fn call_operation(operation: &Operation) -> Result<Option<IqmInstruction>, R
             }))
         }
         Operation::CZQubitResonator(op) => {
+            // The qubit-resonator CZ has no phase parameter, phased CZ variants such as
+            // PhaseShiftedControlledZ are rejected as unsupported operations
             let control = _convert_qubit_name_qoqo_to_iqm(*op.qubit());
             let resonator = _convert_resonator_name_qoqo_to_iqm(*op.mode());
 
@@ -375,6 +690,22 @@ pub fn call_operation(operation: &Operation) -> Result<Option<IqmInstruction>, R
                 args: op_parameters,
             }))
         }
+        Operation::PragmaStopParallelBlock(op) => {
+            // The barrier prevents the server from reordering the instructions of the qubits across
+            // the end of the block. A block without qubits does not constrain any instruction
+            if op.qubits().is_empty() {
+                return Ok(None);
+            }
+            Ok(Some(IqmInstruction {
+                name: "barrier".to_string(),
+                qubits: op
+                    .qubits()
+                    .iter()
+                    .map(|qubit| _convert_qubit_name_qoqo_to_iqm(*qubit))
+                    .collect(),
+                args: op_parameters,
+            }))
+        }
         _ => {
             if ALLOWED_OPERATIONS.contains(&operation.hqslang()) {
                 Ok(None)
@@ -391,14 +722,41 @@ pub fn call_operation(operation: &Operation) -> Result<Option<IqmInstruction>, R
 /// Convert a qubit number into the format accepted by IQM.
 // e.g. "QB2" for qoqo_qubit number 1 (IQM qubits start from 1)
 #[inline]
-fn _convert_qubit_name_qoqo_to_iqm(qoqo_qubit: usize) -> String {
+pub(crate) fn _convert_qubit_name_qoqo_to_iqm(qoqo_qubit: usize) -> String {
     format!("QB{}", qoqo_qubit + 1)
 }
 
+/// Convert a qubit name in the format used by IQM back into a qoqo qubit number.
+// e.g. qoqo_qubit number 1 for "QB2". Returns None if the name is not of the form "QB<n>".
+#[inline]
+pub(crate) fn _convert_qubit_name_iqm_to_qoqo(iqm_qubit: &str) -> Option<usize> {
+    iqm_qubit
+        .strip_prefix("QB")
+        .and_then(|number| number.parse::<usize>().ok())
+        .and_then(|number| number.checked_sub(1))
+}
+
 /// Convert a resonator number into the format accepted by IQM.
+// e.g. "COMP_R" for the first resonator and "COMP_R2" for resonator number 1
+#[inline]
+fn _convert_resonator_name_qoqo_to_iqm(resonator_index: usize) -> String {
+    match resonator_index {
+        0 => "COMP_R".to_string(),
+        _ => format!("COMP_R{}", resonator_index + 1),
+    }
+}
+
+/// Convert a resonator name in the format used by IQM back into a qoqo resonator number.
+// e.g. resonator number 1 for "COMP_R2". Returns None if the name is not of the form "COMP_R<n>".
 #[inline]
-fn _convert_resonator_name_qoqo_to_iqm(_resonator_index: usize) -> String {
-    "COMP_R".to_string()
+pub(crate) fn _convert_resonator_name_iqm_to_qoqo(iqm_resonator: &str) -> Option<usize> {
+    match iqm_resonator.strip_prefix("COMP_R")? {
+        "" => Some(0),
+        number => number
+            .parse::<usize>()
+            .ok()
+            .and_then(|number| number.checked_sub(1)),
+    }
 }
 
 /// Create a vector will all qubit names, in the format accepted by IQM
@@ -21,8 +21,22 @@
 #![deny(missing_debug_implementations)]
 
 use roqoqo::RoqoqoBackendError;
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
+/// Structured error information that IQM can return in the message of a failed job.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IqmErrorDetail {
+    /// Error code identifying the failure mode
+    pub code: String,
+    /// Human-readable description of the error
+    #[serde(default)]
+    pub message: String,
+    /// Qubits affected by the error
+    #[serde(default)]
+    pub qubits: Vec<String>,
+}
+
 /// Errors that can occur in roqoqo-iqm
 #[derive(Error, Debug)]
 pub enum IqmBackendError {
@@ -34,6 +48,14 @@ pub enum IqmBackendError {
         /// Message
         msg: String,
     },
+    /// Status of submitted job is FAILED and the message contains structured error information
+    #[error("Job failed with job ID: {id}\nError code: {}\nMessage: {}", detail.code, detail.message)]
+    JobFailedWithDetails {
+        /// Job ID
+        id: String,
+        /// Error information parsed from the message
+        detail: IqmErrorDetail,
+    },
     /// Status of submitted job is ABORTED
     #[error("Job with job ID {id} is aborted.")]
     JobAborted {
@@ -81,16 +103,47 @@ pub enum IqmBackendError {
         /// Message
         msg: String,
     },
+    /// The results of the job carry warnings, and warnings are treated as errors
+    #[error("Job with job ID {id} returned warnings: {warnings:?}")]
+    WarningsPresent {
+        /// Job ID
+        id: String,
+        /// Warnings returned by the IQM device
+        warnings: Vec<String>,
+    },
     /// Transparent propagation of RoqoqoBackendError
     #[error(transparent)]
     RoqoqoBackendError(#[from] RoqoqoBackendError),
 }
 
+/// Errors of [Backend::submit_checked], separating invalid circuits from failed requests
+#[derive(Error, Debug)]
+pub enum SubmitError {
+    /// The circuits are invalid and need to be changed before they can be submitted
+    #[error("The circuit batch is invalid: {0:?}")]
+    Validation(Vec<IqmBackendError>),
+    /// The request to the server failed, e.g. because the server is unreachable
+    #[error(transparent)]
+    Network(RoqoqoBackendError),
+}
+
+mod decomposition;
+pub use decomposition::transpile_resonator_free_to_deneb;
+
 mod interface;
-pub use interface::{call_circuit, call_operation, IqmCircuit, IqmInstruction};
+pub use interface::{
+    call_circuit, call_operation, iqm_circuit_to_circuit, sanitize_circuit_name, IqmCircuit,
+    IqmInstruction, MAX_CIRCUIT_NAME_LENGTH,
+};
+
+mod measurement_basis;
+pub use measurement_basis::{add_basis_measurement, add_partial_measurement, PauliBasis};
 
 mod backend;
 pub use backend::*;
 
+mod quantum_architecture;
+pub use quantum_architecture::{diff_architectures, ArchitectureDiff, QuantumArchitecture};
+
 pub mod devices;
 pub use devices::*;
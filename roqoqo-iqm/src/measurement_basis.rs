@@ -0,0 +1,143 @@
+// Copyright © 2020-2023 HQS Quantum Simulations GmbH. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the
+// License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either
+// express or implied. See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::f64::consts::PI;
+
+use roqoqo::operations::*;
+use roqoqo::Circuit;
+use serde::{Deserialize, Serialize};
+
+/// Pauli basis in which a qubit is measured.
+#[derive(Eq, PartialEq, Debug, Clone, Copy, Serialize, Deserialize)]
+pub enum PauliBasis {
+    /// Eigenbasis of the Pauli X operator
+    X,
+    /// Eigenbasis of the Pauli Y operator
+    Y,
+    /// Eigenbasis of the Pauli Z operator, i.e. the computational basis
+    Z,
+}
+
+/// Measure a qubit in a Pauli basis.
+///
+/// IQM devices only measure in the computational (Z) basis. For the X and Y bases, a `RotateXY`
+/// gate mapping the eigenstate with eigenvalue +1 onto `|0>` is added before the `MeasureQubit`,
+/// so that a measured `false` corresponds to the eigenvalue +1 in all bases.
+///
+/// # Arguments
+///
+/// * `circuit` - The circuit the measurement is added to.
+/// * `qubit` - The qubit to measure.
+/// * `basis` - The basis of the measurement.
+/// * `register` - The name of the bit register the result is written to.
+/// * `index` - The index in the register the result is written to.
+pub fn add_basis_measurement(
+    circuit: &mut Circuit,
+    qubit: usize,
+    basis: PauliBasis,
+    register: &str,
+    index: usize,
+) {
+    match basis {
+        // Rotation around the Y axis mapping |+> onto |0>
+        PauliBasis::X => *circuit += RotateXY::new(qubit, (-PI / 2.0).into(), (PI / 2.0).into()),
+        // Rotation around the X axis mapping |+i> onto |0>
+        PauliBasis::Y => *circuit += RotateXY::new(qubit, (PI / 2.0).into(), 0.0.into()),
+        PauliBasis::Z => (),
+    }
+    *circuit += MeasureQubit::new(qubit, register.to_string(), index);
+}
+
+/// Measure a subset of the qubits of a circuit.
+///
+/// Adds an output `DefinitionBit` with one entry per measured qubit and a `MeasureQubit` for
+/// each of the given qubits. The result of `qubits[i]` is written to index `i` of the register.
+///
+/// # Arguments
+///
+/// * `circuit` - The circuit the measurements are added to.
+/// * `qubits` - The qubits to measure.
+/// * `register` - The name of the bit register the results are written to.
+pub fn add_partial_measurement(circuit: &mut Circuit, qubits: &[usize], register: &str) {
+    *circuit += DefinitionBit::new(register.to_string(), qubits.len(), true);
+    for (index, qubit) in qubits.iter().enumerate() {
+        *circuit += MeasureQubit::new(*qubit, register.to_string(), index);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use num_complex::Complex64;
+
+    // Returns the probability of measuring |0> after applying the operations of the circuit
+    // preceding the measurement to the given single-qubit state.
+    fn probability_zero(circuit: &Circuit, state: [Complex64; 2]) -> f64 {
+        let mut state = state;
+        for op in circuit.iter() {
+            if let Operation::RotateXY(o) = op {
+                let matrix = o.unitary_matrix().unwrap();
+                state = [
+                    matrix[[0, 0]] * state[0] + matrix[[0, 1]] * state[1],
+                    matrix[[1, 0]] * state[0] + matrix[[1, 1]] * state[1],
+                ];
+            }
+        }
+        state[0].norm_sqr()
+    }
+
+    #[test]
+    fn test_add_basis_measurement() {
+        let s = 1.0 / 2.0_f64.sqrt();
+        let plus = [Complex64::new(s, 0.0), Complex64::new(s, 0.0)];
+        let minus = [Complex64::new(s, 0.0), Complex64::new(-s, 0.0)];
+        let plus_i = [Complex64::new(s, 0.0), Complex64::new(0.0, s)];
+        let minus_i = [Complex64::new(s, 0.0), Complex64::new(0.0, -s)];
+
+        let mut circuit = Circuit::new();
+        add_basis_measurement(&mut circuit, 1, PauliBasis::X, "ro", 2);
+        let mut expected = Circuit::new();
+        expected += RotateXY::new(1, (-PI / 2.0).into(), (PI / 2.0).into());
+        expected += MeasureQubit::new(1, "ro".to_string(), 2);
+        assert_eq!(circuit, expected);
+        assert!((probability_zero(&circuit, plus) - 1.0).abs() < 1e-10);
+        assert!(probability_zero(&circuit, minus).abs() < 1e-10);
+
+        let mut circuit = Circuit::new();
+        add_basis_measurement(&mut circuit, 1, PauliBasis::Y, "ro", 2);
+        let mut expected = Circuit::new();
+        expected += RotateXY::new(1, (PI / 2.0).into(), 0.0.into());
+        expected += MeasureQubit::new(1, "ro".to_string(), 2);
+        assert_eq!(circuit, expected);
+        assert!((probability_zero(&circuit, plus_i) - 1.0).abs() < 1e-10);
+        assert!(probability_zero(&circuit, minus_i).abs() < 1e-10);
+
+        let mut circuit = Circuit::new();
+        add_basis_measurement(&mut circuit, 1, PauliBasis::Z, "ro", 2);
+        let mut expected = Circuit::new();
+        expected += MeasureQubit::new(1, "ro".to_string(), 2);
+        assert_eq!(circuit, expected);
+    }
+
+    #[test]
+    fn test_add_partial_measurement() {
+        let mut circuit = Circuit::new();
+        circuit += RotateXY::new(0, PI.into(), 0.0.into());
+        add_partial_measurement(&mut circuit, &[3, 1], "ro");
+        let mut expected = Circuit::new();
+        expected += RotateXY::new(0, PI.into(), 0.0.into());
+        expected += DefinitionBit::new("ro".to_string(), 2, true);
+        expected += MeasureQubit::new(3, "ro".to_string(), 0);
+        expected += MeasureQubit::new(1, "ro".to_string(), 1);
+        assert_eq!(circuit, expected);
+    }
+}
@@ -0,0 +1,253 @@
+// Copyright © 2020-2023 HQS Quantum Simulations GmbH. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the
+// License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either
+// express or implied. See the License for the specific language governing permissions and
+// limitations under the License.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+use roqoqo::devices::GenericDevice;
+use roqoqo::RoqoqoBackendError;
+
+use crate::interface::{_convert_qubit_name_iqm_to_qoqo, _convert_resonator_name_iqm_to_qoqo};
+
+/// Gate time set for the native operations of a device built from a quantum architecture.
+const ARCHITECTURE_GATE_TIME: f64 = 1.0;
+
+/// Representation of the quantum architecture of a device, as returned by the
+/// `quantum-architecture` endpoint of the IQM REST API.
+#[derive(PartialEq, Eq, Debug, Clone, Serialize, Deserialize)]
+pub struct QuantumArchitecture {
+    /// Name of the quantum architecture
+    pub name: String,
+    /// Names of the qubits (and computational resonators) of the device
+    pub qubits: Vec<String>,
+    /// Pairs of qubits that are coupled in the device
+    pub qubit_connectivity: Vec<(String, String)>,
+    /// Names of the native operations supported by the device
+    pub operations: Vec<String>,
+    /// Maximum depth of the native circuits accepted by the device, if limited
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_circuit_depth: Option<usize>,
+}
+
+/// Response body of the `quantum-architecture` endpoint.
+#[derive(PartialEq, Eq, Debug, Clone, Serialize, Deserialize)]
+struct QuantumArchitectureResponse {
+    quantum_architecture: QuantumArchitecture,
+}
+
+impl QuantumArchitecture {
+    /// Parse the response of the `quantum-architecture` endpoint.
+    ///
+    /// # Arguments
+    ///
+    /// * `response` - The JSON body returned by the endpoint.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(QuantumArchitecture)` - The parsed quantum architecture.
+    /// * `Err(RoqoqoBackendError)` - The response could not be parsed.
+    pub fn from_response(response: &str) -> Result<Self, RoqoqoBackendError> {
+        serde_json::from_str::<QuantumArchitectureResponse>(response)
+            .map(|x| x.quantum_architecture)
+            .map_err(|err| RoqoqoBackendError::GenericError {
+                msg: format!("Could not parse the quantum architecture: {}", err),
+            })
+    }
+
+    /// Build a [GenericDevice] with the qubits, couplings and native operations of the architecture.
+    ///
+    /// Qubit `QB<n>` is mapped to qoqo qubit `n - 1`. The native operations are translated to qoqo
+    /// operations with a gate time of 1.0:
+    ///
+    /// * `prx` - `RotateXY` on every qubit.
+    /// * `cz` - `ControlledPauliZ` in both directions between coupled qubits.
+    ///
+    /// Other operations (e.g. `measure` or `barrier`) have no gate time and are ignored. The
+    /// qubits coupled to a computational resonator are part of the device, but the operations
+    /// between qubits and resonators (`CZQubitResonator`, `SingleExcitationLoad` and
+    /// `SingleExcitationStore`) are not: [DenebDevice](crate::devices::DenebDevice) treats them as
+    /// gates between a qubit and a resonator, which a [GenericDevice] cannot represent without
+    /// mistaking the resonator for a qubit. Use a [DenebDevice](crate::devices::DenebDevice) for
+    /// devices with computational resonators.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(GenericDevice)` - The device described by the architecture.
+    /// * `Err(RoqoqoBackendError)` - The architecture contains a qubit or coupling with an unknown
+    ///   name.
+    pub fn to_generic_device(&self) -> Result<GenericDevice, RoqoqoBackendError> {
+        let mut qubits: BTreeSet<usize> = BTreeSet::new();
+        for name in &self.qubits {
+            if let Some(qubit) = _convert_qubit_name_iqm_to_qoqo(name) {
+                qubits.insert(qubit);
+            } else if _convert_resonator_name_iqm_to_qoqo(name).is_none() {
+                return Err(unknown_qubit_error(name));
+            }
+        }
+
+        let mut qubit_edges: BTreeSet<(usize, usize)> = BTreeSet::new();
+        for (a, b) in &self.qubit_connectivity {
+            match (
+                _convert_qubit_name_iqm_to_qoqo(a),
+                _convert_qubit_name_iqm_to_qoqo(b),
+            ) {
+                (Some(qubit_a), Some(qubit_b)) => {
+                    qubit_edges.insert((qubit_a, qubit_b));
+                    qubits.extend([qubit_a, qubit_b]);
+                }
+                (Some(qubit), None) if _convert_resonator_name_iqm_to_qoqo(b).is_some() => {
+                    qubits.insert(qubit);
+                }
+                (None, Some(qubit)) if _convert_resonator_name_iqm_to_qoqo(a).is_some() => {
+                    qubits.insert(qubit);
+                }
+                (Some(_), None) => return Err(unknown_qubit_error(b)),
+                _ => return Err(unknown_qubit_error(a)),
+            }
+        }
+
+        let number_qubits = qubits.iter().next_back().map_or(0, |qubit| qubit + 1);
+        let mut single_qubit_gates: BTreeMap<&str, &BTreeSet<usize>> = BTreeMap::new();
+        let mut two_qubit_gates: Vec<&str> = Vec::new();
+        for operation in &self.operations {
+            match operation.as_str() {
+                "prx" => {
+                    single_qubit_gates.insert("RotateXY", &qubits);
+                }
+                "cz" => two_qubit_gates.push("ControlledPauliZ"),
+                _ => (),
+            }
+        }
+
+        let mut generic_device = GenericDevice::new(number_qubits);
+        for (gate, gate_qubits) in single_qubit_gates {
+            for qubit in gate_qubits {
+                generic_device.set_single_qubit_gate_time(gate, *qubit, ARCHITECTURE_GATE_TIME)?;
+            }
+        }
+        for gate in two_qubit_gates {
+            for (qubit_a, qubit_b) in &qubit_edges {
+                generic_device.set_two_qubit_gate_time(
+                    gate,
+                    *qubit_a,
+                    *qubit_b,
+                    ARCHITECTURE_GATE_TIME,
+                )?;
+                generic_device.set_two_qubit_gate_time(
+                    gate,
+                    *qubit_b,
+                    *qubit_a,
+                    ARCHITECTURE_GATE_TIME,
+                )?;
+            }
+        }
+        Ok(generic_device)
+    }
+
+    /// Returns the set of couplings of the architecture, with the two qubits of each coupling
+    /// sorted so that the couplings can be compared regardless of their orientation.
+    fn edge_set(&self) -> BTreeSet<(String, String)> {
+        self.qubit_connectivity
+            .iter()
+            .map(|(a, b)| {
+                if a <= b {
+                    (a.clone(), b.clone())
+                } else {
+                    (b.clone(), a.clone())
+                }
+            })
+            .collect()
+    }
+}
+
+/// Error for a qubit name that is neither a qubit nor a computational resonator.
+fn unknown_qubit_error(name: &str) -> RoqoqoBackendError {
+    RoqoqoBackendError::GenericError {
+        msg: format!("Unknown qubit {} in the quantum architecture", name),
+    }
+}
+
+/// Differences between two snapshots of a quantum architecture.
+#[derive(PartialEq, Eq, Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ArchitectureDiff {
+    /// Qubits present only in the new snapshot
+    pub added_qubits: Vec<String>,
+    /// Qubits present only in the old snapshot
+    pub removed_qubits: Vec<String>,
+    /// Couplings present only in the new snapshot
+    pub added_edges: Vec<(String, String)>,
+    /// Couplings present only in the old snapshot
+    pub removed_edges: Vec<(String, String)>,
+    /// Native operations present only in the new snapshot
+    pub added_operations: Vec<String>,
+    /// Native operations present only in the old snapshot
+    pub removed_operations: Vec<String>,
+}
+
+impl ArchitectureDiff {
+    /// Returns true if the two compared snapshots have the same qubits, couplings and operations.
+    pub fn is_empty(&self) -> bool {
+        self.added_qubits.is_empty()
+            && self.removed_qubits.is_empty()
+            && self.added_edges.is_empty()
+            && self.removed_edges.is_empty()
+            && self.added_operations.is_empty()
+            && self.removed_operations.is_empty()
+    }
+}
+
+/// Compare two snapshots of a quantum architecture.
+///
+/// Useful to detect hardware changes (e.g. after a recalibration) by periodically fetching the
+/// architecture with [crate::Backend::get_quantum_architecture_parsed]. Couplings are compared
+/// regardless of the order of the two qubits.
+///
+/// # Arguments
+///
+/// * `old` - The earlier snapshot.
+/// * `new` - The later snapshot.
+///
+/// # Returns
+///
+/// * `ArchitectureDiff` - The qubits, couplings and operations added or removed in `new`.
+pub fn diff_architectures(
+    old: &QuantumArchitecture,
+    new: &QuantumArchitecture,
+) -> ArchitectureDiff {
+    let old_qubits: BTreeSet<&String> = old.qubits.iter().collect();
+    let new_qubits: BTreeSet<&String> = new.qubits.iter().collect();
+    let old_edges = old.edge_set();
+    let new_edges = new.edge_set();
+    let old_operations: BTreeSet<&String> = old.operations.iter().collect();
+    let new_operations: BTreeSet<&String> = new.operations.iter().collect();
+
+    ArchitectureDiff {
+        added_qubits: new_qubits
+            .difference(&old_qubits)
+            .map(|x| (*x).clone())
+            .collect(),
+        removed_qubits: old_qubits
+            .difference(&new_qubits)
+            .map(|x| (*x).clone())
+            .collect(),
+        added_edges: new_edges.difference(&old_edges).cloned().collect(),
+        removed_edges: old_edges.difference(&new_edges).cloned().collect(),
+        added_operations: new_operations
+            .difference(&old_operations)
+            .map(|x| (*x).clone())
+            .collect(),
+        removed_operations: old_operations
+            .difference(&new_operations)
+            .map(|x| (*x).clone())
+            .collect(),
+    }
+}
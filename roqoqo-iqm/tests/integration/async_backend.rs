@@ -0,0 +1,220 @@
+// Copyright © 2020-2023 HQS Quantum Simulations GmbH. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the
+// License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either
+// express or implied. See the License for the specific language governing permissions and
+// limitations under the License.
+
+use roqoqo::{operations::*, Circuit, RoqoqoBackendError};
+use roqoqo_iqm::{AsyncBackend, Backend, GarnetDevice, IqmBackendError};
+use std::f64::consts::PI;
+use std::future::Future;
+
+// Run a future to completion on a single-threaded tokio runtime.
+fn block_on<F: Future>(future: F) -> F::Output {
+    tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap()
+        .block_on(future)
+}
+
+fn async_backend(server: &mockito::Server) -> AsyncBackend {
+    let mut device = GarnetDevice::new();
+    device.set_endpoint_url(format!("{}/garnet/jobs", server.url()));
+    let mut backend = Backend::new(device.into(), Some("dummy_token".to_string())).unwrap();
+    backend.set_allow_http(true);
+    backend.set_polling_interval(0.01).unwrap();
+    backend.set_timeout(1.0).unwrap();
+    AsyncBackend::new(backend)
+}
+
+fn test_circuit() -> Circuit {
+    let mut circuit = Circuit::new();
+    circuit += DefinitionBit::new("ro".to_string(), 1, true);
+    circuit += RotateXY::new(0, PI.into(), 0.0.into());
+    circuit += MeasureQubit::new(0, "ro".to_string(), 0);
+    circuit += PragmaSetNumberOfMeasurements::new(2, "ro".to_string());
+    circuit
+}
+
+#[test]
+fn submit_and_wait_for_results() {
+    let mut server = mockito::Server::new();
+    let backend = async_backend(&server);
+
+    let submit = server
+        .mock("POST", "/garnet/jobs")
+        .match_header("Authorization", "Bearer dummy_token")
+        .match_body(mockito::Matcher::PartialJson(
+            serde_json::json!({"shots": 2}),
+        ))
+        .with_status(201)
+        .with_body(r#"{"id": "job_id"}"#)
+        .expect(1)
+        .create();
+    let pending = server
+        .mock("GET", "/garnet/jobs/job_id")
+        .with_status(200)
+        .with_body(
+            serde_json::json!({
+                "status": "pending execution",
+                "metadata": {
+                    "request": {
+                        "circuits": [],
+                        "shots": 2,
+                        "heralding_mode": "none"
+                    }
+                }
+            })
+            .to_string(),
+        )
+        .expect(1)
+        .create();
+    let ready = server
+        .mock("GET", "/garnet/jobs/job_id")
+        .with_status(200)
+        .with_body(
+            serde_json::json!({
+                "status": "ready",
+                "measurements": [{"ro": [[1], [1]]}],
+                "metadata": {
+                    "request": {
+                        "circuits": [{"name": "qc_0", "instructions": [], "metadata": {"ro": [[0], 1]}}],
+                        "shots": 2,
+                        "heralding_mode": "none"
+                    }
+                }
+            })
+            .to_string(),
+        )
+        .expect(1)
+        .create();
+
+    let result = block_on(async {
+        let id = backend.submit_circuit_batch(&[test_circuit()]).await?;
+        backend.wait_for_results(id).await
+    })
+    .unwrap();
+    submit.assert();
+    pending.assert();
+    ready.assert();
+    assert!(result.warnings().is_empty());
+
+    // The results of finished jobs are cached
+    assert!(block_on(backend.get_results("job_id".to_string())).is_ok());
+    ready.assert();
+}
+
+#[test]
+fn wait_for_results_failed_job() {
+    let mut server = mockito::Server::new();
+    let backend = async_backend(&server);
+
+    server
+        .mock("GET", "/garnet/jobs/job_id")
+        .with_status(200)
+        .with_body(
+            serde_json::json!({
+                "status": "failed",
+                "message": "Something went wrong",
+                "metadata": {
+                    "request": {
+                        "circuits": [],
+                        "shots": 2,
+                        "heralding_mode": "none"
+                    }
+                }
+            })
+            .to_string(),
+        )
+        .create();
+
+    assert!(matches!(
+        block_on(backend.wait_for_results("job_id".to_string())),
+        Err(IqmBackendError::JobFailed { .. })
+    ));
+}
+
+#[test]
+fn wait_for_results_timeout() {
+    let mut server = mockito::Server::new();
+    let mut backend = async_backend(&server);
+    backend.backend_mut().set_timeout(0.05).unwrap();
+
+    server
+        .mock("GET", "/garnet/jobs/job_id")
+        .with_status(200)
+        .with_body(
+            serde_json::json!({
+                "status": "pending compilation",
+                "metadata": {
+                    "request": {
+                        "circuits": [],
+                        "shots": 2,
+                        "heralding_mode": "none"
+                    }
+                }
+            })
+            .to_string(),
+        )
+        .create();
+
+    assert!(matches!(
+        block_on(backend.wait_for_results("job_id".to_string())),
+        Err(IqmBackendError::RoqoqoBackendError(
+            RoqoqoBackendError::Timeout { .. }
+        ))
+    ));
+}
+
+#[test]
+fn submit_invalid_circuit() {
+    let server = mockito::Server::new();
+    let backend = async_backend(&server);
+
+    let mut circuit = Circuit::new();
+    circuit += DefinitionBit::new("ro".to_string(), 1, true);
+    circuit += RotateXY::new(40, PI.into(), 0.0.into());
+    circuit += MeasureQubit::new(40, "ro".to_string(), 0);
+    assert!(block_on(backend.submit_circuit_batch(&[circuit])).is_err());
+}
+
+#[test]
+fn abort_job() {
+    let mut server = mockito::Server::new();
+    let backend = async_backend(&server);
+
+    let abort = server
+        .mock("POST", "/garnet/jobs/jobs/job_id/abort")
+        .with_status(200)
+        .expect(1)
+        .create();
+    assert!(block_on(backend.abort_job("job_id".to_string())).is_ok());
+    abort.assert();
+
+    server
+        .mock("POST", "/garnet/jobs/jobs/other_id/abort")
+        .with_status(400)
+        .with_body(r#"{"detail": "Job is already finished"}"#)
+        .create();
+    assert!(matches!(
+        block_on(backend.abort_job("other_id".to_string())),
+        Err(IqmBackendError::JobAbortionFailed { .. })
+    ));
+    // A body that is not JSON is reported as is
+    server
+        .mock("POST", "/garnet/jobs/jobs/third_id/abort")
+        .with_status(502)
+        .with_body("Bad Gateway")
+        .create();
+    match block_on(backend.abort_job("third_id".to_string())) {
+        Err(IqmBackendError::JobAbortionFailed { msg, .. }) => assert_eq!(msg, "Bad Gateway"),
+        res => panic!("Unexpected result: {:?}", res),
+    }
+}
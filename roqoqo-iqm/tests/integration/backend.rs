@@ -10,18 +10,28 @@
 // express or implied. See the License for the specific language governing permissions and
 // limitations under the License.
 
+use qoqo_calculator::CalculatorFloat;
 use roqoqo::devices::Device;
+use roqoqo::measurements::ClassicalRegister;
 use roqoqo::prelude::*;
-use roqoqo::{operations::*, Circuit};
-use roqoqo_iqm::devices::DenebDevice;
-use roqoqo_iqm::{Backend, GarnetDevice, IqmBackendError};
+use roqoqo::{operations::*, Circuit, RoqoqoBackendError};
+use roqoqo_iqm::devices::{DenebDevice, IqmDevice, ResonatorFreeDevice};
+use roqoqo_iqm::{
+    expand_parameter_sweep, registers_to_csv, Backend, BatchResult, CostModel, EmptyResultPolicy,
+    EndpointPaths, GarnetDevice, HeraldingMode, IqmBackendError, IqmCircuit, IqmInstruction,
+    JobSummary, PauliBasis, RequestFormat, RetryPolicy, Status, SubmitError, TokenSource,
+    ValidationLevel, MAX_CIRCUIT_NAME_LENGTH,
+};
+use std::collections::HashMap;
 use std::env;
 use std::f64::consts::PI;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 #[test]
 fn init_backend() {
     let device = DenebDevice::new();
-    if env::var("IQM_TOKEN").is_ok() {
+    if env::var("IQM_TOKEN").is_ok() || env::var("IQM_TOKENS_FILE").is_ok() {
         let ok = Backend::new(device.into(), None).is_ok();
         assert!(ok);
     } else {
@@ -340,6 +350,388 @@ fn disconnected_qubits_garnet() {
     assert!(matches!(err, Err(IqmBackendError::InvalidCircuit { .. })));
 }
 
+#[test]
+fn repeated_measurement_register_width() {
+    let device = DenebDevice::new();
+    let mut backend = Backend::new(device.into(), Some("dummy_token".to_string())).unwrap();
+
+    // Two-qubit circuit on a six-qubit device, read out into a two-bit register
+    let mut circuit = Circuit::new();
+    circuit += RotateXY::new(0, PI.into(), 0.0.into());
+    circuit += RotateXY::new(1, PI.into(), 0.0.into());
+    circuit += DefinitionBit::new("ro".to_string(), 2, true);
+    circuit += DefinitionBit::new("unused".to_string(), 1, true);
+    circuit += PragmaRepeatedMeasurement::new("ro".to_string(), 10, None);
+    // All the qubits of the device are measured by default
+    assert!(matches!(
+        backend.validate_circuit(&circuit),
+        Err(IqmBackendError::RegisterTooSmall { .. })
+    ));
+    assert!(matches!(
+        backend.measurement_key_map(&circuit),
+        Err(IqmBackendError::RegisterTooSmall { .. })
+    ));
+
+    backend.set_repeated_measurement_register_qubits(true);
+    assert!(backend.validate_circuit(&circuit).is_ok());
+    let key_map = backend.measurement_key_map(&circuit).unwrap();
+    assert_eq!(key_map["ro"], (vec![0, 1], 2));
+
+    // Only the qubits in the mapping need to fit into the register
+    let mut circuit = Circuit::new();
+    circuit += RotateXY::new(3, PI.into(), 0.0.into());
+    circuit += RotateXY::new(4, PI.into(), 0.0.into());
+    circuit += DefinitionBit::new("ro".to_string(), 2, true);
+    circuit +=
+        PragmaRepeatedMeasurement::new("ro".to_string(), 10, Some(HashMap::from([(3, 0), (4, 1)])));
+    assert!(backend.validate_circuit(&circuit).is_ok());
+
+    let mut circuit = Circuit::new();
+    circuit += RotateXY::new(2, PI.into(), 0.0.into());
+    circuit += DefinitionBit::new("ro".to_string(), 2, true);
+    circuit += PragmaRepeatedMeasurement::new("ro".to_string(), 10, None);
+    assert!(matches!(
+        backend.validate_circuit(&circuit),
+        Err(IqmBackendError::RegisterTooSmall { .. })
+    ));
+}
+
+#[test]
+fn feed_forward_not_supported() {
+    let device = GarnetDevice::new();
+    let backend = Backend::new(device.into(), Some("dummy_token".to_string())).unwrap();
+
+    let mut conditional_circuit = Circuit::new();
+    conditional_circuit += RotateXY::new(1, PI.into(), 0.0.into());
+
+    let mut circuit = Circuit::new();
+    circuit += DefinitionBit::new("ro".to_string(), 2, true);
+    circuit += RotateXY::new(0, PI.into(), 0.0.into());
+    circuit += MeasureQubit::new(0, "ro".to_string(), 0);
+    circuit += PragmaConditional::new("ro".to_string(), 0, conditional_circuit.clone());
+    circuit += MeasureQubit::new(1, "ro".to_string(), 1);
+
+    match backend.validate_circuit(&circuit) {
+        Err(IqmBackendError::InvalidCircuit { msg }) => assert!(msg.contains("feed-forward")),
+        res => panic!("Unexpected validation result: {:?}", res),
+    }
+
+    // Conditional operations nested in a loop are rejected as well
+    let mut loop_circuit = Circuit::new();
+    loop_circuit += PragmaConditional::new("ro".to_string(), 0, conditional_circuit);
+    let mut circuit = Circuit::new();
+    circuit += DefinitionBit::new("ro".to_string(), 2, true);
+    circuit += MeasureQubit::new(0, "ro".to_string(), 0);
+    circuit += PragmaLoop::new(2.into(), loop_circuit);
+    circuit += MeasureQubit::new(1, "ro".to_string(), 1);
+
+    match backend.validate_circuit(&circuit) {
+        Err(IqmBackendError::InvalidCircuit { msg }) => assert!(msg.contains("feed-forward")),
+        res => panic!("Unexpected validation result: {:?}", res),
+    }
+}
+
+#[test]
+fn unsupported_measurements() {
+    let device = DenebDevice::new();
+    let backend = Backend::new(device.into(), Some("dummy_token".to_string())).unwrap();
+
+    let mut circuit = Circuit::new();
+    circuit += DefinitionComplex::new("state".to_string(), 64, true);
+    circuit += RotateXY::new(0, PI.into(), 0.0.into());
+    circuit += PragmaGetStateVector::new("state".to_string(), None);
+    match backend.validate_circuit(&circuit) {
+        Err(IqmBackendError::InvalidCircuit { msg }) => {
+            assert!(msg.contains("PragmaGetStateVector"))
+        }
+        res => panic!("Unexpected validation result: {:?}", res),
+    }
+
+    // Measurements nested in a loop are rejected as well
+    let mut loop_circuit = Circuit::new();
+    loop_circuit += PragmaGetOccupationProbability::new("occupation".to_string(), None);
+    let mut circuit = Circuit::new();
+    circuit += DefinitionFloat::new("occupation".to_string(), 6, true);
+    circuit += DefinitionBit::new("ro".to_string(), 6, true);
+    circuit += RotateXY::new(0, PI.into(), 0.0.into());
+    circuit += PragmaLoop::new(2.into(), loop_circuit);
+    circuit += PragmaRepeatedMeasurement::new("ro".to_string(), 10, None);
+    match backend.validate_circuit(&circuit) {
+        Err(IqmBackendError::InvalidCircuit { msg }) => {
+            assert!(msg.contains("PragmaGetOccupationProbability"))
+        }
+        res => panic!("Unexpected validation result: {:?}", res),
+    }
+}
+
+#[test]
+fn batch_exceeding_device_qubits() {
+    let device = DenebDevice::new();
+    let backend = Backend::new(device.into(), Some("dummy_token".to_string())).unwrap();
+
+    let mut small_circuit = Circuit::new();
+    small_circuit += DefinitionBit::new("ro_0".to_string(), 2, true);
+    small_circuit += RotateXY::new(1, PI.into(), 0.0.into());
+    small_circuit += MeasureQubit::new(0, "ro_0".to_string(), 0);
+    small_circuit += MeasureQubit::new(1, "ro_0".to_string(), 1);
+
+    let mut large_circuit = Circuit::new();
+    large_circuit += DefinitionBit::new("ro_1".to_string(), 1, true);
+    large_circuit += RotateXY::new(14, PI.into(), 0.0.into());
+    large_circuit += MeasureQubit::new(14, "ro_1".to_string(), 0);
+
+    match backend.validate_circuit_batch(&[small_circuit.clone(), large_circuit]) {
+        Err(IqmBackendError::InvalidCircuit { msg }) => {
+            assert!(msg.contains("up to 15 qubits"));
+            assert!(msg.contains("only 6 qubits"));
+            assert!(msg.contains("1 (15 qubits)"));
+        }
+        res => panic!("Unexpected validation result: {:?}", res),
+    }
+    assert!(backend.validate_circuit_batch(&[small_circuit]).is_ok());
+}
+
+#[test]
+fn validate_circuit_batch_detailed() {
+    let device = DenebDevice::new();
+    let backend = Backend::new(device.into(), Some("dummy_token".to_string())).unwrap();
+
+    let valid_circuit = |register: &str| {
+        let mut circuit = Circuit::new();
+        circuit += DefinitionBit::new(register.to_string(), 1, true);
+        circuit += RotateXY::new(0, PI.into(), 0.0.into());
+        circuit += MeasureQubit::new(0, register.to_string(), 0);
+        circuit
+    };
+    let mut invalid_circuit = Circuit::new();
+    invalid_circuit += DefinitionBit::new("ro_1".to_string(), 1, true);
+    invalid_circuit += RotateXY::new(14, PI.into(), 0.0.into());
+    invalid_circuit += MeasureQubit::new(14, "ro_1".to_string(), 0);
+
+    let results = backend.validate_circuit_batch_detailed(&[
+        valid_circuit("ro_0"),
+        invalid_circuit,
+        valid_circuit("ro_2"),
+    ]);
+    assert_eq!(results.len(), 3);
+    assert!(results[0].is_ok());
+    assert!(matches!(
+        results[1],
+        Err(IqmBackendError::InvalidCircuit { .. })
+    ));
+    assert!(results[2].is_ok());
+
+    let results =
+        backend.validate_circuit_batch_detailed(&[valid_circuit("ro"), valid_circuit("ro")]);
+    assert!(results.iter().all(|res| res.is_err()));
+}
+
+#[test]
+fn validate_virtual_z_rotations() {
+    let circuit = |qubit: usize| {
+        let mut circuit = Circuit::new();
+        circuit += DefinitionBit::new("ro".to_string(), 1, true);
+        circuit += RotateZ::new(qubit, 0.5.into());
+        circuit += RotateXY::new(qubit, PI.into(), 0.0.into());
+        circuit += MeasureQubit::new(qubit, "ro".to_string(), 0);
+        circuit
+    };
+    let devices: [IqmDevice; 2] = [GarnetDevice::new().into(), DenebDevice::new().into()];
+    for device in devices {
+        assert_eq!(device.single_qubit_gate_time("RotateZ", &0), Some(0.0));
+        let number_qubits = device.number_qubits();
+        let backend = Backend::new(device, Some("dummy_token".to_string())).unwrap();
+        assert!(backend.validate_circuit(&circuit(0)).is_ok());
+        assert!(backend.validate_circuit(&circuit(number_qubits)).is_err());
+    }
+}
+
+#[test]
+fn validate_barriers() {
+    let mut circuit = Circuit::new();
+    circuit += DefinitionBit::new("ro".to_string(), 2, true);
+    circuit += RotateXY::new(0, PI.into(), 0.0.into());
+    circuit += PragmaStopParallelBlock::new(vec![0, 1], 0.0.into());
+    circuit += RotateXY::new(1, PI.into(), 0.0.into());
+    circuit += MeasureQubit::new(0, "ro".to_string(), 0);
+    circuit += MeasureQubit::new(1, "ro".to_string(), 1);
+    let devices: [IqmDevice; 2] = [GarnetDevice::new().into(), DenebDevice::new().into()];
+    for device in devices {
+        let backend = Backend::new(device, Some("dummy_token".to_string())).unwrap();
+        assert!(backend.validate_circuit(&circuit).is_ok());
+    }
+}
+
+#[test]
+fn submit_checked_failure_categories() {
+    let mut server = mockito::Server::new();
+    let mut device = GarnetDevice::new();
+    device.set_endpoint_url(format!("{}/garnet/jobs", server.url()));
+    let mut backend = Backend::new(device.into(), Some("dummy_token".to_string())).unwrap();
+    backend.set_allow_http(true);
+
+    let valid_circuit = |register: &str| {
+        let mut circuit = Circuit::new();
+        circuit += DefinitionBit::new(register.to_string(), 1, true);
+        circuit += RotateXY::new(0, PI.into(), 0.0.into());
+        circuit += MeasureQubit::new(0, register.to_string(), 0);
+        circuit
+    };
+    let invalid_circuit = |register: &str| {
+        let mut circuit = Circuit::new();
+        circuit += DefinitionBit::new(register.to_string(), 1, true);
+        circuit += RotateXY::new(30, PI.into(), 0.0.into());
+        circuit += MeasureQubit::new(30, register.to_string(), 0);
+        circuit
+    };
+
+    // All invalid circuits are reported, without a request to the server
+    match backend.submit_checked(&[
+        invalid_circuit("ro_0"),
+        valid_circuit("ro_1"),
+        invalid_circuit("ro_2"),
+    ]) {
+        Err(SubmitError::Validation(errors)) => assert_eq!(errors.len(), 2),
+        res => panic!("Unexpected result: {:?}", res),
+    }
+
+    let failed_post = server
+        .mock("POST", "/garnet/jobs")
+        .with_status(503)
+        .expect(1)
+        .create();
+    match backend.submit_checked(&[valid_circuit("ro")]) {
+        Err(SubmitError::Network(RoqoqoBackendError::NetworkError { .. })) => {}
+        res => panic!("Unexpected result: {:?}", res),
+    }
+    failed_post.assert();
+    failed_post.remove();
+
+    server
+        .mock("POST", "/garnet/jobs")
+        .with_status(201)
+        .with_body(r#"{"id": "job_id"}"#)
+        .create();
+    assert_eq!(
+        backend.submit_checked(&[valid_circuit("ro")]).unwrap(),
+        "job_id"
+    );
+}
+
+#[test]
+fn validation_levels() {
+    let device = GarnetDevice::new();
+    let mut backend = Backend::new(device.into(), Some("dummy_token".to_string())).unwrap();
+
+    // Supported by the device, but never measured
+    let mut unmeasured = Circuit::new();
+    unmeasured += DefinitionBit::new("ro".to_string(), 2, true);
+    unmeasured += ControlledPauliZ::new(1, 2);
+    // Acting on qubits that are not coupled
+    let mut disconnected = Circuit::new();
+    disconnected += DefinitionBit::new("ro".to_string(), 2, true);
+    disconnected += ControlledPauliZ::new(1, 5);
+    disconnected += MeasureQubit::new(1, "ro".to_string(), 0);
+    disconnected += MeasureQubit::new(5, "ro".to_string(), 1);
+
+    assert!(backend.validate_circuit(&unmeasured).is_err());
+    assert!(backend.validate_circuit(&disconnected).is_err());
+
+    backend.set_validation_level(ValidationLevel::Connectivity);
+    assert!(backend.validate_circuit(&unmeasured).is_ok());
+    assert!(backend.validate_circuit(&disconnected).is_err());
+    assert!(backend
+        .validate_circuit_batch(&[unmeasured.clone(), unmeasured.clone()])
+        .is_ok());
+
+    backend.set_validation_level(ValidationLevel::Off);
+    assert!(backend.validate_circuit(&unmeasured).is_ok());
+    assert!(backend.validate_circuit(&disconnected).is_ok());
+    assert!(backend
+        .validate_circuit_batch(&[disconnected.clone(), disconnected])
+        .is_ok());
+}
+
+#[test]
+fn auto_resonator_cz() {
+    let device = DenebDevice::new();
+    let mut backend = Backend::new(device.into(), Some("dummy_token".to_string())).unwrap();
+
+    // Consecutive gates rotating the qubits in between respect the load/store constraints
+    let mut circuit = Circuit::new();
+    circuit += DefinitionBit::new("ro".to_string(), 6, true);
+    circuit += RotateXY::new(0, PI.into(), 0.0.into());
+    circuit += ControlledPauliZ::new(0, 1);
+    circuit += RotateXY::new(1, PI.into(), 0.0.into());
+    circuit += ControlledPauliZ::new(1, 2);
+    circuit += PragmaRepeatedMeasurement::new("ro".to_string(), 10, None);
+
+    assert!(backend.validate_circuit(&circuit).is_err());
+
+    backend.set_auto_resonator_cz(true);
+    assert!(backend.validate_circuit(&circuit).is_ok());
+    let explanation = backend.explain_compilation(&circuit).unwrap();
+    let instructions: Vec<(&str, &str)> = explanation
+        .lines()
+        .skip(1)
+        .map(|line| {
+            let columns: Vec<&str> = line.split_whitespace().collect();
+            (columns[1], columns[2])
+        })
+        .collect();
+    assert_eq!(
+        instructions,
+        vec![
+            ("prx", "QB1"),
+            ("move", "QB2,COMP_R"),
+            ("cz", "QB1,COMP_R"),
+            ("move", "QB2,COMP_R"),
+            ("prx", "QB2"),
+            ("move", "QB3,COMP_R"),
+            ("cz", "QB2,COMP_R"),
+            ("move", "QB3,COMP_R"),
+            ("measure", "QB1,QB2,QB3,QB4,QB5,QB6"),
+        ]
+    );
+
+    // No resonator is involved on other devices
+    let mut backend =
+        Backend::new(GarnetDevice::new().into(), Some("dummy_token".to_string())).unwrap();
+    backend.set_auto_resonator_cz(true);
+    let mut circuit = Circuit::new();
+    circuit += DefinitionBit::new("ro".to_string(), 2, true);
+    circuit += ControlledPauliZ::new(1, 2);
+    circuit += MeasureQubit::new(1, "ro".to_string(), 0);
+    circuit += MeasureQubit::new(2, "ro".to_string(), 1);
+    assert!(backend.validate_circuit(&circuit).is_ok());
+}
+
+#[test]
+fn decompose_multiqubit_gates() {
+    let device = GarnetDevice::new();
+    let mut backend = Backend::new(device.into(), Some("dummy_token".to_string())).unwrap();
+
+    let mut circuit = Circuit::new();
+    circuit += DefinitionBit::new("ro".to_string(), 20, true);
+    circuit += ControlledControlledPauliZ::new(1, 5, 2);
+    circuit += PragmaRepeatedMeasurement::new("ro".to_string(), 10, None);
+
+    assert!(backend.validate_circuit(&circuit).is_err());
+
+    backend.set_decompose_multiqubit(true);
+    assert!(backend.validate_circuit(&circuit).is_ok());
+    assert!(backend.measurement_key_map(&circuit).is_ok());
+
+    let mut circuit = Circuit::new();
+    circuit += DefinitionBit::new("ro".to_string(), 20, true);
+    circuit += ControlledControlledPauliZ::new(1, 5, 7);
+    circuit += PragmaRepeatedMeasurement::new("ro".to_string(), 10, None);
+    assert!(matches!(
+        backend.validate_circuit(&circuit),
+        Err(IqmBackendError::InvalidCircuit { .. })
+    ));
+}
+
 #[test]
 fn too_many_qubits_deneb() {
     let device = DenebDevice::new();
@@ -400,3 +792,2681 @@ fn test_overwrite_number_measurements() {
     backend._overwrite_number_of_measurements(20);
     assert_eq!(backend.number_measurements_internal.unwrap(), 20);
 }
+
+/// Body of a mocked response to a result query, for a job running `circuit_metadata` with
+/// `measurements` as results.
+fn mock_ready_response(
+    circuit_metadata: serde_json::Value,
+    measurements: serde_json::Value,
+) -> String {
+    serde_json::json!({
+        "status": "ready",
+        "measurements": [measurements],
+        "metadata": {
+            "request": {
+                "circuits": [{"name": "qc_0", "instructions": [], "metadata": circuit_metadata}],
+                "shots": 2,
+                "circuit_duration_check": false,
+                "heralding_mode": "none"
+            }
+        }
+    })
+    .to_string()
+}
+
+#[test]
+fn test_expand_parameter_sweep() {
+    let mut circuit = Circuit::new();
+    circuit += RotateXY::new(0, "amplitude".into(), "phase".into());
+    circuit += DefinitionBit::new("ro".to_string(), 1, true);
+    circuit += MeasureQubit::new(0, "ro".to_string(), 0);
+
+    let mut parameter_sets = vec![];
+    for amplitude in [PI / 2.0, PI] {
+        for phase in [0.0, PI / 2.0] {
+            parameter_sets.push(HashMap::from([
+                ("amplitude".to_string(), amplitude),
+                ("phase".to_string(), phase),
+            ]));
+        }
+    }
+    let circuits = expand_parameter_sweep(&circuit, &parameter_sets).unwrap();
+    assert_eq!(circuits.len(), 4);
+    for (expanded, parameters) in circuits.iter().zip(parameter_sets.iter()) {
+        let mut expected = Circuit::new();
+        expected += RotateXY::new(
+            0,
+            parameters["amplitude"].into(),
+            parameters["phase"].into(),
+        );
+        expected += DefinitionBit::new("ro".to_string(), 1, true);
+        expected += MeasureQubit::new(0, "ro".to_string(), 0);
+        assert_eq!(expanded, &expected);
+    }
+
+    // Parameter sets must define all parameters
+    let missing_phase = [HashMap::from([("amplitude".to_string(), PI)])];
+    assert!(matches!(
+        expand_parameter_sweep(&circuit, &missing_phase),
+        Err(IqmBackendError::InvalidCircuit { .. })
+    ));
+}
+
+#[test]
+fn run_parameter_sweep_submits_single_batch() {
+    let mut server = mockito::Server::new();
+    let mut device = GarnetDevice::new();
+    device.set_endpoint_url(format!("{}/garnet/jobs", server.url()));
+    let mut backend = Backend::new(device.into(), Some("dummy_token".to_string())).unwrap();
+    backend.set_allow_http(true);
+
+    let mut circuit = Circuit::new();
+    circuit += RotateXY::new(0, "amplitude".into(), "phase".into());
+    circuit += DefinitionBit::new("ro".to_string(), 1, true);
+    circuit += MeasureQubit::new(0, "ro".to_string(), 0);
+
+    // Symbolic parameters are rejected before submission
+    match backend.submit_circuit_batch(&[circuit.clone()]) {
+        Err(IqmBackendError::InvalidCircuit { msg }) => {
+            assert!(msg.contains("symbolic parameter"), "{}", msg)
+        }
+        res => panic!("Unexpected result: {:?}", res),
+    }
+
+    let parameter_sets: Vec<HashMap<String, f64>> = [(0.0, 0.0), (0.0, PI), (PI, 0.0), (PI, PI)]
+        .iter()
+        .map(|(amplitude, phase)| {
+            HashMap::from([
+                ("amplitude".to_string(), *amplitude),
+                ("phase".to_string(), *phase),
+            ])
+        })
+        .collect();
+
+    let submit_mock = server
+        .mock("POST", "/garnet/jobs")
+        .match_body(mockito::Matcher::PartialJson(serde_json::json!({
+            "circuits": [
+                {"instructions": [{"name": "prx", "args": {"angle_t": 0.0, "phase_t": 0.0}}]},
+                {"instructions": [{"name": "prx", "args": {"angle_t": 0.0, "phase_t": 0.5}}]},
+                {"instructions": [{"name": "prx", "args": {"angle_t": 0.5, "phase_t": 0.0}}]},
+                {"instructions": [{"name": "prx", "args": {"angle_t": 0.5, "phase_t": 0.5}}]}
+            ]
+        })))
+        .with_status(201)
+        .with_body(r#"{"id": "job_id"}"#)
+        .expect(1)
+        .create();
+    // The registers are renamed, since the circuits of a batch cannot share registers
+    let shots = [[[0], [0]], [[0], [0]], [[1], [1]], [[1], [0]]];
+    server
+        .mock("GET", "/garnet/jobs/job_id")
+        .with_status(200)
+        .with_body(
+            serde_json::json!({
+                "status": "ready",
+                "measurements": (0..4)
+                    .map(|index| serde_json::json!({format!("ro__sweep_{}", index): shots[index]}))
+                    .collect::<Vec<serde_json::Value>>(),
+                "metadata": {
+                    "request": {
+                        "circuits": (0..4)
+                            .map(|index| serde_json::json!({
+                                "name": format!("qc_{}", index),
+                                "instructions": [],
+                                "metadata": {format!("ro__sweep_{}", index): [[0], 1]}
+                            }))
+                            .collect::<Vec<serde_json::Value>>(),
+                        "shots": 2,
+                        "circuit_duration_check": false,
+                        "heralding_mode": "none"
+                    }
+                }
+            })
+            .to_string(),
+        )
+        .create();
+
+    let registers = backend
+        .run_parameter_sweep(&circuit, &parameter_sets)
+        .unwrap();
+    submit_mock.assert();
+    assert_eq!(registers.len(), 4);
+    assert_eq!(registers[0].0["ro"], vec![vec![false], vec![false]]);
+    assert_eq!(registers[2].0["ro"], vec![vec![true], vec![true]]);
+    assert_eq!(registers[3].0["ro"], vec![vec![true], vec![false]]);
+}
+
+#[test]
+fn run_circuit_accumulate_splits_jobs() {
+    let mut server = mockito::Server::new();
+    let mut device = GarnetDevice::new();
+    device.set_endpoint_url(format!("{}/garnet/jobs", server.url()));
+    let mut backend = Backend::new(device.into(), Some("dummy_token".to_string())).unwrap();
+    backend.set_allow_http(true);
+
+    // Each job gets its own ID, since the results of finished jobs are cached
+    let job_counter = std::sync::atomic::AtomicUsize::new(0);
+    let submit_mock = server
+        .mock("POST", "/garnet/jobs")
+        .match_body(mockito::Matcher::PartialJson(
+            serde_json::json!({"shots": 10000}),
+        ))
+        .with_status(201)
+        .with_body_from_request(move |_| {
+            let job = job_counter.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            format!(r#"{{"id": "job_{}"}}"#, job).into()
+        })
+        .expect(3)
+        .create();
+    let results_mock = server
+        .mock(
+            "GET",
+            mockito::Matcher::Regex(r"^/garnet/jobs/job_\d$".to_string()),
+        )
+        .with_status(200)
+        .with_body(mock_ready_response(
+            serde_json::json!({"ro": [[0, 1], 2]}),
+            serde_json::json!({"ro": [[1, 0], [0, 1]]}),
+        ))
+        .expect(3)
+        .create();
+
+    let mut circuit = Circuit::new();
+    circuit += RotateXY::new(0, PI.into(), 0.0.into());
+    circuit += DefinitionBit::new("ro".to_string(), 2, true);
+    circuit += MeasureQubit::new(0, "ro".to_string(), 0);
+    circuit += MeasureQubit::new(1, "ro".to_string(), 1);
+
+    let (bit_registers, _, _) = backend.run_circuit_accumulate(&circuit, 30000).unwrap();
+
+    submit_mock.assert();
+    results_mock.assert();
+    // Each mocked job returns two shots
+    assert_eq!(bit_registers["ro"].len(), 6);
+    assert_eq!(bit_registers["ro"][0], vec![true, false]);
+    assert_eq!(bit_registers["ro"][5], vec![false, true]);
+}
+
+#[test]
+fn run_circuit_timed() {
+    let mut server = mockito::Server::new();
+    let mut device = GarnetDevice::new();
+    device.set_endpoint_url(format!("{}/garnet/jobs", server.url()));
+    let mut backend = Backend::new(device.into(), Some("dummy_token".to_string())).unwrap();
+    backend.set_allow_http(true);
+
+    server
+        .mock("POST", "/garnet/jobs")
+        .with_status(201)
+        .with_body(r#"{"id": "job_id"}"#)
+        .create();
+    server
+        .mock("GET", "/garnet/jobs/job_id")
+        .with_status(200)
+        .with_body(mock_ready_response(
+            serde_json::json!({"ro": [[0], 1]}),
+            serde_json::json!({"ro": [[1], [0]]}),
+        ))
+        .create();
+
+    let mut circuit = Circuit::new();
+    circuit += RotateXY::new(0, PI.into(), 0.0.into());
+    circuit += DefinitionBit::new("ro".to_string(), 1, true);
+    circuit += MeasureQubit::new(0, "ro".to_string(), 0);
+
+    let start = Instant::now();
+    let ((bit_registers, _, _), timing) = backend.run_circuit_timed(&circuit).unwrap();
+    let elapsed = start.elapsed();
+
+    assert_eq!(bit_registers["ro"], vec![vec![true], vec![false]]);
+    assert!(timing.submit > Duration::ZERO);
+    assert!(timing.wait > Duration::ZERO);
+    assert!(timing.submit + timing.wait + timing.processing <= elapsed);
+}
+
+#[test]
+fn run_circuit_registers_to_csv() {
+    let mut server = mockito::Server::new();
+    let mut device = GarnetDevice::new();
+    device.set_endpoint_url(format!("{}/garnet/jobs", server.url()));
+    let mut backend = Backend::new(device.into(), Some("dummy_token".to_string())).unwrap();
+    backend.set_allow_http(true);
+
+    server
+        .mock("POST", "/garnet/jobs")
+        .with_status(201)
+        .with_body(r#"{"id": "job_id"}"#)
+        .create();
+    server
+        .mock("GET", "/garnet/jobs/job_id")
+        .with_status(200)
+        .with_body(mock_ready_response(
+            serde_json::json!({"ro": [[0, 1], 2]}),
+            serde_json::json!({"ro": [[1, 0], [1, 1]]}),
+        ))
+        .create();
+
+    let mut circuit = Circuit::new();
+    circuit += RotateXY::new(0, PI.into(), 0.0.into());
+    circuit += DefinitionBit::new("ro".to_string(), 2, true);
+    circuit += MeasureQubit::new(0, "ro".to_string(), 0);
+    circuit += MeasureQubit::new(1, "ro".to_string(), 1);
+
+    let registers = backend.run_circuit(&circuit).unwrap();
+    let csv = registers_to_csv(&registers, "ro");
+    let rows: Vec<&str> = csv.lines().collect();
+    assert_eq!(rows, vec!["shot,bit_0,bit_1", "0,1,0", "1,1,1"]);
+    assert!(registers_to_csv(&registers, "missing").is_empty());
+}
+
+fn mock_ready_without_measurements() -> String {
+    serde_json::json!({
+        "status": "ready",
+        "metadata": {
+            "request": {
+                "circuits": [{"name": "qc_0", "instructions": [], "metadata": {"ro": [[0], 1]}}],
+                "shots": 2,
+                "circuit_duration_check": false,
+                "heralding_mode": "none"
+            }
+        }
+    })
+    .to_string()
+}
+
+// Backend running a single-qubit circuit on a mock server reporting the job as ready without
+// measurements `empty_responses` times before returning the measurements.
+fn empty_result_backend(
+    server: &mut mockito::Server,
+    policy: EmptyResultPolicy,
+    empty_responses: usize,
+) -> (Backend, Circuit, mockito::Mock) {
+    let mut device = GarnetDevice::new();
+    device.set_endpoint_url(format!("{}/garnet/jobs", server.url()));
+    let mut backend = Backend::new(device.into(), Some("dummy_token".to_string())).unwrap();
+    backend.set_allow_http(true);
+    backend.set_polling_interval(0.01).unwrap();
+    backend.set_empty_result_policy(policy);
+
+    server
+        .mock("POST", "/garnet/jobs")
+        .with_status(201)
+        .with_body(r#"{"id": "job_id"}"#)
+        .create();
+    let empty_mock = server
+        .mock("GET", "/garnet/jobs/job_id")
+        .with_status(200)
+        .with_body(mock_ready_without_measurements())
+        .expect(empty_responses)
+        .create();
+    server
+        .mock("GET", "/garnet/jobs/job_id")
+        .with_status(200)
+        .with_body(mock_ready_response(
+            serde_json::json!({"ro": [[0], 1]}),
+            serde_json::json!({"ro": [[1], [0]]}),
+        ))
+        .create();
+
+    let mut circuit = Circuit::new();
+    circuit += RotateXY::new(0, PI.into(), 0.0.into());
+    circuit += DefinitionBit::new("ro".to_string(), 1, true);
+    circuit += MeasureQubit::new(0, "ro".to_string(), 0);
+    (backend, circuit, empty_mock)
+}
+
+#[test]
+fn empty_result_policy_error() {
+    let mut server = mockito::Server::new();
+    let (backend, circuit, empty_mock) =
+        empty_result_backend(&mut server, EmptyResultPolicy::Error, 1);
+    assert!(matches!(
+        backend.run_circuit(&circuit),
+        Err(RoqoqoBackendError::GenericError { .. })
+    ));
+    empty_mock.assert();
+}
+
+#[test]
+fn empty_result_policy_retry_then_error() {
+    let mut server = mockito::Server::new();
+    let (backend, circuit, empty_mock) =
+        empty_result_backend(&mut server, EmptyResultPolicy::RetryThenError, 2);
+    let (bit_registers, _, _) = backend.run_circuit(&circuit).unwrap();
+    assert_eq!(bit_registers["ro"], vec![vec![true], vec![false]]);
+    empty_mock.assert();
+
+    // The results are re-fetched three times before giving up
+    let mut server = mockito::Server::new();
+    let (backend, circuit, empty_mock) =
+        empty_result_backend(&mut server, EmptyResultPolicy::RetryThenError, 4);
+    let id = backend.submit_circuit_batch(&[circuit]).unwrap();
+    assert!(matches!(
+        backend.wait_for_results(id),
+        Err(IqmBackendError::EmptyResult { .. })
+    ));
+    empty_mock.assert();
+}
+
+#[test]
+fn empty_result_policy_return_empty() {
+    let mut server = mockito::Server::new();
+    let (backend, circuit, empty_mock) =
+        empty_result_backend(&mut server, EmptyResultPolicy::ReturnEmpty, 1);
+    let (bit_registers, float_registers, complex_registers) =
+        backend.run_circuit(&circuit).unwrap();
+    assert!(bit_registers.is_empty());
+    assert!(float_registers.is_empty());
+    assert!(complex_registers.is_empty());
+    empty_mock.assert();
+}
+
+#[test]
+fn get_results_cache() {
+    let mut server = mockito::Server::new();
+    let mut device = GarnetDevice::new();
+    device.set_endpoint_url(format!("{}/garnet/jobs", server.url()));
+    let mut backend = Backend::new(device.into(), Some("dummy_token".to_string())).unwrap();
+    backend.set_allow_http(true);
+
+    let ready_mock = server
+        .mock("GET", "/garnet/jobs/job_ready")
+        .with_status(200)
+        .with_body(mock_ready_response(
+            serde_json::json!({"ro": [[0], 1]}),
+            serde_json::json!({"ro": [[1], [0]]}),
+        ))
+        .expect(2)
+        .create();
+    let first = backend.get_results("job_ready".to_string()).unwrap();
+    let second = backend.get_results("job_ready".to_string()).unwrap();
+    assert_eq!(first, second);
+    backend.clear_result_cache();
+    backend.get_results("job_ready".to_string()).unwrap();
+    ready_mock.assert();
+
+    // Pending results are fetched again
+    let pending_mock = server
+        .mock("GET", "/garnet/jobs/job_pending")
+        .with_status(200)
+        .with_body(
+            serde_json::json!({
+                "status": "pending execution",
+                "metadata": {
+                    "request": {
+                        "circuits": [],
+                        "shots": 2,
+                        "circuit_duration_check": false,
+                        "heralding_mode": "none"
+                    }
+                }
+            })
+            .to_string(),
+        )
+        .expect(2)
+        .create();
+    backend.get_results("job_pending".to_string()).unwrap();
+    backend.get_results("job_pending".to_string()).unwrap();
+    pending_mock.assert();
+}
+
+#[test]
+fn run_parameter_sweep_reversed_results() {
+    let mut server = mockito::Server::new();
+    let mut device = GarnetDevice::new();
+    device.set_endpoint_url(format!("{}/garnet/jobs", server.url()));
+    let mut backend = Backend::new(device.into(), Some("dummy_token".to_string())).unwrap();
+    backend.set_allow_http(true);
+
+    let mut circuit = Circuit::new();
+    circuit += RotateXY::new(0, "amplitude".into(), 0.0.into());
+    circuit += DefinitionBit::new("ro".to_string(), 1, true);
+    circuit += MeasureQubit::new(0, "ro".to_string(), 0);
+    let parameter_sets: Vec<HashMap<String, f64>> = [0.0, PI, 0.0]
+        .iter()
+        .map(|amplitude| HashMap::from([("amplitude".to_string(), *amplitude)]))
+        .collect();
+
+    server
+        .mock("POST", "/garnet/jobs")
+        .with_status(201)
+        .with_body(r#"{"id": "job_id"}"#)
+        .create();
+    // The results are returned in reversed order relative to the submitted circuits
+    let shots = [[[0], [0]], [[1], [1]], [[0], [1]]];
+    server
+        .mock("GET", "/garnet/jobs/job_id")
+        .with_status(200)
+        .with_body(
+            serde_json::json!({
+                "status": "ready",
+                "measurements": (0..3)
+                    .rev()
+                    .map(|index| serde_json::json!({format!("ro__sweep_{}", index): shots[index]}))
+                    .collect::<Vec<serde_json::Value>>(),
+                "metadata": {
+                    "request": {
+                        "circuits": (0..3)
+                            .map(|index| serde_json::json!({
+                                "name": format!("qc_{}", index),
+                                "instructions": [],
+                                "metadata": {format!("ro__sweep_{}", index): [[0], 1]}
+                            }))
+                            .collect::<Vec<serde_json::Value>>(),
+                        "shots": 2,
+                        "circuit_duration_check": false,
+                        "heralding_mode": "none"
+                    }
+                }
+            })
+            .to_string(),
+        )
+        .create();
+
+    let registers = backend
+        .run_parameter_sweep(&circuit, &parameter_sets)
+        .unwrap();
+    assert_eq!(registers.len(), 3);
+    assert_eq!(registers[0].0["ro"], vec![vec![false], vec![false]]);
+    assert_eq!(registers[1].0["ro"], vec![vec![true], vec![true]]);
+    assert_eq!(registers[2].0["ro"], vec![vec![false], vec![true]]);
+
+    let raw_results = backend.get_raw_batch_result("job_id".to_string()).unwrap();
+    let registers: Vec<&String> = raw_results
+        .iter()
+        .flat_map(|result| result.keys())
+        .collect();
+    assert_eq!(registers, vec!["ro__sweep_0", "ro__sweep_1", "ro__sweep_2"]);
+}
+
+#[test]
+fn get_raw_batch_result() {
+    let mut server = mockito::Server::new();
+    let mut device = GarnetDevice::new();
+    device.set_endpoint_url(format!("{}/garnet/jobs", server.url()));
+    let mut backend = Backend::new(device.into(), Some("dummy_token".to_string())).unwrap();
+    backend.set_allow_http(true);
+
+    server
+        .mock("GET", "/garnet/jobs/job_id")
+        .with_status(200)
+        .with_body(mock_ready_response(
+            serde_json::json!({"ro": [[0, 2], 3]}),
+            serde_json::json!({"ro": [[1, 0], [0, 1]]}),
+        ))
+        .create();
+
+    let result = backend.get_raw_batch_result("job_id".to_string()).unwrap();
+    let expected: BatchResult = vec![HashMap::from([(
+        "ro".to_string(),
+        vec![vec![1, 0], vec![0, 1]],
+    )])];
+    assert_eq!(result, expected);
+}
+
+#[test]
+fn run_pauli_measurement() {
+    let mut server = mockito::Server::new();
+    let mut device = GarnetDevice::new();
+    device.set_endpoint_url(format!("{}/garnet/jobs", server.url()));
+    let mut backend = Backend::new(device.into(), Some("dummy_token".to_string())).unwrap();
+    backend.set_allow_http(true);
+
+    let circuit = |name: &str, register: &str, length: usize| {
+        serde_json::json!({
+            "name": name,
+            "instructions": [],
+            "metadata": {register: [(0..length).collect::<Vec<usize>>(), length]}
+        })
+    };
+    let response = serde_json::json!({
+        "status": "ready",
+        "measurements": [
+            {"pauli_0": [[0], [1]]},
+            {"pauli_1": [[0, 1], [0, 1]]},
+            {"pauli_2": [[1], [1]]}
+        ],
+        "metadata": {
+            "request": {
+                "circuits": [
+                    circuit("qc_0", "pauli_0", 1),
+                    circuit("qc_1", "pauli_1", 2),
+                    circuit("qc_2", "pauli_2", 1)
+                ],
+                "shots": 2,
+                "heralding_mode": "none"
+            }
+        }
+    });
+    let submit_mock = server
+        .mock("POST", "/garnet/jobs")
+        .match_body(mockito::Matcher::PartialJson(serde_json::json!({
+            "circuits": [{"name": "qc_0"}, {"name": "qc_1"}, {"name": "qc_2"}]
+        })))
+        .with_status(201)
+        .with_body(r#"{"id": "job_id"}"#)
+        .expect(1)
+        .create();
+    server
+        .mock("GET", "/garnet/jobs/job_id")
+        .with_status(200)
+        .with_body(response.to_string())
+        .create();
+
+    // Prepares qubit 0 in |0> and qubit 1 in |1>
+    let mut base_circuit = Circuit::new();
+    base_circuit += RotateXY::new(1, PI.into(), 0.0.into());
+    let pauli_terms = HashMap::from([
+        ("I".to_string(), vec![]),
+        ("X0".to_string(), vec![(0, PauliBasis::X)]),
+        (
+            "Z0Z1".to_string(),
+            vec![(0, PauliBasis::Z), (1, PauliBasis::Z)],
+        ),
+        ("Z1".to_string(), vec![(1, PauliBasis::Z)]),
+    ]);
+
+    let expectation_values = backend
+        .run_pauli_measurement(&base_circuit, &pauli_terms)
+        .unwrap();
+
+    submit_mock.assert();
+    assert_eq!(
+        expectation_values,
+        HashMap::from([
+            ("I".to_string(), 1.0),
+            ("X0".to_string(), 0.0),
+            ("Z0Z1".to_string(), -1.0),
+            ("Z1".to_string(), -1.0),
+        ])
+    );
+}
+
+#[test]
+fn msgpack_request_format() {
+    let mut server = mockito::Server::new();
+    let mut device = GarnetDevice::new();
+    device.set_endpoint_url(format!("{}/garnet/jobs", server.url()));
+    let mut backend = Backend::new(device.into(), Some("dummy_token".to_string())).unwrap();
+    backend.set_allow_http(true);
+
+    let bodies: Arc<Mutex<Vec<serde_json::Value>>> = Arc::new(Mutex::new(vec![]));
+    let json_bodies = bodies.clone();
+    let json_post = server
+        .mock("POST", "/garnet/jobs")
+        .match_header("content-type", "application/json")
+        .with_status(201)
+        .with_body_from_request(move |request| {
+            json_bodies
+                .lock()
+                .unwrap()
+                .push(serde_json::from_slice(request.body().unwrap()).unwrap());
+            br#"{"id": "job_id"}"#.to_vec()
+        })
+        .expect(1)
+        .create();
+    let msgpack_bodies = bodies.clone();
+    let msgpack_post = server
+        .mock("POST", "/garnet/jobs")
+        .match_header("content-type", "application/msgpack")
+        .with_status(201)
+        .with_body_from_request(move |request| {
+            msgpack_bodies
+                .lock()
+                .unwrap()
+                .push(rmp_serde::from_slice(request.body().unwrap()).unwrap());
+            br#"{"id": "job_id"}"#.to_vec()
+        })
+        .expect(1)
+        .create();
+
+    let mut circuit = Circuit::new();
+    circuit += DefinitionBit::new("ro".to_string(), 2, true);
+    circuit += RotateXY::new(0, PI.into(), 0.5.into());
+    circuit += ControlledPauliZ::new(1, 2);
+    circuit += MeasureQubit::new(0, "ro".to_string(), 0);
+    circuit += MeasureQubit::new(1, "ro".to_string(), 1);
+
+    backend.submit_circuit_batch(&[circuit.clone()]).unwrap();
+    backend.set_request_format(RequestFormat::MsgPack);
+    backend.submit_circuit_batch(&[circuit]).unwrap();
+
+    json_post.assert();
+    msgpack_post.assert();
+    let bodies = bodies.lock().unwrap();
+    assert_eq!(bodies.len(), 2);
+    assert_eq!(bodies[0]["circuits"][0]["name"], "qc_0");
+    assert_eq!(bodies[1], bodies[0]);
+}
+
+#[test]
+fn run_circuit_batch_returns_warnings() {
+    let mut server = mockito::Server::new();
+    let mut device = GarnetDevice::new();
+    device.set_endpoint_url(format!("{}/garnet/jobs", server.url()));
+    let mut backend = Backend::new(device.into(), Some("dummy_token".to_string())).unwrap();
+    backend.set_allow_http(true);
+
+    let mut response: serde_json::Value = serde_json::from_str(&mock_ready_response(
+        serde_json::json!({"ro": [[0], 1]}),
+        serde_json::json!({"ro": [[1], [0]]}),
+    ))
+    .unwrap();
+    response["warnings"] = serde_json::json!(["Calibration data is stale."]);
+
+    server
+        .mock("POST", "/garnet/jobs")
+        .with_status(201)
+        .with_body(r#"{"id": "job_id"}"#)
+        .create();
+    server
+        .mock("GET", "/garnet/jobs/job_id")
+        .with_status(200)
+        .with_body(response.to_string())
+        .create();
+
+    let mut circuit = Circuit::new();
+    circuit += RotateXY::new(0, PI.into(), 0.0.into());
+    circuit += DefinitionBit::new("ro".to_string(), 1, true);
+    circuit += MeasureQubit::new(0, "ro".to_string(), 0);
+
+    let ((bit_registers, _, _), warnings) =
+        backend.run_circuit_batch_with_warnings(&[circuit]).unwrap();
+
+    assert_eq!(bit_registers["ro"], vec![vec![true], vec![false]]);
+    assert_eq!(warnings, vec!["Calibration data is stale.".to_string()]);
+}
+
+#[test]
+fn warnings_as_errors() {
+    let mut server = mockito::Server::new();
+    let mut device = GarnetDevice::new();
+    device.set_endpoint_url(format!("{}/garnet/jobs", server.url()));
+    let mut backend = Backend::new(device.into(), Some("dummy_token".to_string())).unwrap();
+    backend.set_allow_http(true);
+
+    let mut response: serde_json::Value = serde_json::from_str(&mock_ready_response(
+        serde_json::json!({"ro": [[0], 1]}),
+        serde_json::json!({"ro": [[1], [0]]}),
+    ))
+    .unwrap();
+    response["warnings"] = serde_json::json!(["Calibration data is stale."]);
+
+    server
+        .mock("POST", "/garnet/jobs")
+        .with_status(201)
+        .with_body(r#"{"id": "job_id"}"#)
+        .create();
+    server
+        .mock("GET", "/garnet/jobs/job_id")
+        .with_status(200)
+        .with_body(response.to_string())
+        .create();
+
+    let mut circuit = Circuit::new();
+    circuit += RotateXY::new(0, PI.into(), 0.0.into());
+    circuit += DefinitionBit::new("ro".to_string(), 1, true);
+    circuit += MeasureQubit::new(0, "ro".to_string(), 0);
+
+    let (bit_registers, _, _) = backend.run_circuit_batch(&[circuit.clone()]).unwrap();
+    assert_eq!(bit_registers["ro"], vec![vec![true], vec![false]]);
+
+    backend.set_warnings_as_errors(true);
+    match backend.run_circuit_batch(&[circuit]) {
+        Err(IqmBackendError::WarningsPresent { id, warnings }) => {
+            assert_eq!(id, "job_id");
+            assert_eq!(warnings, vec!["Calibration data is stale.".to_string()]);
+        }
+        res => panic!("Unexpected result: {:?}", res),
+    }
+}
+
+#[test]
+fn last_warnings() {
+    let mut server = mockito::Server::new();
+    let mut device = GarnetDevice::new();
+    device.set_endpoint_url(format!("{}/garnet/jobs", server.url()));
+    let mut backend = Backend::new(device.into(), Some("dummy_token".to_string())).unwrap();
+    backend.set_allow_http(true);
+    backend.set_print_warnings(false);
+    assert_eq!(backend.get_last_warnings(), None);
+
+    let mut response: serde_json::Value = serde_json::from_str(&mock_ready_response(
+        serde_json::json!({"ro": [[0], 1]}),
+        serde_json::json!({"ro": [[1], [0]]}),
+    ))
+    .unwrap();
+    response["warnings"] = serde_json::json!(["Calibration data is stale."]);
+
+    server
+        .mock("POST", "/garnet/jobs")
+        .with_status(201)
+        .with_body(r#"{"id": "job_id"}"#)
+        .create();
+    server
+        .mock("GET", "/garnet/jobs/job_id")
+        .with_status(200)
+        .with_body(response.to_string())
+        .create();
+
+    let mut circuit = Circuit::new();
+    circuit += RotateXY::new(0, PI.into(), 0.0.into());
+    circuit += DefinitionBit::new("ro".to_string(), 1, true);
+    circuit += MeasureQubit::new(0, "ro".to_string(), 0);
+
+    backend.run_circuit_batch(&[circuit]).unwrap();
+    assert_eq!(
+        backend.get_last_warnings(),
+        Some(vec!["Calibration data is stale.".to_string()])
+    );
+
+    // The print flag is serialized with the backend
+    let serialized = serde_json::to_string(&backend).unwrap();
+    let deserialized: Backend = serde_json::from_str(&serialized).unwrap();
+    assert_eq!(deserialized, backend);
+    backend.set_print_warnings(true);
+    assert_ne!(deserialized, backend);
+}
+
+#[test]
+fn calibration_set_id_mismatch() {
+    let mut server = mockito::Server::new();
+    let mut device = GarnetDevice::new();
+    device.set_endpoint_url(format!("{}/garnet/jobs", server.url()));
+    let mut backend = Backend::new(device.into(), Some("dummy_token".to_string())).unwrap();
+    backend.set_allow_http(true);
+
+    let mut response: serde_json::Value = serde_json::from_str(&mock_ready_response(
+        serde_json::json!({"ro": [[0], 1]}),
+        serde_json::json!({"ro": [[1], [0]]}),
+    ))
+    .unwrap();
+    response["metadata"]["calibration_set_id"] = serde_json::json!("cal_2");
+
+    server
+        .mock("GET", "/garnet/jobs/job_id")
+        .with_status(200)
+        .with_body(response.to_string())
+        .create();
+
+    let mut circuit = Circuit::new();
+    circuit += RotateXY::new(0, PI.into(), 0.0.into());
+    circuit += DefinitionBit::new("ro".to_string(), 1, true);
+    circuit += MeasureQubit::new(0, "ro".to_string(), 0);
+
+    backend.set_calibration_set_id(Some("cal_1".to_string()));
+    let pinned_post = server
+        .mock("POST", "/garnet/jobs")
+        .match_body(mockito::Matcher::PartialJson(
+            serde_json::json!({"calibration_set_id": "cal_1"}),
+        ))
+        .with_status(201)
+        .with_body(r#"{"id": "job_id"}"#)
+        .expect(1)
+        .create();
+    assert!(matches!(
+        backend.run_circuit_batch(&[circuit.clone()]),
+        Err(IqmBackendError::MetadataError { .. })
+    ));
+    pinned_post.assert();
+
+    backend.set_calibration_set_id(Some("cal_2".to_string()));
+    server
+        .mock("POST", "/garnet/jobs")
+        .match_body(mockito::Matcher::PartialJson(
+            serde_json::json!({"calibration_set_id": "cal_2"}),
+        ))
+        .with_status(201)
+        .with_body(r#"{"id": "job_id"}"#)
+        .create();
+    let (bit_registers, _, _) = backend.run_circuit_batch(&[circuit]).unwrap();
+    assert_eq!(bit_registers["ro"], vec![vec![true], vec![false]]);
+}
+
+#[test]
+fn test_measurement_key_map() {
+    let device = GarnetDevice::new();
+    let backend = Backend::new(device.into(), Some("dummy_token".to_string())).unwrap();
+
+    let mut circuit = Circuit::new();
+    circuit += DefinitionBit::new("ro1".to_string(), 3, true);
+    circuit += DefinitionBit::new("ro2".to_string(), 2, true);
+    circuit += RotateXY::new(0, PI.into(), 0.0.into());
+    circuit += MeasureQubit::new(0, "ro1".to_string(), 2);
+    circuit += MeasureQubit::new(1, "ro1".to_string(), 0);
+    circuit += MeasureQubit::new(2, "ro2".to_string(), 1);
+
+    let map = backend.measurement_key_map(&circuit).unwrap();
+    assert_eq!(map.len(), 2);
+    assert_eq!(map["ro1"], (vec![2, 0], 3));
+    assert_eq!(map["ro2"], (vec![1], 2));
+
+    let mut invalid_circuit = Circuit::new();
+    invalid_circuit += MeasureQubit::new(0, "ro".to_string(), 0);
+    assert!(backend.measurement_key_map(&invalid_circuit).is_err());
+}
+
+#[test]
+fn test_check_authentication() {
+    let mut server = mockito::Server::new();
+    let mut device = DenebDevice::new();
+    device.set_endpoint_url(format!("{}/deneb/jobs", server.url()));
+    let mut backend = Backend::new(device.into(), Some("dummy_token".to_string())).unwrap();
+    backend.set_allow_http(true);
+
+    let accepted = server
+        .mock("GET", "/deneb/quantum-architecture")
+        .match_header("Authorization", "Bearer dummy_token")
+        .with_status(200)
+        .with_body("{}")
+        .create();
+    assert!(backend.check_authentication().is_ok());
+    accepted.assert();
+    accepted.remove();
+
+    server
+        .mock("GET", "/deneb/quantum-architecture")
+        .with_status(401)
+        .create();
+    assert!(matches!(
+        backend.check_authentication(),
+        Err(RoqoqoBackendError::MissingAuthentication { .. })
+    ));
+}
+
+#[test]
+fn refresh_expired_access_token() {
+    let mut server = mockito::Server::new();
+    let mut device = GarnetDevice::new();
+    device.set_endpoint_url(format!("{}/garnet/jobs", server.url()));
+    let mut backend = Backend::new(device.into(), Some("expired_token".to_string())).unwrap();
+    backend.set_allow_http(true);
+    backend.set_refresh_credentials(
+        "refresh_token".to_string(),
+        format!("{}/auth", server.url()),
+    );
+
+    let rejected = server
+        .mock("POST", "/garnet/jobs")
+        .match_header("Authorization", "Bearer expired_token")
+        .with_status(401)
+        .expect(1)
+        .create();
+    let refresh = server
+        .mock("POST", "/auth/realms/cortex/protocol/openid-connect/token")
+        .match_body(mockito::Matcher::AllOf(vec![
+            mockito::Matcher::UrlEncoded("grant_type".to_string(), "refresh_token".to_string()),
+            mockito::Matcher::UrlEncoded("refresh_token".to_string(), "refresh_token".to_string()),
+        ]))
+        .with_status(200)
+        .with_body(r#"{"access_token": "fresh_token", "refresh_token": "new_refresh_token"}"#)
+        .expect(1)
+        .create();
+    let accepted = server
+        .mock("POST", "/garnet/jobs")
+        .match_header("Authorization", "Bearer fresh_token")
+        .with_status(201)
+        .with_body(r#"{"id": "job_id"}"#)
+        .expect(1)
+        .create();
+
+    let mut circuit = Circuit::new();
+    circuit += RotateXY::new(0, PI.into(), 0.0.into());
+    circuit += DefinitionBit::new("ro".to_string(), 1, true);
+    circuit += MeasureQubit::new(0, "ro".to_string(), 0);
+    assert_eq!(backend.submit_circuit_batch(&[circuit]).unwrap(), "job_id");
+    rejected.assert();
+    refresh.assert();
+    accepted.assert();
+
+    // The refreshed token is used for the following requests, and the rotated refresh token for
+    // the next refresh
+    let results_rejected = server
+        .mock("GET", "/garnet/jobs/job_id")
+        .match_header("Authorization", "Bearer fresh_token")
+        .with_status(401)
+        .expect(1)
+        .create();
+    let second_refresh = server
+        .mock("POST", "/auth/realms/cortex/protocol/openid-connect/token")
+        .match_body(mockito::Matcher::UrlEncoded(
+            "refresh_token".to_string(),
+            "new_refresh_token".to_string(),
+        ))
+        .with_status(200)
+        .with_body(r#"{"access_token": "second_token", "refresh_token": "third_refresh_token"}"#)
+        .expect(1)
+        .create();
+    let results = server
+        .mock("GET", "/garnet/jobs/job_id")
+        .match_header("Authorization", "Bearer second_token")
+        .with_status(200)
+        .with_body(mock_ready_response(
+            serde_json::json!({"ro": [[0], 1]}),
+            serde_json::json!({"ro": [[1], [1]]}),
+        ))
+        .expect(1)
+        .create();
+    assert!(backend.get_results("job_id".to_string()).is_ok());
+    results_rejected.assert();
+    second_refresh.assert();
+    results.assert();
+
+    // Status queries refresh the token as well
+    let status_rejected = server
+        .mock("GET", "/garnet/jobs/other_job_id")
+        .match_header("Authorization", "Bearer second_token")
+        .with_status(401)
+        .expect(1)
+        .create();
+    let third_refresh = server
+        .mock("POST", "/auth/realms/cortex/protocol/openid-connect/token")
+        .match_body(mockito::Matcher::UrlEncoded(
+            "refresh_token".to_string(),
+            "third_refresh_token".to_string(),
+        ))
+        .with_status(200)
+        .with_body(r#"{"access_token": "third_token", "refresh_token": "fourth_refresh_token"}"#)
+        .expect(1)
+        .create();
+    let status = server
+        .mock("GET", "/garnet/jobs/other_job_id")
+        .match_header("Authorization", "Bearer third_token")
+        .with_status(200)
+        .with_body(r#"{"status": "pending execution"}"#)
+        .expect(1)
+        .create();
+    assert_eq!(
+        backend.get_status("other_job_id".to_string()).unwrap(),
+        "pending execution"
+    );
+    status_rejected.assert();
+    third_refresh.assert();
+    status.assert();
+}
+
+#[test]
+fn refresh_access_token_fails() {
+    let mut server = mockito::Server::new();
+    let mut device = GarnetDevice::new();
+    device.set_endpoint_url(format!("{}/garnet/jobs", server.url()));
+    let mut backend = Backend::new(device.into(), Some("expired_token".to_string())).unwrap();
+    backend.set_allow_http(true);
+    backend.set_refresh_credentials(
+        "invalid_refresh_token".to_string(),
+        format!("{}/auth", server.url()),
+    );
+
+    server
+        .mock("GET", "/garnet/jobs/job_id")
+        .with_status(401)
+        .create();
+    let refresh = server
+        .mock("POST", "/auth/realms/cortex/protocol/openid-connect/token")
+        .with_status(400)
+        .with_body(r#"{"error": "invalid_grant"}"#)
+        .expect(1)
+        .create();
+    assert!(matches!(
+        backend.get_results("job_id".to_string()),
+        Err(RoqoqoBackendError::MissingAuthentication { .. })
+    ));
+    refresh.assert();
+}
+
+#[test]
+fn new_with_profile_file() {
+    let path = std::env::temp_dir().join(format!("qoqo_iqm_credentials_{}", std::process::id()));
+    std::fs::write(
+        &path,
+        "# IQM accounts\n\
+         [default]\n\
+         token = \"default_token\"\n\
+         \n\
+         [staging]\n\
+         token = staging_token\n\
+         endpoint = \"https://staging.example.com/garnet/jobs\"\n",
+    )
+    .unwrap();
+
+    let backend =
+        Backend::new_with_profile_file(GarnetDevice::new().into(), "default", &path).unwrap();
+    assert_eq!(backend.token_source(), TokenSource::CredentialsProfile);
+    let default_backend = Backend::new(
+        GarnetDevice::new().into(),
+        Some("default_token".to_string()),
+    )
+    .unwrap();
+    let mut expected = serde_json::to_value(&default_backend).unwrap();
+    expected["token_source"] = serde_json::to_value(TokenSource::CredentialsProfile).unwrap();
+    assert_eq!(serde_json::to_value(&backend).unwrap(), expected);
+
+    let backend =
+        Backend::new_with_profile_file(GarnetDevice::new().into(), "staging", &path).unwrap();
+    assert_eq!(
+        backend.remote_host(),
+        "https://staging.example.com/garnet/jobs"
+    );
+    let mut staging_device = GarnetDevice::new();
+    staging_device.set_endpoint_url("https://staging.example.com/garnet/jobs".to_string());
+    let staging_backend =
+        Backend::new(staging_device.into(), Some("staging_token".to_string())).unwrap();
+    let mut expected = serde_json::to_value(&staging_backend).unwrap();
+    expected["token_source"] = serde_json::to_value(TokenSource::CredentialsProfile).unwrap();
+    assert_eq!(serde_json::to_value(&backend).unwrap(), expected);
+
+    assert!(matches!(
+        Backend::new_with_profile_file(GarnetDevice::new().into(), "missing", &path),
+        Err(RoqoqoBackendError::MissingAuthentication { .. })
+    ));
+    std::fs::remove_file(&path).unwrap();
+    assert!(matches!(
+        Backend::new_with_profile_file(GarnetDevice::new().into(), "default", &path),
+        Err(RoqoqoBackendError::MissingAuthentication { .. })
+    ));
+}
+
+#[test]
+fn token_source() {
+    let backend =
+        Backend::new(GarnetDevice::new().into(), Some("dummy_token".to_string())).unwrap();
+    assert_eq!(backend.token_source(), TokenSource::Argument);
+
+    if env::var("IQM_TOKEN").is_ok() {
+        let backend = Backend::new(GarnetDevice::new().into(), None).unwrap();
+        assert_eq!(backend.token_source(), TokenSource::EnvironmentVariable);
+    } else if env::var("IQM_TOKENS_FILE").is_ok() {
+        let backend = Backend::new(GarnetDevice::new().into(), None).unwrap();
+        assert_eq!(backend.token_source(), TokenSource::TokensFile);
+    }
+}
+
+#[test]
+fn submit_circuit_without_gates() {
+    let device = GarnetDevice::new();
+    let mut backend = Backend::new(device.into(), Some("dummy_token".to_string())).unwrap();
+
+    // Valid circuit with measurements only
+    let mut circuit = Circuit::new();
+    circuit += DefinitionBit::new("ro".to_string(), 1, true);
+    circuit += PragmaGlobalPhase::new(PI.into());
+    circuit += MeasureQubit::new(0, "ro".to_string(), 0);
+    assert!(backend.validate_circuit(&circuit).is_ok());
+    assert!(matches!(
+        backend.submit_circuit_batch(&[circuit]),
+        Err(IqmBackendError::EmptyCircuit)
+    ));
+
+    // Only pragmas, which are not caught by the validation when it is disabled
+    backend.set_validation_level(ValidationLevel::Off);
+    let mut circuit = Circuit::new();
+    circuit += DefinitionBit::new("ro".to_string(), 1, true);
+    circuit += PragmaGlobalPhase::new(PI.into());
+    circuit += PragmaSetNumberOfMeasurements::new(10, "ro".to_string());
+    assert!(matches!(
+        backend.submit_circuit_batch(&[circuit]),
+        Err(IqmBackendError::EmptyCircuit)
+    ));
+}
+
+#[test]
+fn max_circuit_depth() {
+    let mut server = mockito::Server::new();
+    let mut device = GarnetDevice::new();
+    device.set_endpoint_url(format!("{}/garnet/jobs", server.url()));
+    let mut backend = Backend::new(device.into(), Some("dummy_token".to_string())).unwrap();
+    backend.set_allow_http(true);
+
+    server
+        .mock("GET", "/garnet/quantum-architecture")
+        .with_status(200)
+        .with_body(
+            serde_json::json!({
+                "quantum_architecture": {
+                    "name": "garnet",
+                    "qubits": ["QB1", "QB2"],
+                    "qubit_connectivity": [["QB1", "QB2"]],
+                    "operations": ["prx", "cz", "measure"],
+                    "max_circuit_depth": 3
+                }
+            })
+            .to_string(),
+        )
+        .create();
+    assert_eq!(backend.max_circuit_depth(), Some(3));
+
+    let mut shallow_circuit = Circuit::new();
+    shallow_circuit += DefinitionBit::new("ro".to_string(), 20, true);
+    shallow_circuit += RotateXY::new(1, PI.into(), 0.0.into());
+    shallow_circuit += RotateXY::new(2, PI.into(), 0.0.into());
+    shallow_circuit += ControlledPauliZ::new(1, 2);
+    shallow_circuit += PragmaRepeatedMeasurement::new("ro".to_string(), 10, None);
+    let mut deep_circuit = Circuit::new();
+    deep_circuit += DefinitionBit::new("ro".to_string(), 20, true);
+    for _ in 0..3 {
+        deep_circuit += RotateXY::new(0, PI.into(), 0.0.into());
+    }
+    deep_circuit += PragmaRepeatedMeasurement::new("ro".to_string(), 10, None);
+
+    // The depth is only checked when enabled
+    assert!(backend.validate_circuit(&deep_circuit).is_ok());
+    backend.set_check_circuit_depth(true);
+    assert!(backend.validate_circuit(&shallow_circuit).is_ok());
+    match backend.validate_circuit(&deep_circuit) {
+        Err(IqmBackendError::InvalidCircuit { msg }) => {
+            assert!(msg.contains("depth of 4"));
+            assert!(msg.contains("at most 3"));
+        }
+        res => panic!("Unexpected validation result: {:?}", res),
+    }
+}
+
+#[test]
+fn device_from_quantum_architecture() {
+    let mut server = mockito::Server::new();
+    let mut device = GarnetDevice::new();
+    device.set_endpoint_url(format!("{}/garnet/jobs", server.url()));
+    let mut backend = Backend::new(device.into(), Some("dummy_token".to_string())).unwrap();
+    backend.set_allow_http(true);
+
+    let architecture_mock = server
+        .mock("GET", "/garnet/quantum-architecture")
+        .with_status(200)
+        .with_body(
+            serde_json::json!({
+                "quantum_architecture": {
+                    "name": "garnet",
+                    "qubits": ["QB1", "QB2", "QB3"],
+                    "qubit_connectivity": [["QB1", "QB2"], ["QB2", "QB3"]],
+                    "operations": ["prx", "cz", "measure"]
+                }
+            })
+            .to_string(),
+        )
+        .create();
+
+    let generic_device = backend.device_from_quantum_architecture().unwrap();
+    architecture_mock.assert();
+    assert_eq!(generic_device.number_qubits(), 3);
+    assert_eq!(
+        generic_device.single_qubit_gate_time("RotateXY", &2),
+        Some(1.0)
+    );
+    assert_eq!(
+        generic_device.two_qubit_gate_time("ControlledPauliZ", &2, &1),
+        Some(1.0)
+    );
+    assert_eq!(
+        generic_device.two_qubit_gate_time("ControlledPauliZ", &0, &2),
+        None
+    );
+
+    server
+        .mock("GET", "/garnet/quantum-architecture")
+        .with_status(500)
+        .create();
+    assert!(backend.device_from_quantum_architecture().is_err());
+}
+
+#[test]
+fn test_ping() {
+    let mut server = mockito::Server::new();
+    let mut device = DenebDevice::new();
+    device.set_endpoint_url(format!("{}/deneb/jobs", server.url()));
+    let mut backend = Backend::new(device.into(), Some("dummy_token".to_string())).unwrap();
+    backend.set_allow_http(true);
+
+    let reachable = server
+        .mock("GET", "/deneb/quantum-architecture")
+        .with_status(200)
+        .with_body("{}")
+        .create();
+    assert!(backend.ping().is_ok());
+    reachable.assert();
+    reachable.remove();
+
+    server
+        .mock("GET", "/deneb/quantum-architecture")
+        .with_status(503)
+        .create();
+    assert!(matches!(
+        backend.ping(),
+        Err(RoqoqoBackendError::NetworkError { .. })
+    ));
+
+    let mut device = DenebDevice::new();
+    device.set_endpoint_url("http://localhost:1/deneb/jobs".to_string());
+    let mut backend = Backend::new(device.into(), Some("dummy_token".to_string())).unwrap();
+    backend.set_allow_http(true);
+    assert!(matches!(
+        backend.ping(),
+        Err(RoqoqoBackendError::NetworkError { .. })
+    ));
+}
+
+#[test]
+fn test_wait_for_status_change() {
+    let mut server = mockito::Server::new();
+    let mut device = GarnetDevice::new();
+    device.set_endpoint_url(format!("{}/garnet/jobs", server.url()));
+    let mut backend = Backend::new(device.into(), Some("dummy_token".to_string())).unwrap();
+    backend.set_allow_http(true);
+
+    let pending_response = |status: &str| {
+        serde_json::json!({
+            "status": status,
+            "metadata": {
+                "request": {
+                    "circuits": [],
+                    "shots": 2,
+                    "circuit_duration_check": false,
+                    "heralding_mode": "none"
+                }
+            }
+        })
+        .to_string()
+    };
+    let compilation_mock = server
+        .mock("GET", "/garnet/jobs/job_1")
+        .with_status(200)
+        .with_body(pending_response("pending compilation"))
+        .expect(1)
+        .create();
+    let execution_mock = server
+        .mock("GET", "/garnet/jobs/job_1")
+        .with_status(200)
+        .with_body(pending_response("pending execution"))
+        .expect(1)
+        .create();
+
+    let status = backend
+        .wait_for_status_change("job_1".to_string(), Status::PendingCompilation)
+        .unwrap();
+    assert_eq!(status, Status::PendingExecution);
+    compilation_mock.assert();
+    execution_mock.assert();
+}
+
+#[test]
+fn test_get_status() {
+    let mut server = mockito::Server::new();
+    let mut device = GarnetDevice::new();
+    device.set_endpoint_url(format!("{}/garnet/jobs", server.url()));
+    let mut backend = Backend::new(device.into(), Some("dummy_token".to_string())).unwrap();
+    backend.set_allow_http(true);
+
+    server
+        .mock("GET", "/garnet/jobs/job_1")
+        .with_status(200)
+        .with_body(r#"{"status": "pending compilation"}"#)
+        .create();
+    server
+        .mock("GET", "/garnet/jobs/job_2")
+        .with_status(200)
+        .with_body(mock_ready_response(
+            serde_json::json!({"ro": [[0], 1]}),
+            serde_json::json!({"ro": [[1], [0]]}),
+        ))
+        .create();
+
+    assert_eq!(
+        backend.get_status("job_1".to_string()).unwrap(),
+        "pending compilation"
+    );
+    assert_eq!(backend.get_status("job_2".to_string()).unwrap(), "ready");
+
+    // Error responses are reported with their status code
+    server
+        .mock("GET", "/garnet/jobs/job_3")
+        .with_status(404)
+        .with_body(r#"{"detail": "Job not found"}"#)
+        .create();
+    match backend.get_status("job_3".to_string()) {
+        Err(RoqoqoBackendError::NetworkError { msg }) => {
+            assert!(msg.contains("HTTP status code"), "{}", msg)
+        }
+        res => panic!("Unexpected result: {:?}", res),
+    }
+}
+
+#[test]
+fn unknown_job_status() {
+    assert_eq!(
+        serde_json::from_str::<Status>(r#""pending deletion""#).unwrap(),
+        Status::Unknown("pending deletion".to_string())
+    );
+    assert_eq!(
+        serde_json::from_str::<Status>(r#""ready""#).unwrap(),
+        Status::Ready
+    );
+    assert_eq!(
+        serde_json::to_string(&Status::Unknown("pending deletion".to_string())).unwrap(),
+        r#""pending deletion""#
+    );
+
+    let mut server = mockito::Server::new();
+    let mut device = GarnetDevice::new();
+    device.set_endpoint_url(format!("{}/garnet/jobs", server.url()));
+    let mut backend = Backend::new(device.into(), Some("dummy_token".to_string())).unwrap();
+    backend.set_allow_http(true);
+    backend.set_polling_interval(0.01).unwrap();
+
+    // Jobs with an unknown status are polled until they reach a known terminal status
+    let unknown_mock = server
+        .mock("GET", "/garnet/jobs/job_1")
+        .with_status(200)
+        .with_body(
+            serde_json::json!({
+                "status": "pending deletion",
+                "metadata": {
+                    "request": {
+                        "circuits": [],
+                        "shots": 2,
+                        "heralding_mode": "none"
+                    }
+                }
+            })
+            .to_string(),
+        )
+        .expect(1)
+        .create();
+    assert_eq!(
+        backend.get_status("job_1".to_string()).unwrap(),
+        "pending deletion"
+    );
+    unknown_mock.assert();
+
+    let unknown_mock = server
+        .mock("GET", "/garnet/jobs/job_2")
+        .with_status(200)
+        .with_body(
+            serde_json::json!({
+                "status": "pending deletion",
+                "metadata": {
+                    "request": {
+                        "circuits": [],
+                        "shots": 2,
+                        "heralding_mode": "none"
+                    }
+                }
+            })
+            .to_string(),
+        )
+        .expect(1)
+        .create();
+    let ready_mock = server
+        .mock("GET", "/garnet/jobs/job_2")
+        .with_status(200)
+        .with_body(mock_ready_response(
+            serde_json::json!({"ro": [[0], 1]}),
+            serde_json::json!({"ro": [[1], [0]]}),
+        ))
+        .expect(1)
+        .create();
+    assert!(backend.wait_for_results("job_2".to_string()).is_ok());
+    unknown_mock.assert();
+    ready_mock.assert();
+}
+
+#[test]
+fn test_estimate_wait_time() {
+    let mut server = mockito::Server::new();
+    let mut device = GarnetDevice::new();
+    device.set_endpoint_url(format!("{}/garnet/jobs", server.url()));
+    let mut backend = Backend::new(device.into(), Some("dummy_token".to_string())).unwrap();
+    backend.set_allow_http(true);
+
+    let status_response = |status: &str, queue_metrics: serde_json::Value| {
+        let mut response = serde_json::json!({
+            "status": status,
+            "metadata": {
+                "request": {
+                    "circuits": [],
+                    "shots": 2,
+                    "circuit_duration_check": false,
+                    "heralding_mode": "none"
+                }
+            }
+        });
+        response
+            .as_object_mut()
+            .unwrap()
+            .extend(queue_metrics.as_object().unwrap().clone());
+        response.to_string()
+    };
+    server
+        .mock("GET", "/garnet/jobs/job_1")
+        .with_status(200)
+        .with_body(status_response(
+            "pending execution",
+            serde_json::json!({"queue_position": 3, "average_execution_time": 2.5}),
+        ))
+        .create();
+    server
+        .mock("GET", "/garnet/jobs/job_2")
+        .with_status(200)
+        .with_body(status_response("pending execution", serde_json::json!({})))
+        .create();
+    server
+        .mock("GET", "/garnet/jobs/job_3")
+        .with_status(200)
+        .with_body(status_response("aborted", serde_json::json!({})))
+        .create();
+
+    assert_eq!(
+        backend.estimate_wait_time("job_1".to_string()).unwrap(),
+        Some(Duration::from_secs(10))
+    );
+    assert_eq!(
+        backend.estimate_wait_time("job_2".to_string()).unwrap(),
+        None
+    );
+    assert_eq!(
+        backend.estimate_wait_time("job_3".to_string()).unwrap(),
+        Some(Duration::ZERO)
+    );
+}
+
+#[test]
+fn test_set_timeout() {
+    let mut server = mockito::Server::new();
+    let mut device = GarnetDevice::new();
+    device.set_endpoint_url(format!("{}/garnet/jobs", server.url()));
+    let mut backend = Backend::new(device.into(), Some("dummy_token".to_string())).unwrap();
+    backend.set_allow_http(true);
+    backend.set_timeout(0.5).unwrap();
+    backend.set_polling_interval(0.1).unwrap();
+
+    server
+        .mock("GET", "/garnet/jobs/job_id")
+        .with_status(200)
+        .with_body(
+            serde_json::json!({
+                "status": "pending execution",
+                "metadata": {
+                    "request": {
+                        "circuits": [],
+                        "shots": 2,
+                        "circuit_duration_check": false,
+                        "heralding_mode": "none"
+                    }
+                }
+            })
+            .to_string(),
+        )
+        .create();
+
+    match backend.wait_for_results("job_id".to_string()) {
+        Err(IqmBackendError::RoqoqoBackendError(RoqoqoBackendError::Timeout { msg })) => {
+            assert_eq!(msg, "Job did not finish in 0.5 seconds")
+        }
+        res => panic!("Unexpected result: {:?}", res),
+    }
+
+    // The timeout is serialized with the backend
+    let serialized = serde_json::to_string(&backend).unwrap();
+    let deserialized: Backend = serde_json::from_str(&serialized).unwrap();
+    assert_eq!(deserialized, backend);
+    backend.set_timeout(60.0).unwrap();
+    assert_ne!(deserialized, backend);
+
+    for seconds in [-1.0, f64::NAN, f64::INFINITY] {
+        assert!(matches!(
+            backend.set_timeout(seconds),
+            Err(RoqoqoBackendError::GenericError { .. })
+        ));
+    }
+    assert!(backend.set_timeout(0.0).is_ok());
+}
+
+#[test]
+fn test_set_polling_interval() {
+    let device = GarnetDevice::new();
+    let mut backend = Backend::new(device.into(), Some("dummy_token".to_string())).unwrap();
+
+    assert!(backend.set_polling_interval(0.5).is_ok());
+    for seconds in [0.0, -1.0, f64::NAN, f64::INFINITY] {
+        assert!(matches!(
+            backend.set_polling_interval(seconds),
+            Err(RoqoqoBackendError::GenericError { .. })
+        ));
+    }
+
+    // The polling interval is serialized with the backend
+    let serialized = serde_json::to_string(&backend).unwrap();
+    let deserialized: Backend = serde_json::from_str(&serialized).unwrap();
+    assert_eq!(deserialized, backend);
+    backend.set_polling_interval(4.0).unwrap();
+    assert_ne!(deserialized, backend);
+}
+
+#[test]
+fn test_estimate_cost() {
+    let device = GarnetDevice::new();
+    let mut backend = Backend::new(device.into(), Some("dummy_token".to_string())).unwrap();
+    backend.set_cost_model(CostModel {
+        cost_per_shot: 0.5,
+        cost_per_gate: 0.1,
+    });
+
+    let mut circuit = Circuit::new();
+    circuit += DefinitionBit::new("ro".to_string(), 20, true);
+    circuit += RotateXY::new(0, PI.into(), 0.0.into());
+    circuit += RotateXY::new(1, PI.into(), 0.0.into());
+    circuit += ControlledPauliZ::new(0, 1);
+    circuit += PragmaRepeatedMeasurement::new("ro".to_string(), 100, None);
+
+    // 100 shots * (0.5 + 3 gates * 0.1) per circuit
+    let cost = backend
+        .estimate_cost(&[circuit.clone(), circuit.clone()])
+        .unwrap();
+    assert!((cost - 160.0).abs() < 1e-9);
+
+    backend._overwrite_number_of_measurements(10);
+    let cost = backend.estimate_cost(&[circuit]).unwrap();
+    assert!((cost - 8.0).abs() < 1e-9);
+}
+
+#[test]
+fn test_effective_shots() {
+    let device = GarnetDevice::new();
+    let mut backend = Backend::new(device.into(), Some("dummy_token".to_string())).unwrap();
+
+    let mut circuit = Circuit::new();
+    circuit += DefinitionBit::new("ro".to_string(), 20, true);
+    circuit += RotateXY::new(0, PI.into(), 0.0.into());
+    assert_eq!(backend.effective_shots(&circuit), 1000);
+
+    circuit += PragmaRepeatedMeasurement::new("ro".to_string(), 100, None);
+    assert_eq!(backend.effective_shots(&circuit), 100);
+
+    backend._overwrite_number_of_measurements(10);
+    assert_eq!(backend.effective_shots(&circuit), 10);
+}
+
+#[test]
+fn test_native_two_qubit_gate() {
+    assert_eq!(
+        IqmDevice::from(GarnetDevice::new()).native_two_qubit_gate(),
+        "ControlledPauliZ"
+    );
+    assert_eq!(
+        IqmDevice::from(DenebDevice::new()).native_two_qubit_gate(),
+        "CZQubitResonator"
+    );
+    assert_eq!(
+        IqmDevice::from(ResonatorFreeDevice::new()).native_two_qubit_gate(),
+        "ControlledPauliZ"
+    );
+}
+
+#[test]
+fn test_round_shots() {
+    let garnet = IqmDevice::from(GarnetDevice::new());
+    assert_eq!(garnet.round_shots(1), 1);
+    assert_eq!(garnet.round_shots(1234), 1234);
+    assert_eq!(garnet.round_shots(20000), 10000);
+
+    let deneb = IqmDevice::from(DenebDevice::new());
+    assert_eq!(deneb.round_shots(1), 100);
+    assert_eq!(deneb.round_shots(149), 100);
+    assert_eq!(deneb.round_shots(150), 200);
+    assert_eq!(deneb.round_shots(1000), 1000);
+    assert_eq!(deneb.round_shots(20000), 10000);
+
+    let resonator_free = IqmDevice::from(ResonatorFreeDevice::new());
+    assert_eq!(resonator_free.round_shots(0), 1);
+    assert_eq!(resonator_free.round_shots(77), 77);
+}
+
+#[test]
+fn round_shots_in_request() {
+    let mut server = mockito::Server::new();
+    let mut device = DenebDevice::new();
+    device.set_endpoint_url(format!("{}/deneb/jobs", server.url()));
+    let mut backend = Backend::new(device.into(), Some("dummy_token".to_string())).unwrap();
+    backend.set_allow_http(true);
+
+    let mut circuit = Circuit::new();
+    circuit += RotateXY::new(1, PI.into(), 0.0.into());
+    circuit += DefinitionBit::new("ro".to_string(), 6, true);
+    circuit += PragmaRepeatedMeasurement::new("ro".to_string(), 130, None);
+
+    let unrounded = server
+        .mock("POST", "/deneb/jobs")
+        .match_body(mockito::Matcher::PartialJson(
+            serde_json::json!({"shots": 130}),
+        ))
+        .with_status(201)
+        .with_body(r#"{"id": "job_id"}"#)
+        .expect(1)
+        .create();
+    backend.submit_circuit_batch(&[circuit.clone()]).unwrap();
+    unrounded.assert();
+
+    assert_eq!(backend.get_submission_warnings(), None);
+
+    backend.set_round_shots(true);
+    backend.set_print_warnings(false);
+    let rounded = server
+        .mock("POST", "/deneb/jobs")
+        .match_body(mockito::Matcher::PartialJson(
+            serde_json::json!({"shots": 100}),
+        ))
+        .with_status(201)
+        .with_body(r#"{"id": "job_id"}"#)
+        .expect(1)
+        .create();
+    backend.submit_circuit_batch(&[circuit]).unwrap();
+    rounded.assert();
+    let warnings = backend.get_submission_warnings().unwrap();
+    assert_eq!(warnings.len(), 1);
+    assert!(warnings[0].contains("from 130 to 100"));
+}
+
+#[test]
+fn min_shots_warning_in_submission_warnings() {
+    let mut server = mockito::Server::new();
+    let mut device = GarnetDevice::new();
+    device.set_endpoint_url(format!("{}/garnet/jobs", server.url()));
+    let mut backend = Backend::new(device.into(), Some("dummy_token".to_string())).unwrap();
+    backend.set_allow_http(true);
+    backend.set_print_warnings(false);
+    backend.set_min_shots_warning(100);
+
+    let mut circuit = Circuit::new();
+    circuit += DefinitionBit::new("ro".to_string(), 1, true);
+    circuit += RotateXY::new(0, PI.into(), 0.0.into());
+    circuit += MeasureQubit::new(0, "ro".to_string(), 0);
+    circuit += PragmaSetNumberOfMeasurements::new(10, "ro".to_string());
+
+    server
+        .mock("POST", "/garnet/jobs")
+        .with_status(201)
+        .with_body(r#"{"id": "job_id"}"#)
+        .expect(2)
+        .create();
+    assert_eq!(backend.get_submission_warnings(), None);
+    backend.submit_circuit_batch(&[circuit.clone()]).unwrap();
+    let warnings = backend.get_submission_warnings().unwrap();
+    assert_eq!(warnings.len(), 1);
+    assert!(warnings[0].contains("10 shots"));
+
+    // The warnings are reset by the next submission
+    backend.set_min_shots_warning(0);
+    backend.submit_circuit_batch(&[circuit]).unwrap();
+    assert_eq!(backend.get_submission_warnings(), None);
+}
+
+#[test]
+fn test_default_shots() {
+    assert_eq!(IqmDevice::from(GarnetDevice::new()).default_shots(), 1000);
+    assert_eq!(IqmDevice::from(DenebDevice::new()).default_shots(), 1000);
+    assert_eq!(
+        IqmDevice::from(ResonatorFreeDevice::new()).default_shots(),
+        100
+    );
+
+    // The default shots are used for circuits without a number of measurements
+    let device = ResonatorFreeDevice::new();
+    let mut backend = Backend::new(device.into(), Some("dummy_token".to_string())).unwrap();
+    let mut circuit = Circuit::new();
+    circuit += DefinitionBit::new("ro".to_string(), 1, true);
+    circuit += RotateXY::new(0, PI.into(), 0.0.into());
+    circuit += MeasureQubit::new(0, "ro".to_string(), 0);
+    backend.set_cost_model(CostModel {
+        cost_per_shot: 1.0,
+        cost_per_gate: 0.0,
+    });
+    let cost = backend.estimate_cost(&[circuit]).unwrap();
+    assert!((cost - 100.0).abs() < 1e-9);
+}
+
+#[test]
+fn test_list_jobs() {
+    let mut server = mockito::Server::new();
+    let mut device = GarnetDevice::new();
+    device.set_endpoint_url(format!("{}/garnet/jobs", server.url()));
+    let mut backend = Backend::new(device.into(), Some("dummy_token".to_string())).unwrap();
+    backend.set_allow_http(true);
+
+    let list_mock = server
+        .mock("GET", "/garnet/jobs")
+        .match_query(mockito::Matcher::UrlEncoded(
+            "limit".to_string(),
+            "2".to_string(),
+        ))
+        .with_status(200)
+        .with_body(
+            r#"[
+                {"id": "job_1", "status": "ready", "submitted_at": "2024-05-02T10:00:00Z"},
+                {"id": "job_2", "status": "pending execution"}
+            ]"#,
+        )
+        .create();
+
+    let jobs = backend.list_jobs(2).unwrap();
+
+    list_mock.assert();
+    assert_eq!(
+        jobs,
+        vec![
+            JobSummary {
+                id: "job_1".to_string(),
+                status: "ready".to_string(),
+                submitted_at: Some("2024-05-02T10:00:00Z".to_string()),
+                name: None,
+            },
+            JobSummary {
+                id: "job_2".to_string(),
+                status: "pending execution".to_string(),
+                submitted_at: None,
+                name: None,
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_abort_jobs_by_prefix() {
+    let mut server = mockito::Server::new();
+    let mut device = GarnetDevice::new();
+    device.set_endpoint_url(format!("{}/garnet/jobs", server.url()));
+    let mut backend = Backend::new(device.into(), Some("dummy_token".to_string())).unwrap();
+    backend.set_allow_http(true);
+
+    let list_mock = server
+        .mock("GET", "/garnet/jobs")
+        .match_query(mockito::Matcher::Any)
+        .with_status(200)
+        .with_body(
+            r#"[
+                {"id": "job_1", "status": "pending execution", "name": "sweep_0"},
+                {"id": "job_2", "status": "pending compilation", "name": "sweep_1"},
+                {"id": "job_3", "status": "pending execution", "name": "other_0"},
+                {"id": "job_4", "status": "ready", "name": "sweep_2"},
+                {"id": "job_5", "status": "pending execution"},
+                {"id": "job_6", "status": "pending execution", "name": "sweep_3"}
+            ]"#,
+        )
+        .create();
+    let abort_mock = server
+        .mock("POST", "/garnet/jobs/jobs/job_1/abort")
+        .with_status(200)
+        .create();
+    let failed_abort_mock = server
+        .mock("POST", "/garnet/jobs/jobs/job_2/abort")
+        .with_status(400)
+        .with_body(r#"{"detail": "Job already running"}"#)
+        .create();
+    let gateway_error_mock = server
+        .mock("POST", "/garnet/jobs/jobs/job_6/abort")
+        .with_status(502)
+        .with_body("Bad Gateway")
+        .create();
+
+    let results = backend.abort_jobs_by_prefix("sweep_").unwrap();
+
+    list_mock.assert();
+    abort_mock.assert();
+    failed_abort_mock.assert();
+    gateway_error_mock.assert();
+    assert_eq!(results.len(), 3);
+    assert_eq!(results[0].0, "job_1");
+    assert!(results[0].1.is_ok());
+    assert_eq!(results[1].0, "job_2");
+    assert!(matches!(
+        results[1].1,
+        Err(IqmBackendError::JobAbortionFailed { .. })
+    ));
+    // A body that is not JSON is reported as is
+    assert_eq!(results[2].0, "job_6");
+    match &results[2].1 {
+        Err(IqmBackendError::JobAbortionFailed { id, msg }) => {
+            assert_eq!(id, "job_6");
+            assert_eq!(msg, "Bad Gateway");
+        }
+        res => panic!("Unexpected result: {:?}", res),
+    }
+}
+
+#[test]
+fn test_endpoint_paths() {
+    let mut server = mockito::Server::new();
+    let mut device = GarnetDevice::new();
+    device.set_endpoint_url(format!("{}/api/v1/jobs", server.url()));
+    let mut backend = Backend::new(device.into(), Some("dummy_token".to_string())).unwrap();
+    backend.set_allow_http(true);
+    assert_eq!(backend.endpoint_paths(), EndpointPaths::default());
+    let endpoint_paths = EndpointPaths {
+        job_status: "{base_url}/job-status/{id}".to_string(),
+        abort: "{jobs_url}/{id}/cancel".to_string(),
+        quantum_architecture: "{base_url}/architecture".to_string(),
+        calibration_metrics: "{base_url}/calibration/latest-metrics".to_string(),
+    };
+    backend.set_endpoint_paths(endpoint_paths.clone());
+    assert_eq!(backend.endpoint_paths(), endpoint_paths);
+
+    let status_mock = server
+        .mock("GET", "/api/v1/job-status/job_1")
+        .with_status(200)
+        .with_body(
+            serde_json::json!({
+                "status": "pending execution",
+                "metadata": {
+                    "request": {
+                        "circuits": [],
+                        "shots": 2,
+                        "circuit_duration_check": false,
+                        "heralding_mode": "none"
+                    }
+                }
+            })
+            .to_string(),
+        )
+        .create();
+    let abort_mock = server
+        .mock("POST", "/api/v1/jobs/job_1/cancel")
+        .with_status(200)
+        .create();
+    let architecture_mock = server
+        .mock("GET", "/api/v1/architecture")
+        .with_status(200)
+        .with_body("{}")
+        .create();
+    let calibration_mock = server
+        .mock("GET", "/api/v1/calibration/latest-metrics")
+        .with_status(200)
+        .with_body(r#"{"metrics": {}}"#)
+        .create();
+
+    assert!(backend.get_results("job_1".to_string()).is_ok());
+    backend.abort_job("job_1".to_string()).unwrap();
+    assert_eq!(backend.get_quantum_architecture().unwrap(), "{}");
+    assert!(backend.get_gate_fidelities().unwrap().is_empty());
+    status_mock.assert();
+    abort_mock.assert();
+    architecture_mock.assert();
+    calibration_mock.assert();
+
+    // The default templates only replace the trailing jobs segment of the URL
+    let mut device = GarnetDevice::new();
+    device.set_endpoint_url(format!("{}/myjobs/garnet/jobs", server.url()));
+    let mut backend = Backend::new(device.into(), Some("dummy_token".to_string())).unwrap();
+    backend.set_allow_http(true);
+    let architecture_mock = server
+        .mock("GET", "/myjobs/garnet/quantum-architecture")
+        .with_status(200)
+        .with_body("{}")
+        .create();
+    let calibration_mock = server
+        .mock("GET", "/myjobs/garnet/calibration/metrics/latest")
+        .with_status(200)
+        .with_body(r#"{"metrics": {}}"#)
+        .create();
+    assert_eq!(backend.get_quantum_architecture().unwrap(), "{}");
+    assert!(backend.get_gate_fidelities().unwrap().is_empty());
+    architecture_mock.assert();
+    calibration_mock.assert();
+
+    // The templates are serialized with the backend
+    let serialized = serde_json::to_string(&backend).unwrap();
+    let deserialized: Backend = serde_json::from_str(&serialized).unwrap();
+    assert_eq!(deserialized, backend);
+    backend.set_endpoint_paths(endpoint_paths);
+    assert_ne!(deserialized, backend);
+}
+
+#[test]
+fn retried_submission_keeps_idempotency_key() {
+    let mut server = mockito::Server::new();
+    let mut device = GarnetDevice::new();
+    device.set_endpoint_url(format!("{}/garnet/jobs", server.url()));
+    let mut backend = Backend::new(device.into(), Some("dummy_token".to_string())).unwrap();
+    backend.set_allow_http(true);
+    backend.set_retry_policy(RetryPolicy {
+        max_retries: 2,
+        initial_backoff: Duration::from_millis(10),
+        ..RetryPolicy::default()
+    });
+
+    let keys = Arc::new(Mutex::new(Vec::<String>::new()));
+    let failed_keys = keys.clone();
+    let failed_post = server
+        .mock("POST", "/garnet/jobs")
+        .match_header("Idempotency-Key", mockito::Matcher::Any)
+        .with_status(503)
+        .with_body_from_request(move |request| {
+            failed_keys.lock().unwrap().push(
+                String::from_utf8(request.header("Idempotency-Key")[0].as_bytes().to_vec())
+                    .unwrap(),
+            );
+            vec![]
+        })
+        .expect(1)
+        .create();
+    let accepted_keys = keys.clone();
+    let accepted_post = server
+        .mock("POST", "/garnet/jobs")
+        .match_header("Idempotency-Key", mockito::Matcher::Any)
+        .with_status(201)
+        .with_body_from_request(move |request| {
+            accepted_keys.lock().unwrap().push(
+                String::from_utf8(request.header("Idempotency-Key")[0].as_bytes().to_vec())
+                    .unwrap(),
+            );
+            br#"{"id": "job_id"}"#.to_vec()
+        })
+        .expect(1)
+        .create();
+    server
+        .mock("GET", "/garnet/jobs/job_id")
+        .with_status(200)
+        .with_body(mock_ready_response(
+            serde_json::json!({"ro": [[0], 1]}),
+            serde_json::json!({"ro": [[1], [0]]}),
+        ))
+        .create();
+
+    let mut circuit = Circuit::new();
+    circuit += RotateXY::new(0, PI.into(), 0.0.into());
+    circuit += DefinitionBit::new("ro".to_string(), 1, true);
+    circuit += MeasureQubit::new(0, "ro".to_string(), 0);
+
+    let (bit_registers, _, _) = backend.run_circuit_batch(&[circuit]).unwrap();
+    assert_eq!(bit_registers["ro"], vec![vec![true], vec![false]]);
+
+    failed_post.assert();
+    accepted_post.assert();
+    let keys = keys.lock().unwrap();
+    assert_eq!(keys.len(), 2);
+    assert!(!keys[0].is_empty());
+    assert_eq!(keys[0], keys[1]);
+}
+
+#[test]
+fn circuit_name_prefix_is_sanitized() {
+    let mut server = mockito::Server::new();
+    let mut device = GarnetDevice::new();
+    device.set_endpoint_url(format!("{}/garnet/jobs", server.url()));
+    let mut backend = Backend::new(device.into(), Some("dummy_token".to_string())).unwrap();
+    backend.set_allow_http(true);
+    backend.set_circuit_name_prefix(Some("my job/#1 ".to_string()));
+
+    let post = server
+        .mock("POST", "/garnet/jobs")
+        .match_body(mockito::Matcher::PartialJson(serde_json::json!({
+            "circuits": [{"name": "my_job__1_qc_0"}, {"name": "my_job__1_qc_1"}]
+        })))
+        .with_status(201)
+        .with_body(r#"{"id": "job_id"}"#)
+        .expect(1)
+        .create();
+
+    let circuit = |readout: &str| {
+        let mut circuit = Circuit::new();
+        circuit += RotateXY::new(0, PI.into(), 0.0.into());
+        circuit += DefinitionBit::new(readout.to_string(), 1, true);
+        circuit += MeasureQubit::new(0, readout.to_string(), 0);
+        circuit
+    };
+
+    let job_id = backend
+        .submit_circuit_batch(&[circuit("ro1"), circuit("ro2")])
+        .unwrap();
+    assert_eq!(job_id, "job_id");
+    post.assert();
+
+    // A prefix filling the maximum length truncates all circuit names to the same name
+    backend.set_circuit_name_prefix(Some("a".repeat(MAX_CIRCUIT_NAME_LENGTH)));
+    assert!(matches!(
+        backend.submit_circuit_batch(&[circuit("ro1"), circuit("ro2")]),
+        Err(IqmBackendError::InvalidCircuit { .. })
+    ));
+}
+
+#[test]
+fn random_seed_in_request() {
+    let mut server = mockito::Server::new();
+    let mut device = GarnetDevice::new();
+    device.set_endpoint_url(format!("{}/garnet/jobs", server.url()));
+    let mut backend = Backend::new(device.into(), Some("dummy_token".to_string())).unwrap();
+    backend.set_allow_http(true);
+
+    let mut circuit = Circuit::new();
+    circuit += RotateXY::new(0, PI.into(), 0.0.into());
+    circuit += DefinitionBit::new("ro".to_string(), 1, true);
+    circuit += MeasureQubit::new(0, "ro".to_string(), 0);
+
+    let unseeded = server
+        .mock("POST", "/garnet/jobs")
+        .match_body(mockito::Matcher::PartialJson(
+            serde_json::json!({"custom_settings": null}),
+        ))
+        .with_status(201)
+        .with_body(r#"{"id": "job_id"}"#)
+        .expect(1)
+        .create();
+    backend.submit_circuit_batch(&[circuit.clone()]).unwrap();
+    unseeded.assert();
+
+    backend.set_random_seed(Some(42));
+    let seeded = server
+        .mock("POST", "/garnet/jobs")
+        .match_body(mockito::Matcher::PartialJson(
+            serde_json::json!({"custom_settings": {"seed": "42"}}),
+        ))
+        .with_status(201)
+        .with_body(r#"{"id": "job_id"}"#)
+        .expect(1)
+        .create();
+    backend.submit_circuit_batch(&[circuit]).unwrap();
+    seeded.assert();
+}
+
+#[test]
+fn job_priority_in_request() {
+    let mut server = mockito::Server::new();
+    let mut device = GarnetDevice::new();
+    device.set_endpoint_url(format!("{}/garnet/jobs", server.url()));
+    let mut backend = Backend::new(device.into(), Some("dummy_token".to_string())).unwrap();
+    backend.set_allow_http(true);
+
+    let bodies: Arc<Mutex<Vec<serde_json::Value>>> = Arc::new(Mutex::new(vec![]));
+    let request_bodies = bodies.clone();
+    let post = server
+        .mock("POST", "/garnet/jobs")
+        .with_status(201)
+        .with_body_from_request(move |request| {
+            request_bodies
+                .lock()
+                .unwrap()
+                .push(serde_json::from_slice(request.body().unwrap()).unwrap());
+            br#"{"id": "job_id"}"#.to_vec()
+        })
+        .expect(2)
+        .create();
+
+    let mut circuit = Circuit::new();
+    circuit += RotateXY::new(0, PI.into(), 0.0.into());
+    circuit += DefinitionBit::new("ro".to_string(), 1, true);
+    circuit += MeasureQubit::new(0, "ro".to_string(), 0);
+
+    backend.submit_circuit_batch(&[circuit.clone()]).unwrap();
+    backend.set_job_priority(Some("high".to_string()));
+    backend.submit_circuit_batch(&[circuit]).unwrap();
+
+    post.assert();
+    let bodies = bodies.lock().unwrap();
+    assert!(bodies[0].get("priority").is_none());
+    assert_eq!(bodies[1]["priority"], "high");
+}
+
+#[test]
+fn heralding_mode_in_request() {
+    let mut server = mockito::Server::new();
+    let mut device = GarnetDevice::new();
+    device.set_endpoint_url(format!("{}/garnet/jobs", server.url()));
+    let mut backend = Backend::new(device.into(), Some("dummy_token".to_string())).unwrap();
+    backend.set_allow_http(true);
+
+    let mut circuit = Circuit::new();
+    circuit += RotateXY::new(0, PI.into(), 0.0.into());
+    circuit += DefinitionBit::new("ro".to_string(), 1, true);
+    circuit += MeasureQubit::new(0, "ro".to_string(), 0);
+
+    let default_mode = server
+        .mock("POST", "/garnet/jobs")
+        .match_body(mockito::Matcher::PartialJson(
+            serde_json::json!({"heralding_mode": "none"}),
+        ))
+        .with_status(201)
+        .with_body(r#"{"id": "job_id"}"#)
+        .expect(1)
+        .create();
+    backend.submit_circuit_batch(&[circuit.clone()]).unwrap();
+    default_mode.assert();
+
+    backend.set_heralding_mode(HeraldingMode::Zeros);
+    let zeros_mode = server
+        .mock("POST", "/garnet/jobs")
+        .match_body(mockito::Matcher::PartialJson(
+            serde_json::json!({"heralding_mode": "zeros"}),
+        ))
+        .with_status(201)
+        .with_body(r#"{"id": "job_id"}"#)
+        .expect(1)
+        .create();
+    backend.submit_circuit_batch(&[circuit]).unwrap();
+    zeros_mode.assert();
+}
+
+#[test]
+fn circuit_duration_check_in_request() {
+    let mut server = mockito::Server::new();
+    let mut device = GarnetDevice::new();
+    device.set_endpoint_url(format!("{}/garnet/jobs", server.url()));
+    let mut backend = Backend::new(device.into(), Some("dummy_token".to_string())).unwrap();
+    backend.set_allow_http(true);
+
+    let mut circuit = Circuit::new();
+    circuit += RotateXY::new(0, PI.into(), 0.0.into());
+    circuit += DefinitionBit::new("ro".to_string(), 1, true);
+    circuit += MeasureQubit::new(0, "ro".to_string(), 0);
+
+    let unchecked = server
+        .mock("POST", "/garnet/jobs")
+        .match_body(mockito::Matcher::PartialJson(
+            serde_json::json!({"circuit_duration_check": false}),
+        ))
+        .with_status(201)
+        .with_body(r#"{"id": "job_id"}"#)
+        .expect(1)
+        .create();
+    backend.submit_circuit_batch(&[circuit.clone()]).unwrap();
+    unchecked.assert();
+
+    backend.set_circuit_duration_check(true);
+    let rejected = server
+        .mock("POST", "/garnet/jobs")
+        .match_body(mockito::Matcher::PartialJson(
+            serde_json::json!({"circuit_duration_check": true}),
+        ))
+        .with_status(400)
+        .with_body(r#"{"detail": "Circuit qc_0 exceeds the maximum circuit duration."}"#)
+        .expect(1)
+        .create();
+    match backend.submit_circuit_batch(&[circuit]) {
+        Err(IqmBackendError::JobFailed { msg, .. }) => {
+            assert_eq!(msg, "Circuit qc_0 exceeds the maximum circuit duration.")
+        }
+        res => panic!("Unexpected result: {:?}", res),
+    }
+    rejected.assert();
+}
+
+#[test]
+fn qubit_mapping_in_request() {
+    let mut server = mockito::Server::new();
+    let mut device = GarnetDevice::new();
+    device.set_endpoint_url(format!("{}/garnet/jobs", server.url()));
+    let mut backend = Backend::new(device.into(), Some("dummy_token".to_string())).unwrap();
+    backend.set_allow_http(true);
+    backend.set_qubit_mapping(HashMap::from([
+        (0, "QB3".to_string()),
+        (1, "QB4".to_string()),
+    ]));
+
+    let mut circuit = Circuit::new();
+    circuit += RotateXY::new(0, PI.into(), 0.0.into());
+    circuit += DefinitionBit::new("ro".to_string(), 2, true);
+    circuit += MeasureQubit::new(0, "ro".to_string(), 0);
+    circuit += MeasureQubit::new(1, "ro".to_string(), 1);
+
+    let mapped = server
+        .mock("POST", "/garnet/jobs")
+        .match_body(mockito::Matcher::PartialJson(serde_json::json!({
+            "qubit_mapping": [
+                {"logical_name": "QB1", "physical_name": "QB3"},
+                {"logical_name": "QB2", "physical_name": "QB4"}
+            ]
+        })))
+        .with_status(201)
+        .with_body(r#"{"id": "job_id"}"#)
+        .expect(1)
+        .create();
+    backend.submit_circuit_batch(&[circuit.clone()]).unwrap();
+    mapped.assert();
+
+    // Qubits missing from the mapping are rejected before submission
+    circuit += RotateXY::new(2, PI.into(), 0.0.into());
+    match backend.submit_circuit_batch(&[circuit]) {
+        Err(IqmBackendError::InvalidCircuit { msg }) => {
+            assert!(msg.contains("Qubit QB3"), "{}", msg)
+        }
+        res => panic!("Unexpected result: {:?}", res),
+    }
+}
+
+#[test]
+fn rerun_failed_circuits_resubmits_only_failed() {
+    let mut server = mockito::Server::new();
+    let mut device = GarnetDevice::new();
+    device.set_endpoint_url(format!("{}/garnet/jobs", server.url()));
+    let mut backend = Backend::new(device.into(), Some("dummy_token".to_string())).unwrap();
+    backend.set_allow_http(true);
+
+    let circuit = |name: &str| {
+        serde_json::json!({
+            "name": name,
+            "instructions": [{"name": "measure", "qubits": ["QB1"], "args": {"key": "ro"}}],
+            "metadata": {"ro": [[0], 1]}
+        })
+    };
+    let response = serde_json::json!({
+        "status": "ready",
+        "measurements": [{"ro": [[1], [0]]}, {}, {"ro": [[0], [0]]}],
+        "circuit_statuses": ["ready", "failed", "ready"],
+        "metadata": {
+            "request": {
+                "circuits": [circuit("qc_0"), circuit("qc_1"), circuit("qc_2")],
+                "shots": 2,
+                "heralding_mode": "none"
+            }
+        }
+    });
+    server
+        .mock("GET", "/garnet/jobs/job_id")
+        .with_status(200)
+        .with_body(response.to_string())
+        .create();
+    let resubmit = server
+        .mock("POST", "/garnet/jobs")
+        .match_body(mockito::Matcher::PartialJson(serde_json::json!({
+            "circuits": [{"name": "qc_1"}],
+            "shots": 2
+        })))
+        .with_status(201)
+        .with_body(r#"{"id": "new_job_id"}"#)
+        .expect(1)
+        .create();
+
+    let iqm_result = backend.get_results("job_id".to_string()).unwrap();
+    assert_eq!(iqm_result.failed_circuits(), vec![1]);
+
+    let new_id = backend.rerun_failed_circuits("job_id".to_string()).unwrap();
+    assert_eq!(new_id, "new_job_id");
+    resubmit.assert();
+}
+
+#[test]
+fn rerun_failed_circuits_without_failures() {
+    let mut server = mockito::Server::new();
+    let mut device = GarnetDevice::new();
+    device.set_endpoint_url(format!("{}/garnet/jobs", server.url()));
+    let mut backend = Backend::new(device.into(), Some("dummy_token".to_string())).unwrap();
+    backend.set_allow_http(true);
+
+    server
+        .mock("GET", "/garnet/jobs/job_id")
+        .with_status(200)
+        .with_body(mock_ready_response(
+            serde_json::json!({"ro": [[0], 1]}),
+            serde_json::json!({"ro": [[1], [0]]}),
+        ))
+        .create();
+    let resubmit = server.mock("POST", "/garnet/jobs").expect(0).create();
+
+    assert!(matches!(
+        backend.rerun_failed_circuits("job_id".to_string()),
+        Err(IqmBackendError::InvalidResults { .. })
+    ));
+    resubmit.assert();
+}
+
+#[test]
+fn success_status_codes_are_configurable() {
+    let mut server = mockito::Server::new();
+    let mut device = GarnetDevice::new();
+    device.set_endpoint_url(format!("{}/garnet/jobs", server.url()));
+    let mut backend = Backend::new(device.into(), Some("dummy_token".to_string())).unwrap();
+    backend.set_allow_http(true);
+
+    server
+        .mock("POST", "/garnet/jobs")
+        .with_status(203)
+        .with_body(r#"{"id": "job_id"}"#)
+        .create();
+
+    let mut circuit = Circuit::new();
+    circuit += RotateXY::new(0, PI.into(), 0.0.into());
+    circuit += DefinitionBit::new("ro".to_string(), 1, true);
+    circuit += MeasureQubit::new(0, "ro".to_string(), 0);
+
+    // 203 is not a success status code by default
+    assert!(matches!(
+        backend.submit_circuit_batch(&[circuit.clone()]),
+        Err(IqmBackendError::RoqoqoBackendError(
+            RoqoqoBackendError::NetworkError { .. }
+        ))
+    ));
+
+    backend.set_success_status_codes(vec![200, 201, 202, 203]);
+    assert_eq!(backend.submit_circuit_batch(&[circuit]).unwrap(), "job_id");
+}
+
+#[test]
+fn test_analyze_circuit() {
+    let device = GarnetDevice::new();
+    let backend = Backend::new(device.into(), Some("dummy_token".to_string())).unwrap();
+
+    let mut circuit = Circuit::new();
+    circuit += DefinitionBit::new("ro".to_string(), 2, true);
+    circuit += RotateXY::new(1, PI.into(), 0.0.into());
+    circuit += RotateXY::new(2, PI.into(), 0.0.into());
+    circuit += RotateXY::new(5, PI.into(), 0.0.into());
+    circuit += ControlledPauliZ::new(1, 2);
+    // Qubits 1 and 5 are not coupled
+    circuit += ControlledPauliZ::new(1, 5);
+    circuit += MeasureQubit::new(1, "ro".to_string(), 0);
+    circuit += MeasureQubit::new(2, "ro".to_string(), 1);
+
+    let analysis = backend.analyze_circuit(&circuit);
+    assert_eq!(analysis.errors.len(), 1);
+    assert!(analysis.errors[0].contains("ControlledPauliZ"));
+    assert_eq!(
+        analysis.warnings,
+        vec!["Qubit 5 is acted on by gates but never measured.".to_string()]
+    );
+    assert_eq!(analysis.qubit_count, 3);
+    assert_eq!(analysis.gate_count, 5);
+    assert_eq!(analysis.estimated_duration, 2.0);
+}
+
+#[test]
+fn test_measured_qubit_counts() {
+    let device = GarnetDevice::new();
+    let backend = Backend::new(device.into(), Some("dummy_token".to_string())).unwrap();
+
+    let mut circuit = Circuit::new();
+    circuit += DefinitionBit::new("ro".to_string(), 10, true);
+    circuit += DefinitionBit::new("unused".to_string(), 2, true);
+    circuit += RotateXY::new(1, PI.into(), 0.0.into());
+    circuit += MeasureQubit::new(1, "ro".to_string(), 7);
+    circuit += MeasureQubit::new(2, "ro".to_string(), 3);
+
+    assert_eq!(
+        backend.measured_qubit_counts(&circuit).unwrap(),
+        HashMap::from([("ro".to_string(), 2), ("unused".to_string(), 0)])
+    );
+}
+
+#[test]
+fn test_explain_compilation() {
+    let device = GarnetDevice::new();
+    let backend = Backend::new(device.into(), Some("dummy_token".to_string())).unwrap();
+
+    let mut circuit = Circuit::new();
+    circuit += DefinitionBit::new("ro".to_string(), 2, true);
+    circuit += RotateXY::new(1, PI.into(), 0.0.into());
+    circuit += RotateXY::new(2, PI.into(), 0.0.into());
+    circuit += ControlledPauliZ::new(1, 2);
+    circuit += RotateXY::new(1, PI.into(), 0.0.into());
+    circuit += MeasureQubit::new(1, "ro".to_string(), 0);
+    circuit += MeasureQubit::new(2, "ro".to_string(), 1);
+
+    let explanation = backend.explain_compilation(&circuit).unwrap();
+    let lines: Vec<Vec<&str>> = explanation
+        .lines()
+        .map(|line| line.split_whitespace().collect())
+        .collect();
+    assert_eq!(
+        lines,
+        vec![
+            vec!["#", "name", "qubits", "duration", "end"],
+            vec!["0", "prx", "QB2", "1.00", "1.00"],
+            vec!["1", "prx", "QB3", "1.00", "1.00"],
+            vec!["2", "cz", "QB2,QB3", "1.00", "2.00"],
+            vec!["3", "prx", "QB2", "1.00", "3.00"],
+            vec!["4", "measure", "QB2,QB3", "-", "3.00"],
+        ]
+    );
+}
+
+#[test]
+fn validate_measurement_names_failing_circuit() {
+    let device = GarnetDevice::new();
+    let backend = Backend::new(device.into(), Some("dummy_token".to_string())).unwrap();
+
+    let mut constant_circuit = Circuit::new();
+    constant_circuit += DefinitionBit::new("ro".to_string(), 2, true);
+    let mut valid_circuit = Circuit::new();
+    valid_circuit += RotateXY::new(0, PI.into(), 0.0.into());
+    valid_circuit += MeasureQubit::new(0, "ro".to_string(), 0);
+    let mut invalid_circuit = Circuit::new();
+    invalid_circuit += RotateXY::new(25, PI.into(), 0.0.into());
+    invalid_circuit += MeasureQubit::new(0, "ro".to_string(), 0);
+
+    let measurement = ClassicalRegister {
+        constant_circuit: Some(constant_circuit.clone()),
+        circuits: vec![valid_circuit.clone(), valid_circuit.clone()],
+    };
+    backend.validate_measurement(&measurement).unwrap();
+
+    let measurement = ClassicalRegister {
+        constant_circuit: Some(constant_circuit),
+        circuits: vec![valid_circuit, invalid_circuit],
+    };
+    match backend.validate_measurement(&measurement) {
+        Err(IqmBackendError::InvalidCircuit { msg }) => {
+            assert!(msg.starts_with("Circuit 1 of the measurement is invalid"))
+        }
+        _ => panic!("Expected an InvalidCircuit error"),
+    }
+}
+
+#[test]
+fn retries_stop_at_time_budget() {
+    let mut server = mockito::Server::new();
+    let mut device = GarnetDevice::new();
+    device.set_endpoint_url(format!("{}/garnet/jobs", server.url()));
+    let mut backend = Backend::new(device.into(), Some("dummy_token".to_string())).unwrap();
+    backend.set_allow_http(true);
+    // The first backoff would end after the timeout of the backend
+    backend.set_retry_policy(RetryPolicy {
+        max_retries: 5,
+        initial_backoff: Duration::from_secs(3600),
+        max_backoff: Duration::from_secs(3600),
+    });
+
+    let post = server
+        .mock("POST", "/garnet/jobs")
+        .with_status(503)
+        .expect(1)
+        .create();
+
+    let mut circuit = Circuit::new();
+    circuit += RotateXY::new(0, PI.into(), 0.0.into());
+    circuit += DefinitionBit::new("ro".to_string(), 1, true);
+    circuit += MeasureQubit::new(0, "ro".to_string(), 0);
+
+    let start_time = Instant::now();
+    assert!(backend.submit_circuit_batch(&[circuit]).is_err());
+    assert!(start_time.elapsed() < Duration::from_secs(10));
+    post.assert();
+}
+
+#[test]
+fn submit_iqm_circuits() {
+    let mut server = mockito::Server::new();
+    let mut device = GarnetDevice::new();
+    device.set_endpoint_url(format!("{}/garnet/jobs", server.url()));
+    let mut backend = Backend::new(device.into(), Some("dummy_token".to_string())).unwrap();
+    backend.set_allow_http(true);
+
+    let prx = IqmInstruction {
+        name: "prx".to_string(),
+        qubits: vec!["QB1".to_string()],
+        args: HashMap::from([
+            ("angle_t".to_string(), CalculatorFloat::Float(0.5)),
+            ("phase_t".to_string(), CalculatorFloat::Float(0.0)),
+        ]),
+    };
+    let measure = IqmInstruction {
+        name: "measure".to_string(),
+        qubits: vec!["QB1".to_string()],
+        args: HashMap::from([("key".to_string(), CalculatorFloat::Str("ro".to_string()))]),
+    };
+    let circuit = IqmCircuit {
+        name: "native".to_string(),
+        instructions: vec![prx.clone(), measure],
+        metadata: None,
+    };
+
+    let post = server
+        .mock("POST", "/garnet/jobs")
+        .match_body(mockito::Matcher::PartialJson(serde_json::json!({
+            "circuits": [{
+                "name": "native",
+                "instructions": [
+                    {"name": "prx", "qubits": ["QB1"]},
+                    {"name": "measure", "qubits": ["QB1"], "args": {"key": "ro"}}
+                ]
+            }],
+            "shots": 100
+        })))
+        .with_status(201)
+        .with_body(r#"{"id": "job_id"}"#)
+        .expect(1)
+        .create();
+
+    let job_id = backend.submit_iqm_circuits(vec![circuit], 100).unwrap();
+    assert_eq!(job_id, "job_id");
+    post.assert();
+
+    let unmeasured_circuit = IqmCircuit {
+        name: "unmeasured".to_string(),
+        instructions: vec![prx],
+        metadata: None,
+    };
+    assert!(matches!(
+        backend.submit_iqm_circuits(vec![unmeasured_circuit], 100),
+        Err(IqmBackendError::InvalidCircuit { .. })
+    ));
+}
+
+#[test]
+fn test_get_gate_fidelities() {
+    let mut server = mockito::Server::new();
+    let mut device = GarnetDevice::new();
+    device.set_endpoint_url(format!("{}/garnet/jobs", server.url()));
+    let mut backend = Backend::new(device.into(), Some("dummy_token".to_string())).unwrap();
+    backend.set_allow_http(true);
+
+    let calibration = server
+        .mock("GET", "/garnet/calibration/metrics/latest")
+        .with_status(200)
+        .with_body(
+            serde_json::json!({
+                "calibration_set_id": "calibration_id",
+                "metrics": {
+                    "prx.QB1.fidelity": 0.999,
+                    "prx.QB2.fidelity": "0.998",
+                    "cz.QB1__QB2.fidelity": 0.98,
+                    "QB1.t1_time": 3.5e-5
+                }
+            })
+            .to_string(),
+        )
+        .create();
+
+    let fidelities = backend.get_gate_fidelities().unwrap();
+    assert_eq!(
+        fidelities,
+        HashMap::from([
+            ("prx.QB1".to_string(), 0.999),
+            ("prx.QB2".to_string(), 0.998),
+            ("cz.QB1__QB2".to_string(), 0.98),
+        ])
+    );
+    calibration.assert();
+    calibration.remove();
+
+    server
+        .mock("GET", "/garnet/calibration/metrics/latest")
+        .with_status(200)
+        .with_body(r#"{"metrics": {"prx.QB1.fidelity": "unknown"}}"#)
+        .create();
+    assert!(matches!(
+        backend.get_gate_fidelities(),
+        Err(RoqoqoBackendError::GenericError { .. })
+    ));
+}
+
+#[test]
+fn test_get_circuit_warnings() {
+    let mut server = mockito::Server::new();
+    let mut device = GarnetDevice::new();
+    device.set_endpoint_url(format!("{}/garnet/jobs", server.url()));
+    let mut backend = Backend::new(device.into(), Some("dummy_token".to_string())).unwrap();
+    backend.set_allow_http(true);
+
+    let circuit = |name: &str| {
+        serde_json::json!({
+            "name": name,
+            "instructions": [{"name": "measure", "qubits": ["QB1"], "args": {"key": "ro"}}],
+            "metadata": {"ro": [[0], 1]}
+        })
+    };
+    let response = serde_json::json!({
+        "status": "ready",
+        "measurements": [{"ro": [[1]]}, {"ro": [[0]]}, {"ro": [[0]]}],
+        "warnings": ["Job-level warning"],
+        "circuit_warnings": [["prx decomposed sub-optimally"], [], ["Long idle time", "Low fidelity"]],
+        "metadata": {
+            "request": {
+                "circuits": [circuit("qc_0"), circuit("qc_1"), circuit("qc_2")],
+                "shots": 1,
+                "heralding_mode": "none"
+            }
+        }
+    });
+    server
+        .mock("GET", "/garnet/jobs/job_id")
+        .with_status(200)
+        .with_body(response.to_string())
+        .create();
+
+    let warnings = backend.get_circuit_warnings("job_id".to_string()).unwrap();
+    assert_eq!(
+        warnings,
+        HashMap::from([
+            (
+                "qc_0".to_string(),
+                vec!["prx decomposed sub-optimally".to_string()]
+            ),
+            (
+                "qc_2".to_string(),
+                vec!["Long idle time".to_string(), "Low fidelity".to_string()]
+            ),
+        ])
+    );
+}
+
+#[test]
+fn test_compile_on_server() {
+    let mut server = mockito::Server::new();
+    let mut device = GarnetDevice::new();
+    device.set_endpoint_url(format!("{}/garnet/jobs", server.url()));
+    let mut backend = Backend::new(device.into(), Some("dummy_token".to_string())).unwrap();
+    backend.set_allow_http(true);
+
+    let schedule = r#"{"schedule": {"QB1__drive.awg": [["IQPulse", 40]]}}"#;
+    let compile = server
+        .mock("POST", "/garnet/jobs")
+        .match_query(mockito::Matcher::UrlEncoded(
+            "compile_only".to_string(),
+            "true".to_string(),
+        ))
+        .match_body(mockito::Matcher::PartialJson(serde_json::json!({
+            "circuits": [{"name": "qc_0"}]
+        })))
+        .with_status(200)
+        .with_body(schedule)
+        .expect(1)
+        .create();
+
+    let mut circuit = Circuit::new();
+    circuit += RotateXY::new(0, PI.into(), 0.0.into());
+    circuit += DefinitionBit::new("ro".to_string(), 1, true);
+    circuit += MeasureQubit::new(0, "ro".to_string(), 0);
+
+    assert_eq!(backend.compile_on_server(&[circuit]).unwrap(), schedule);
+    compile.assert();
+
+    assert!(matches!(
+        backend.compile_on_server(&[Circuit::new()]),
+        Err(RoqoqoBackendError::GenericError { .. })
+    ));
+}
@@ -12,7 +12,10 @@
 
 use qoqo_calculator::CalculatorFloat;
 use roqoqo::{operations::*, Circuit, RoqoqoBackendError};
-use roqoqo_iqm::{call_circuit, call_operation, IqmBackendError, IqmCircuit, IqmInstruction};
+use roqoqo_iqm::{
+    call_circuit, call_operation, iqm_circuit_to_circuit, sanitize_circuit_name, IqmBackendError,
+    IqmCircuit, IqmInstruction, MAX_CIRCUIT_NAME_LENGTH,
+};
 
 use std::collections::HashMap;
 use std::f64::consts::PI;
@@ -61,6 +64,30 @@ use test_case::test_case;
         args: HashMap::new(),
     };
     "SingleExcitationStore")]
+#[test_case(
+    CZQubitResonator::new(3, 1).into(),
+    IqmInstruction {
+        name : "cz".to_string(),
+        qubits: vec!["QB4".to_string(), "COMP_R2".to_string()],
+        args: HashMap::new(),
+    };
+    "CZQubitResonator second resonator")]
+#[test_case(
+    SingleExcitationStore::new(3, 1).into(),
+    IqmInstruction {
+        name : "move".to_string(),
+        qubits: vec!["QB4".to_string(), "COMP_R2".to_string()],
+        args: HashMap::new(),
+    };
+    "SingleExcitationStore second resonator")]
+#[test_case(
+    PragmaStopParallelBlock::new(vec![2, 0, 5], 0.0.into()).into(),
+    IqmInstruction {
+        name : "barrier".to_string(),
+        qubits: vec!["QB3".to_string(), "QB1".to_string(), "QB6".to_string()],
+        args: HashMap::new(),
+    };
+    "PragmaStopParallelBlock")]
 fn test_passing_interface(operation: Operation, instruction: IqmInstruction) {
     let called = call_operation(&operation).unwrap().unwrap();
     assert_eq!(instruction, called);
@@ -69,6 +96,9 @@ fn test_passing_interface(operation: Operation, instruction: IqmInstruction) {
 #[test_case(CNOT::new(0, 1).into(); "CNOT")]
 #[test_case(RotateX::new(0, 1.0.into()).into(); "RotateX")]
 #[test_case(Hadamard::new(0).into(); "Hadamard")]
+#[test_case(RotateZ::new(0, 1.0.into()).into(); "RotateZ")]
+#[test_case(PhaseShiftedControlledZ::new(0, 1, 0.5.into()).into(); "PhaseShiftedControlledZ")]
+#[test_case(ControlledPhaseShift::new(0, 1, 0.5.into()).into(); "ControlledPhaseShift")]
 fn test_failure_unsupported_operation(operation: Operation) {
     let called = call_operation(&operation);
     match called {
@@ -77,6 +107,21 @@ fn test_failure_unsupported_operation(operation: Operation) {
     }
 }
 
+#[test]
+fn test_call_operation_symbolic_parameters() {
+    let operation: Operation = RotateXY::new(0, "amplitude".into(), "phase".into()).into();
+    let instruction = call_operation(&operation).unwrap().unwrap();
+    assert_eq!(
+        instruction.args["angle_t"],
+        CalculatorFloat::from("amplitude") / (2.0 * PI)
+    );
+    assert_eq!(
+        instruction.args["phase_t"],
+        CalculatorFloat::from("phase") / (2.0 * PI)
+    );
+    assert!(!instruction.args["angle_t"].is_float());
+}
+
 #[test]
 fn test_call_circuit_single_measurement() {
     let mut circuit = Circuit::new();
@@ -124,6 +169,50 @@ fn test_call_circuit_single_measurement() {
     assert_eq!(res, res_expected)
 }
 
+#[test]
+fn test_call_circuit_many_measurements() {
+    let mut circuit = Circuit::new();
+    circuit += DefinitionBit::new("ro".to_string(), 100, true);
+    circuit += DefinitionBit::new("other".to_string(), 1, true);
+    circuit += MeasureQubit::new(100, "other".to_string(), 0);
+    // Measurements in reverse qubit order, interleaved with gates
+    for qubit in (0..100).rev() {
+        circuit += RotateXY::new(qubit, PI.into(), 0.0.into());
+        circuit += MeasureQubit::new(qubit, "ro".to_string(), 99 - qubit);
+    }
+    let res = call_circuit(circuit.iter(), 101, None, 0).unwrap().0;
+
+    let measurements: Vec<&IqmInstruction> = res
+        .instructions
+        .iter()
+        .filter(|instruction| instruction.name == "measure")
+        .collect();
+    assert_eq!(measurements.len(), 2);
+    assert_eq!(measurements[0].qubits, vec!["QB101".to_string()]);
+    assert_eq!(
+        measurements[1].qubits,
+        (1..=100)
+            .rev()
+            .map(|qubit| format!("QB{}", qubit))
+            .collect::<Vec<String>>()
+    );
+    assert_eq!(
+        measurements[1].args,
+        HashMap::from([("key".to_string(), CalculatorFloat::from("ro"))])
+    );
+    assert_eq!(
+        res.metadata.unwrap()["ro"],
+        ((0..100).collect::<Vec<usize>>(), 100)
+    );
+
+    // Measuring a qubit twice is still detected
+    circuit += MeasureQubit::new(5, "ro".to_string(), 0);
+    assert!(matches!(
+        call_circuit(circuit.iter(), 101, None, 0),
+        Err(IqmBackendError::InvalidCircuit { .. })
+    ));
+}
+
 #[test]
 fn test_call_circuit_single_measurement_load_store() {
     let mut circuit = Circuit::new();
@@ -188,6 +277,119 @@ fn test_call_circuit_single_measurement_load_store() {
     assert_eq!(res, res_expected)
 }
 
+#[test]
+fn test_call_circuit_virtual_z() {
+    let mut circuit = Circuit::new();
+    circuit += RotateZ::new(0, 0.5.into());
+    circuit += RotateXY::new(0, PI.into(), 0.2.into());
+    circuit += ControlledPauliZ::new(0, 1);
+    circuit += RotateZ::new(0, 0.3.into());
+    circuit += RotateXY::new(0, (PI / 2.0).into(), 0.0.into());
+    circuit += RotateXY::new(1, PI.into(), 0.1.into());
+    circuit += RotateZ::new(1, 0.4.into());
+    let res = call_circuit(circuit.iter(), 2, None, 0).unwrap().0;
+
+    let prx = |qubit: &str, angle_t: f64, phase: f64| IqmInstruction {
+        name: "prx".to_string(),
+        qubits: vec![qubit.to_string()],
+        args: HashMap::from([
+            ("angle_t".to_string(), CalculatorFloat::Float(angle_t)),
+            (
+                "phase_t".to_string(),
+                CalculatorFloat::Float(phase / (2.0 * PI)),
+            ),
+        ]),
+    };
+    // The Z rotations are dropped and subtracted from the phases of the following prx
+    let expected = vec![
+        prx("QB1", 0.5, 0.2 - 0.5),
+        IqmInstruction {
+            name: "cz".to_string(),
+            qubits: vec!["QB1".to_string(), "QB2".to_string()],
+            args: HashMap::new(),
+        },
+        prx("QB1", 0.25, 0.0 - 0.5 - 0.3),
+        prx("QB2", 0.5, 0.1),
+    ];
+    assert_eq!(res.instructions, expected);
+
+    // The Z rotations inside loops are accumulated on every repetition
+    let mut inner_circuit = Circuit::new();
+    inner_circuit += RotateZ::new(0, 0.25.into());
+    inner_circuit += RotateXY::new(0, PI.into(), 0.0.into());
+    let mut circuit = Circuit::new();
+    circuit += PragmaLoop::new(2.0.into(), inner_circuit);
+    let res = call_circuit(circuit.iter(), 1, None, 0).unwrap().0;
+    assert_eq!(
+        res.instructions,
+        vec![prx("QB1", 0.5, -0.25), prx("QB1", 0.5, -0.5)]
+    );
+}
+
+#[test]
+fn test_call_circuit_virtual_z_symbolic() {
+    let mut circuit = Circuit::new();
+    circuit += RotateZ::new(0, "alpha".into());
+    circuit += RotateXY::new(0, PI.into(), "phi".into());
+    let res = call_circuit(circuit.iter(), 1, None, 0).unwrap().0;
+    assert_eq!(
+        res.instructions[0].args["phase_t"],
+        (CalculatorFloat::from("phi") - CalculatorFloat::from("alpha")) / (2.0 * PI)
+    );
+}
+
+#[test]
+fn test_call_circuit_virtual_z_through_resonator() {
+    let mut circuit = Circuit::new();
+    circuit += RotateZ::new(0, 0.5.into());
+    circuit += SingleExcitationStore::new(0, 0);
+    // A Z rotation of the empty qubit only changes the global phase
+    circuit += RotateZ::new(0, 0.7.into());
+    circuit += SingleExcitationLoad::new(1, 0);
+    circuit += RotateXY::new(1, PI.into(), 0.0.into());
+    circuit += RotateXY::new(0, PI.into(), 0.0.into());
+    let res = call_circuit(circuit.iter(), 2, None, 0).unwrap().0;
+
+    assert_eq!(res.instructions.len(), 4);
+    assert_eq!(res.instructions[2].qubits, vec!["QB2".to_string()]);
+    assert_eq!(
+        res.instructions[2].args["phase_t"],
+        CalculatorFloat::Float(-0.5 / (2.0 * PI))
+    );
+    assert_eq!(res.instructions[3].qubits, vec!["QB1".to_string()]);
+    assert_eq!(
+        res.instructions[3].args["phase_t"],
+        CalculatorFloat::Float(-0.7 / (2.0 * PI))
+    );
+}
+
+#[test]
+fn test_call_circuit_barrier() {
+    let mut circuit = Circuit::new();
+    circuit += RotateXY::new(0, PI.into(), 0.0.into());
+    circuit += PragmaStopParallelBlock::new(vec![0, 1], 0.0.into());
+    circuit += PragmaStopParallelBlock::new(vec![], 0.0.into());
+    circuit += ControlledPauliZ::new(1, 2);
+    let res = call_circuit(circuit.iter(), 3, None, 0).unwrap().0;
+
+    let names: Vec<&str> = res
+        .instructions
+        .iter()
+        .map(|instruction| instruction.name.as_str())
+        .collect();
+    assert_eq!(names, vec!["prx", "barrier", "cz"]);
+    assert_eq!(
+        res.instructions[1].qubits,
+        vec!["QB1".to_string(), "QB2".to_string()]
+    );
+
+    let converted = iqm_circuit_to_circuit(&res).unwrap();
+    assert_eq!(
+        converted[1],
+        PragmaStopParallelBlock::new(vec![0, 1], 0.0.into()).into()
+    );
+}
+
 #[test]
 fn test_call_circuit_repeated_measurement_passes() {
     let mut inner_circuit = Circuit::new();
@@ -295,6 +497,26 @@ fn test_call_circuit_repeated_measurement_error() {
     assert!(matches!(err, Err(IqmBackendError::InvalidCircuit { .. })))
 }
 
+// test that the error for an unsupported operation names its position in the circuit
+#[test]
+fn test_call_circuit_unsupported_operation_position() {
+    let mut circuit = Circuit::new();
+
+    circuit += DefinitionBit::new("ro".to_string(), 2, true);
+    circuit += RotateXY::new(0, 1.0.into(), 1.0.into());
+    circuit += RotateXY::new(1, 1.0.into(), 1.0.into());
+    circuit += CNOT::new(0, 1);
+    circuit += MeasureQubit::new(0, "ro".to_string(), 0);
+
+    match call_circuit(circuit.iter(), 2, None, 4) {
+        Err(IqmBackendError::InvalidCircuit { msg }) => assert_eq!(
+            msg,
+            "Circuit 4: operation #3 (CNOT on qubits [0, 1]) is not supported by the backend."
+        ),
+        _ => panic!("Expected an InvalidCircuit error"),
+    }
+}
+
 // test the an error is returned when a measurement operation tries to write to an undefined register
 #[test]
 fn test_call_circuit_undefined_register_error() {
@@ -373,6 +595,82 @@ fn test_call_circuit_repeated_measurements_with_mapping() {
     assert!(ok);
 }
 
+#[test]
+fn test_repeated_measurement_register_too_small() {
+    let mut circuit = Circuit::new();
+    circuit += ControlledPauliZ::new(0, 1);
+    circuit += DefinitionBit::new("ro".to_string(), 2, true);
+    circuit += PragmaRepeatedMeasurement::new("ro".to_string(), 3, None);
+
+    let res = call_circuit(circuit.iter(), 6, None, 1);
+    assert!(matches!(res, Err(IqmBackendError::RegisterTooSmall { .. })));
+
+    let (res, _) = call_circuit(circuit.iter(), 2, None, 1).unwrap();
+    let measure = res.instructions.last().unwrap();
+    assert_eq!(measure.qubits, vec!["QB1".to_string(), "QB2".to_string()]);
+}
+
+#[test]
+fn test_iqm_circuit_to_circuit_round_trip() {
+    let mut circuit = Circuit::new();
+    circuit += DefinitionBit::new("ro".to_string(), 3, true);
+    circuit += RotateXY::new(0, PI.into(), (PI / 2.0).into());
+    circuit += ControlledPauliZ::new(0, 1);
+    circuit += SingleExcitationStore::new(3, 0);
+    circuit += CZQubitResonator::new(2, 0);
+    circuit += SingleExcitationLoad::new(3, 0);
+    circuit += MeasureQubit::new(0, "ro".to_string(), 2);
+    circuit += MeasureQubit::new(1, "ro".to_string(), 0);
+
+    let (iqm_circuit, _) = call_circuit(circuit.iter(), 6, None, 0).unwrap();
+    let converted = iqm_circuit_to_circuit(&iqm_circuit).unwrap();
+    assert_eq!(converted, circuit);
+
+    let (iqm_circuit_converted, _) = call_circuit(converted.iter(), 6, None, 0).unwrap();
+    assert_eq!(iqm_circuit_converted, iqm_circuit);
+}
+
+#[test]
+fn test_iqm_circuit_to_circuit_unknown_instruction() {
+    let iqm_circuit = IqmCircuit {
+        name: "qc_0".to_string(),
+        instructions: vec![IqmInstruction {
+            name: "cc_prx".to_string(),
+            qubits: vec!["QB1".to_string()],
+            args: HashMap::new(),
+        }],
+        metadata: None,
+    };
+    let res = iqm_circuit_to_circuit(&iqm_circuit);
+    assert!(matches!(res, Err(IqmBackendError::InvalidCircuit { .. })));
+}
+
+#[test]
+fn test_measurement_to_non_output_register() {
+    let mut circuit = Circuit::new();
+    circuit += DefinitionBit::new("ro".to_string(), 1, false);
+    circuit += RotateXY::new(0, PI.into(), 0.0.into());
+    circuit += MeasureQubit::new(0, "ro".to_string(), 0);
+
+    match call_circuit(circuit.iter(), 6, None, 0) {
+        Err(IqmBackendError::InvalidCircuit { msg }) => {
+            assert!(msg.contains("not an output register"));
+            assert!(msg.contains("is_output"));
+        }
+        res => panic!("Unexpected result: {:?}", res),
+    }
+}
+
+#[test]
+fn test_measure_qubit_out_of_range() {
+    let mut circuit = Circuit::new();
+    circuit += DefinitionBit::new("ro".to_string(), 1, true);
+    circuit += MeasureQubit::new(10, "ro".to_string(), 0);
+
+    let res = call_circuit(circuit.iter(), 6, None, 1);
+    assert!(matches!(res, Err(IqmBackendError::InvalidCircuit { .. })));
+}
+
 #[test]
 fn test_fail_multiple_repeated_measurements() {
     let mut circuit = Circuit::new();
@@ -396,3 +694,19 @@ fn test_fail_overlapping_measurements() {
 
     assert!(res.is_err());
 }
+
+#[test_case("qc_0", "qc_0"; "valid name")]
+#[test_case("my job/#1 qc_0", "my_job__1_qc_0"; "illegal characters")]
+#[test_case("prép-1.qc_0", "pr_p-1.qc_0"; "non-ascii characters")]
+fn test_sanitize_circuit_name(name: &str, expected: &str) {
+    assert_eq!(sanitize_circuit_name(name), expected);
+}
+
+#[test]
+fn test_sanitize_circuit_name_truncates() {
+    let name = format!("{}qc_0", "a".repeat(MAX_CIRCUIT_NAME_LENGTH));
+    assert_eq!(
+        sanitize_circuit_name(&name),
+        "a".repeat(MAX_CIRCUIT_NAME_LENGTH)
+    );
+}
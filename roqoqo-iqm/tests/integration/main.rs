@@ -13,5 +13,11 @@
 #[cfg(test)]
 mod backend;
 
+#[cfg(all(test, feature = "async"))]
+mod async_backend;
+
 #[cfg(test)]
 mod interface;
+
+#[cfg(test)]
+mod quantum_architecture;
@@ -0,0 +1,128 @@
+// Copyright © 2020-2023 HQS Quantum Simulations GmbH. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the
+// License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either
+// express or implied. See the License for the specific language governing permissions and
+// limitations under the License.
+
+use roqoqo::devices::Device;
+use roqoqo_iqm::{diff_architectures, QuantumArchitecture};
+
+const ARCHITECTURE_RESPONSE: &str = r#"{
+    "quantum_architecture": {
+        "name": "Deneb",
+        "operations": ["prx", "cz", "move", "measure", "barrier"],
+        "qubits": ["COMP_R", "QB1", "QB2", "QB3"],
+        "qubit_connectivity": [["QB1", "COMP_R"], ["QB2", "COMP_R"], ["QB3", "COMP_R"]]
+    }
+}"#;
+
+#[test]
+fn test_parse_quantum_architecture() {
+    let architecture = QuantumArchitecture::from_response(ARCHITECTURE_RESPONSE).unwrap();
+
+    assert_eq!(architecture.name, "Deneb");
+    assert_eq!(architecture.qubits.len(), 4);
+    assert_eq!(
+        architecture.qubit_connectivity[0],
+        ("QB1".to_string(), "COMP_R".to_string())
+    );
+    assert!(architecture.operations.contains(&"move".to_string()));
+
+    assert!(QuantumArchitecture::from_response("{}").is_err());
+}
+
+#[test]
+fn test_to_generic_device() {
+    let architecture = QuantumArchitecture::from_response(ARCHITECTURE_RESPONSE).unwrap();
+    let device = architecture.to_generic_device().unwrap();
+
+    assert_eq!(device.number_qubits(), 3);
+    assert_eq!(device.single_qubit_gate_time("RotateXY", &2), Some(1.0));
+    // The operations between qubits and resonators are not part of the generic device
+    for gate in [
+        "CZQubitResonator",
+        "SingleExcitationLoad",
+        "SingleExcitationStore",
+    ] {
+        assert_eq!(device.single_qubit_gate_time(gate, &1), None);
+        assert_eq!(device.two_qubit_gate_time(gate, &1, &0), None);
+    }
+    assert!(device.two_qubit_edges().is_empty());
+
+    let mut architecture = architecture;
+    architecture.qubits = vec!["QB1".to_string(), "QB2".to_string(), "QB4".to_string()];
+    architecture.qubit_connectivity = vec![
+        ("QB1".to_string(), "QB2".to_string()),
+        ("QB4".to_string(), "QB2".to_string()),
+    ];
+    architecture.operations = vec!["prx".to_string(), "cz".to_string()];
+    let device = architecture.to_generic_device().unwrap();
+
+    assert_eq!(device.number_qubits(), 4);
+    assert_eq!(device.single_qubit_gate_time("RotateXY", &3), Some(1.0));
+    assert_eq!(device.single_qubit_gate_time("RotateXY", &2), None);
+    assert_eq!(device.single_qubit_gate_time("CZQubitResonator", &0), None);
+    assert_eq!(
+        device.two_qubit_gate_time("ControlledPauliZ", &1, &3),
+        Some(1.0)
+    );
+    assert_eq!(
+        device.two_qubit_gate_time("ControlledPauliZ", &0, &1),
+        Some(1.0)
+    );
+    assert_eq!(device.two_qubit_gate_time("ControlledPauliZ", &0, &3), None);
+
+    architecture.qubits.push("XY1".to_string());
+    assert!(architecture.to_generic_device().is_err());
+}
+
+#[test]
+fn test_diff_architectures() {
+    let old = QuantumArchitecture::from_response(ARCHITECTURE_RESPONSE).unwrap();
+    assert!(diff_architectures(&old, &old).is_empty());
+
+    let mut new = old.clone();
+    new.qubit_connectivity.pop();
+    new.qubit_connectivity
+        .push(("COMP_R".to_string(), "QB4".to_string()));
+    new.qubits.push("QB4".to_string());
+    // The orientation of a coupling is irrelevant
+    new.qubit_connectivity[0] = ("COMP_R".to_string(), "QB1".to_string());
+
+    let diff = diff_architectures(&old, &new);
+    assert_eq!(diff.added_qubits, vec!["QB4".to_string()]);
+    assert!(diff.removed_qubits.is_empty());
+    assert_eq!(
+        diff.added_edges,
+        vec![("COMP_R".to_string(), "QB4".to_string())]
+    );
+    assert_eq!(
+        diff.removed_edges,
+        vec![("COMP_R".to_string(), "QB3".to_string())]
+    );
+    assert!(diff.added_operations.is_empty());
+    assert!(diff.removed_operations.is_empty());
+}
+
+#[test]
+fn test_diff_architectures_single_edge() {
+    let old = QuantumArchitecture::from_response(ARCHITECTURE_RESPONSE).unwrap();
+    let mut new = old.clone();
+    new.qubit_connectivity.remove(1);
+    new.operations.retain(|x| x != "barrier");
+
+    let diff = diff_architectures(&old, &new);
+    assert!(!diff.is_empty());
+    assert!(diff.added_edges.is_empty());
+    assert_eq!(
+        diff.removed_edges,
+        vec![("COMP_R".to_string(), "QB2".to_string())]
+    );
+    assert_eq!(diff.removed_operations, vec!["barrier".to_string()]);
+}